@@ -0,0 +1,210 @@
+use clap::{Parser, Subcommand};
+use msgpacker::debug::dump;
+use msgpacker::json::JsonOptions;
+use msgpacker::Tokenizer;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+mod path;
+mod reader;
+mod stats;
+
+use reader::{StreamError, ValueReader};
+use stats::Stats;
+
+/// Inspect and convert MessagePack files.
+///
+/// The input is a stream of concatenated values, read from a file or from stdin. Only one
+/// top-level value is held in memory at a time, so the size of the input is not limited by the
+/// memory, but the size of every single value is.
+#[derive(Parser)]
+#[command(name = "msgpacker", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Converts the values to JSON, one per line.
+    ToJson {
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+        /// Pretty prints the JSON values.
+        #[arg(short, long)]
+        pretty: bool,
+    },
+    /// Packs a stream of JSON values.
+    FromJson {
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+    },
+    /// Prints an annotated hex dump of the values, with offsets from the start of the stream.
+    Dump {
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+    },
+    /// Checks that the input is a well-formed stream of values.
+    Validate {
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+    },
+    /// Extracts the sub-value at a path, such as `users.0.name`, from every value.
+    Get {
+        /// Dot-separated map keys and array indexes.
+        path: String,
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+        /// Writes the packed bytes of the sub-values instead of JSON.
+        #[arg(short, long)]
+        raw: bool,
+    },
+    /// Prints element counts and sizes of the values.
+    Stats {
+        /// Input file; stdin if absent or `-`.
+        input: Option<PathBuf>,
+    },
+}
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn open(input: Option<PathBuf>) -> Result<Box<dyn BufRead>> {
+    Ok(match input {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(File::open(path)?)),
+        _ => Box::new(BufReader::new(io::stdin().lock())),
+    })
+}
+
+/// Reads the next value and its offset, checking its contents, such as the UTF-8 of strings.
+fn next<R>(values: &mut ValueReader<R>) -> Result<Option<(u64, &[u8])>>
+where
+    R: BufRead,
+{
+    let Some((offset, buf)) = values.next_value()? else {
+        return Ok(None);
+    };
+    let mut tokens = Tokenizer::new(buf);
+    while let Some(event) = tokens.next() {
+        event.map_err(|error| StreamError::Format {
+            offset: offset + tokens.offset() as u64,
+            error,
+        })?;
+    }
+    Ok(Some((offset, buf)))
+}
+
+/// Maps an error of the value at `offset` to a [StreamError].
+fn at(offset: u64) -> impl Fn(msgpacker::Error) -> StreamError {
+    move |error| StreamError::Format { offset, error }
+}
+
+fn to_json(input: Option<PathBuf>, pretty: bool, out: &mut impl Write) -> Result<()> {
+    let mut values = ValueReader::new(open(input)?);
+    while let Some((offset, buf)) = next(&mut values)? {
+        let (_, value) = JsonOptions::new().to_json(buf).map_err(at(offset))?;
+        match pretty {
+            true => serde_json::to_writer_pretty(&mut *out, &value)?,
+            false => serde_json::to_writer(&mut *out, &value)?,
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+fn from_json(input: Option<PathBuf>, out: &mut impl Write) -> Result<()> {
+    let values = serde_json::Deserializer::from_reader(open(input)?).into_iter();
+    let mut buf = Vec::new();
+    for value in values {
+        buf.clear();
+        JsonOptions::new().from_json(&value?, &mut buf);
+        out.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+fn dump_values(input: Option<PathBuf>, out: &mut impl Write) -> Result<()> {
+    let mut values = ValueReader::new(open(input)?);
+    for index in 0.. {
+        let res = values.next_value().map(|value| value.is_some());
+        if let Ok(false) = res {
+            break;
+        }
+        // a malformed value is dumped up to the failure, where the error is marked
+        writeln!(out, "# value {index} at offset {:#x}", values.start())?;
+        write!(
+            out,
+            "{}",
+            dump(values.partial()).with_base(values.start() as usize)
+        )?;
+        res?;
+    }
+    Ok(())
+}
+
+fn validate(input: Option<PathBuf>, out: &mut impl Write) -> Result<()> {
+    let mut values = ValueReader::new(open(input)?);
+    let (mut count, mut bytes) = (0u64, 0u64);
+    while let Some((_, buf)) = next(&mut values)? {
+        count += 1;
+        bytes += buf.len() as u64;
+    }
+    writeln!(out, "ok: {count} values, {bytes} bytes")?;
+    Ok(())
+}
+
+fn get(input: Option<PathBuf>, path: &str, raw: bool, out: &mut impl Write) -> Result<bool> {
+    let mut values = ValueReader::new(open(input)?);
+    let mut found = false;
+    while let Some((offset, buf)) = next(&mut values)? {
+        let Some(value) = path::get(buf, path).map_err(at(offset))? else {
+            continue;
+        };
+        found = true;
+        if raw {
+            out.write_all(value)?;
+        } else {
+            let (_, value) = JsonOptions::new().to_json(value).map_err(at(offset))?;
+            serde_json::to_writer(&mut *out, &value)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(found)
+}
+
+fn stats(input: Option<PathBuf>, out: &mut impl Write) -> Result<()> {
+    let mut values = ValueReader::new(open(input)?);
+    let mut stats = Stats::default();
+    while let Some((offset, buf)) = next(&mut values)? {
+        stats.add(buf).map_err(at(offset))?;
+    }
+    write!(out, "{stats}")?;
+    Ok(())
+}
+
+fn run(command: Command) -> Result<bool> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    let res = match command {
+        Command::ToJson { input, pretty } => to_json(input, pretty, &mut out).map(|_| true),
+        Command::FromJson { input } => from_json(input, &mut out).map(|_| true),
+        Command::Dump { input } => dump_values(input, &mut out).map(|_| true),
+        Command::Validate { input } => validate(input, &mut out).map(|_| true),
+        Command::Get { path, input, raw } => get(input, &path, raw, &mut out),
+        Command::Stats { input } => stats(input, &mut out).map(|_| true),
+    };
+    out.flush()?;
+    res
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use msgpacker::{Error, Token, Tokenizer};
+
+/// Skips a complete value, including its nested elements.
+pub fn skip(tokens: &mut Tokenizer<'_>) -> Result<(), Error> {
+    let mut pending = 1u64;
+    while pending > 0 {
+        let event = tokens.next().ok_or(Error::BufferTooShort)??;
+        pending = pending - 1
+            + match event.token {
+                Token::ArrayStart(len) => len as u64,
+                Token::MapStart(len) => 2 * len as u64,
+                _ => 0,
+            };
+    }
+    Ok(())
+}
+
+/// Returns the bytes of the sub-value at the path, or `None` if it doesn't exist.
+///
+/// The path is a list of map keys and array indexes separated by dots, such as `users.0.name`. A
+/// segment matches a string key with the same contents, or an integer key with the same decimal
+/// representation.
+pub fn get<'a>(mut buf: &'a [u8], path: &str) -> Result<Option<&'a [u8]>, Error> {
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let mut tokens = Tokenizer::new(buf);
+        let event = tokens.next().ok_or(Error::BufferTooShort)??;
+        let found = match event.token {
+            Token::ArrayStart(len) => match segment.parse::<usize>() {
+                Ok(index) if index < len => {
+                    for _ in 0..index {
+                        skip(&mut tokens)?;
+                    }
+                    true
+                }
+                _ => false,
+            },
+            Token::MapStart(len) => {
+                let mut found = false;
+                for _ in 0..len {
+                    let key = tokens.clone().next().ok_or(Error::BufferTooShort)??;
+                    skip(&mut tokens)?;
+                    found = match key.token {
+                        Token::Str(s) => s == segment,
+                        Token::Uint(v) => segment.parse() == Ok(v),
+                        Token::Int(v) => segment.parse() == Ok(v),
+                        _ => false,
+                    };
+                    if found {
+                        break;
+                    }
+                    skip(&mut tokens)?;
+                }
+                found
+            }
+            _ => false,
+        };
+        if !found {
+            return Ok(None);
+        }
+        let start = tokens.offset();
+        skip(&mut tokens)?;
+        buf = &buf[start..tokens.offset()];
+    }
+    Ok(Some(buf))
+}
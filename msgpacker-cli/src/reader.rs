@@ -0,0 +1,113 @@
+use msgpacker::{Error, ValueScanner};
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// Failure while reading a stream of packed values.
+#[derive(Debug)]
+pub enum StreamError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The input is malformed at the given offset of the stream.
+    Format {
+        /// Offset of the failure, in bytes, from the start of the stream.
+        offset: u64,
+        /// Protocol error.
+        error: Error,
+    },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "{e}"),
+            StreamError::Format { offset, error } => write!(f, "offset {offset:#x}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+/// Reads concatenated packed values from a reader, one complete value at a time.
+///
+/// Only the bytes of the current value are kept in memory, so streams larger than the memory can
+/// be processed as long as every single value fits.
+pub struct ValueReader<R> {
+    reader: R,
+    scanner: ValueScanner,
+    start: u64,
+    buf: Vec<u8>,
+}
+
+impl<R> ValueReader<R>
+where
+    R: BufRead,
+{
+    /// Creates a new reader at the start of the stream.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            scanner: ValueScanner::new(),
+            start: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Offset of the last value in the stream.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Bytes of the last value read, or read up to the failure if the last call to
+    /// [ValueReader::next_value] failed.
+    pub fn partial(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Reads the next value, returning its offset in the stream and its bytes, or `None` at the
+    /// end of the stream.
+    ///
+    /// The value is checked for a valid structure; the contents, such as the UTF-8 of strings,
+    /// are not.
+    pub fn next_value(&mut self) -> Result<Option<(u64, &[u8])>, StreamError> {
+        self.start += self.buf.len() as u64;
+        self.buf.clear();
+
+        loop {
+            let chunk = self.reader.fill_buf()?;
+            if chunk.is_empty() {
+                return match self.buf.is_empty() {
+                    true => Ok(None),
+                    false => Err(StreamError::Format {
+                        offset: self.start + self.buf.len() as u64,
+                        error: Error::BufferTooShort,
+                    }),
+                };
+            }
+            let (n, done) = match self.scanner.feed(chunk) {
+                Ok(Some(n)) => (n, true),
+                Ok(None) => (chunk.len(), false),
+                Err(error) => {
+                    let offset = self.scanner.len();
+                    // the failing tag is kept, to be marked by a dump of the partial value
+                    let n = offset - self.buf.len() + 1;
+                    self.buf.extend_from_slice(&chunk[..n]);
+                    return Err(StreamError::Format {
+                        offset: self.start + offset as u64,
+                        error,
+                    });
+                }
+            };
+            self.buf.extend_from_slice(&chunk[..n]);
+            self.reader.consume(n);
+            if done {
+                return Ok(Some((self.start, &self.buf)));
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use msgpacker::{Error, Token, Tokenizer};
+use std::fmt;
+
+/// Element counts and sizes of a stream of packed values.
+#[derive(Debug, Default)]
+pub struct Stats {
+    values: u64,
+    bytes: u64,
+    nil: u64,
+    bool: u64,
+    uint: u64,
+    int: u64,
+    float: u64,
+    str: u64,
+    bin: u64,
+    array: u64,
+    map: u64,
+    ext: u64,
+    max_depth: u64,
+    max_str: u64,
+    max_bin: u64,
+    max_array: u64,
+    max_map: u64,
+}
+
+impl Stats {
+    /// Adds the elements of a single packed value.
+    pub fn add(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.values += 1;
+        self.bytes += buf.len() as u64;
+
+        // pending elements of each open container
+        let mut open: Vec<usize> = Vec::new();
+        for event in Tokenizer::new(buf) {
+            let token = event?.token;
+            if let Some(pending) = open.last_mut() {
+                *pending -= 1;
+            }
+            let children = match token {
+                Token::Nil => {
+                    self.nil += 1;
+                    0
+                }
+                Token::Bool(_) => {
+                    self.bool += 1;
+                    0
+                }
+                Token::Uint(_) => {
+                    self.uint += 1;
+                    0
+                }
+                Token::Int(_) => {
+                    self.int += 1;
+                    0
+                }
+                Token::F32(_) | Token::F64(_) => {
+                    self.float += 1;
+                    0
+                }
+                Token::Str(s) => {
+                    self.str += 1;
+                    self.max_str = self.max_str.max(s.len() as u64);
+                    0
+                }
+                Token::Bin(b) => {
+                    self.bin += 1;
+                    self.max_bin = self.max_bin.max(b.len() as u64);
+                    0
+                }
+                Token::Ext(..) => {
+                    self.ext += 1;
+                    0
+                }
+                Token::ArrayStart(len) => {
+                    self.array += 1;
+                    self.max_array = self.max_array.max(len as u64);
+                    len
+                }
+                Token::MapStart(len) => {
+                    self.map += 1;
+                    self.max_map = self.max_map.max(len as u64);
+                    2 * len
+                }
+            };
+            if children > 0 {
+                open.push(children);
+                self.max_depth = self.max_depth.max(open.len() as u64);
+            }
+            while open.last() == Some(&0) {
+                open.pop();
+            }
+        }
+        if !open.is_empty() {
+            return Err(Error::BufferTooShort);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("values", self.values),
+            ("bytes", self.bytes),
+            ("nil", self.nil),
+            ("bool", self.bool),
+            ("uint", self.uint),
+            ("int", self.int),
+            ("float", self.float),
+            ("str", self.str),
+            ("bin", self.bin),
+            ("array", self.array),
+            ("map", self.map),
+            ("ext", self.ext),
+            ("max depth", self.max_depth),
+            ("max str len", self.max_str),
+            ("max bin len", self.max_bin),
+            ("max array len", self.max_array),
+            ("max map len", self.max_map),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "{name:<14}{value:>12}")?;
+        }
+        Ok(())
+    }
+}
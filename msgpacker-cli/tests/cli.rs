@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_msgpacker"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(args: &[&str], input: &[u8]) -> String {
+    let output = run(args, input);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn json_round_trip() {
+    let json = "{\"a\":[1,-2,3.5,null,true,\"x\"]}\n[]\n\"b\"\n";
+    let packed = run(&["from-json"], json.as_bytes()).stdout;
+    assert_eq!(
+        vec![0x81, 0xa1, b'a', 0x96, 0x01, 0xfe],
+        packed[..6].to_vec()
+    );
+    assert_eq!(json, stdout(&["to-json", "-"], &packed));
+}
+
+#[test]
+fn json_bins_and_extensions() {
+    // bin8 [1, 2, 3], fixext1 type=5 [7], fixmap {1: nil}
+    let packed = [0xc4, 3, 1, 2, 3, 0xd4, 5, 7, 0x81, 0x01, 0xc0];
+    let json = "\"AQID\"\n{\"$ext\":5,\"data\":\"Bw==\"}\n{\"1\":null}\n";
+    assert_eq!(json, stdout(&["to-json"], &packed));
+    assert_eq!(
+        vec![0xa4, b'A', b'Q', b'I', b'D', 0xd4, 5, 7],
+        run(&["from-json"], b"\"AQID\" {\"$ext\":5,\"data\":\"Bw==\"}").stdout
+    );
+}
+
+#[test]
+fn get_sub_values() {
+    let packed = run(
+        &["from-json"],
+        b"{\"users\":[{\"name\":\"ana\"},{\"name\":\"bo\"}]} {\"users\":[]}",
+    )
+    .stdout;
+    assert_eq!("\"bo\"\n", stdout(&["get", "users.1.name"], &packed));
+    assert_eq!(
+        "[{\"name\":\"ana\"},{\"name\":\"bo\"}]\n[]\n",
+        stdout(&["get", ".users"], &packed)
+    );
+    assert_eq!(
+        vec![0xa3, b'a', b'n', b'a'],
+        run(&["get", "-r", "users.0.name"], &packed).stdout
+    );
+    assert!(!run(&["get", "users.2"], &packed).status.success());
+}
+
+#[test]
+fn errors_carry_offsets() {
+    let output = run(&["validate"], &[0x01, 0x92, 0x01]);
+    assert!(!output.status.success());
+    assert_eq!(
+        "error: offset 0x3: unexpected end of input\n",
+        String::from_utf8(output.stderr).unwrap()
+    );
+
+    let output = run(&["validate"], &[0x01, 0x92, 0xa1, 0xff, 0x01]);
+    assert_eq!(
+        "error: offset 0x2: invalid UTF-8 string\n",
+        String::from_utf8(output.stderr).unwrap()
+    );
+
+    let output = run(&["dump"], &[0x01, 0x93, 0x01, 0xc1]);
+    assert_eq!(
+        "\
+# value 0 at offset 0x0
+0000  01                          positive fixint 1
+# value 1 at offset 0x1
+0001  93                          fixarray len=3
+0002  01                            positive fixint 1
+0003  c1  <- error: unexpected format tag
+",
+        String::from_utf8(output.stdout).unwrap()
+    );
+    assert_eq!(
+        "error: offset 0x3: unexpected format tag\n",
+        String::from_utf8(output.stderr).unwrap()
+    );
+}
+
+#[test]
+fn validate_and_stats() {
+    let packed = run(&["from-json"], b"[1, \"ab\", {\"c\": [null]}] 7").stdout;
+    assert_eq!("ok: 2 values, 11 bytes\n", stdout(&["validate"], &packed));
+    let stats = stdout(&["stats"], &packed);
+    assert!(stats.contains("values                   2\n"));
+    assert!(stats.contains("max depth                3\n"));
+    assert!(stats.contains("uint                     2\n"));
+}
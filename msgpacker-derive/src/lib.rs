@@ -4,14 +4,16 @@ extern crate proc_macro;
 // This code is bad and should be refactored into something cleaner. Maybe some syn-based
 // framework?
 
+mod service;
+
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::punctuated::Punctuated;
 use syn::{
-    parse_macro_input, parse_quote, parse_str, Block, Data, DataEnum, DataStruct, DataUnion,
-    DeriveInput, Expr, ExprMatch, ExprTuple, Field, FieldPat, FieldValue, Fields, FieldsNamed,
-    FieldsUnnamed, GenericArgument, Ident, Member, Meta, Pat, PatIdent, PathArguments, Token, Type,
-    Variant,
+    parse_macro_input, parse_quote, parse_str, Attribute, Block, Data, DataEnum, DataStruct,
+    DataUnion, DeriveInput, Expr, ExprMatch, ExprTuple, Field, FieldPat, FieldValue, Fields,
+    FieldsNamed, FieldsUnnamed, GenericArgument, Generics, Ident, ImplItemFn, ItemTrait, Lifetime,
+    Member, Meta, MetaNameValue, Pat, PatIdent, Path, PathArguments, Token, Type, Variant,
 };
 
 fn contains_attribute(field: &Field, name: &str) -> bool {
@@ -34,13 +36,278 @@ fn contains_attribute(field: &Field, name: &str) -> bool {
     false
 }
 
-fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
+/// Structs borrowing from the input buffer implement `UnpackableBorrowed` instead of `Unpackable`.
+fn borrowed_lifetime(generics: &Generics) -> Option<Lifetime> {
+    generics.lifetimes().next().map(|l| l.lifetime.clone())
+}
+
+/// Borrowing is supported for structs with a single lifetime parameter that aren't extensions.
+fn check_borrowed(input: &DeriveInput, ext: &Option<Expr>) -> syn::Result<()> {
+    let mut lifetimes = input.generics.lifetimes();
+    let Some(lifetime) = lifetimes.next() else {
+        return Ok(());
+    };
+    if let Some(lifetime) = lifetimes.next() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "derive supports at most one lifetime parameter; implement the traits manually",
+        ));
+    }
+    if ext.is_some() {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "extension types can't borrow from the buffer; implement the traits manually",
+        ));
+    }
+    if let Data::Enum(_) = input.data {
+        return Err(syn::Error::new_spanned(
+            lifetime,
+            "enums can't borrow from the buffer; implement the traits manually",
+        ));
+    }
+    Ok(())
+}
+
+fn unpack_path(lifetime: &Option<Lifetime>) -> Path {
+    match lifetime {
+        Some(_) => parse_quote! { ::msgpacker::UnpackableBorrowed::unpack_borrowed },
+        None => parse_quote! { ::msgpacker::Unpackable::unpack },
+    }
+}
+
+fn impl_unpackable(
+    name: &Ident,
+    generics: &Generics,
+    lifetime: Option<Lifetime>,
+    detailed: bool,
+    block_unpackable: Block,
+    block_unpackable_iter: Block,
+) -> impl quote::ToTokens {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let error = error_type(detailed);
+    let error_context = error_context(detailed);
+    match lifetime {
+        Some(lifetime) => quote! {
+            impl #impl_generics ::msgpacker::UnpackableBorrowed<#lifetime> for #name #ty_generics #where_clause {
+                type Error = #error;
+
+                fn unpack_borrowed(mut buf: &#lifetime [u8]) -> Result<(usize, Self), Self::Error>
+                    #block_unpackable
+            }
+        },
+        None => quote! {
+            impl #impl_generics ::msgpacker::Unpackable for #name #ty_generics #where_clause {
+                type Error = #error;
+
+                fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error>
+                    #block_unpackable
+
+                fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+                where
+                    I: IntoIterator<Item = u8>,
+                    #block_unpackable_iter
+
+                #error_context
+            }
+        },
+    }
+}
+
+/// Structs with the attribute `#[msgpacker(ext = 7)]` are packed as the payload of an extension.
+fn ext_type(attrs: &[Attribute]) -> Option<Expr> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("msgpacker"))
+        .filter_map(|attr| attr.parse_args::<MetaNameValue>().ok())
+        .find(|meta| meta.path.is_ident("ext"))
+        .map(|meta| meta.value)
+}
+
+/// Types with the attribute `#[msgpacker(detailed_errors)]` unpack with a `DetailedError`, that
+/// carries the byte offset, format tag and field path of the failure.
+fn detailed_errors(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("msgpacker"))
+        .filter_map(|attr| attr.parse_args::<Path>().ok())
+        .any(|path| path.is_ident("detailed_errors"))
+}
+
+fn error_type(detailed: bool) -> Type {
+    match detailed {
+        true => parse_quote! { ::msgpacker::DetailedError },
+        false => parse_quote! { ::msgpacker::Error },
+    }
+}
+
+/// Forwards the location collected by the containers of the type to its detailed error.
+fn error_context(detailed: bool) -> Option<ImplItemFn> {
+    detailed.then(|| {
+        parse_quote! {
+            fn error_context(error: Self::Error, context: ::msgpacker::Context) -> Self::Error {
+                ::msgpacker::ErrorContext::with_context(error, context)
+            }
+        }
+    })
+}
+
+/// Attaches the location of a failing field to its error, for types with detailed errors.
+///
+/// Otherwise the error is converted by `?`, and the location is dropped.
+fn field_context(detailed: bool, iter: bool, field: &str, unpacked: Expr) -> Expr {
+    match (detailed, iter) {
+        (false, _) => unpacked,
+        (true, false) => parse_quote! {
+            #unpacked.map_err(|e| {
+                use ::msgpacker::ErrorContext as _;
+                ::msgpacker::DetailedError::from(e).with_location(buf, n).with_field(#field)
+            })
+        },
+        (true, true) => parse_quote! {
+            #unpacked.map_err(|e| {
+                use ::msgpacker::ErrorContext as _;
+                ::msgpacker::DetailedError::from(e).with_offset(n).with_field(#field)
+            })
+        },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn impl_struct(
+    name: &Ident,
+    generics: &Generics,
+    lifetime: Option<Lifetime>,
+    ext: Option<Expr>,
+    detailed: bool,
+    block_packable: Block,
+    block_packable_canonical: Block,
+    block_packed_len: Block,
+    block_unpackable: Block,
+    block_unpackable_iter: Block,
+) -> impl Into<TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let Some(ext) = ext else {
+        let unpackable = impl_unpackable(
+            name,
+            generics,
+            lifetime,
+            detailed,
+            block_unpackable,
+            block_unpackable_iter,
+        );
+        return quote! {
+            impl #impl_generics ::msgpacker::Packable for #name #ty_generics #where_clause {
+                fn pack<T>(&self, buf: &mut T) -> usize
+                where
+                    T: Extend<u8>,
+                    #block_packable
+
+                fn pack_canonical<T>(&self, buf: &mut T) -> usize
+                where
+                    T: Extend<u8>,
+                    #block_packable_canonical
+
+                fn packed_len(&self) -> usize
+                    #block_packed_len
+            }
+
+            #unpackable
+        };
+    };
+
+    let error = error_type(detailed);
+    let error_context = error_context(detailed);
+    quote! {
+        impl #impl_generics ::msgpacker::ExtensionType for #name #ty_generics #where_clause {
+            const EXT_TYPE: i8 = #ext;
+
+            type Error = #error;
+
+            fn pack_payload<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+                #block_packable
+
+            fn pack_payload_canonical<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+                #block_packable_canonical
+
+            fn payload_len(&self) -> usize
+                #block_packed_len
+
+            fn unpack_payload(mut buf: &[u8]) -> Result<(usize, Self), Self::Error>
+                #block_unpackable
+
+            fn unpack_payload_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+            where
+                I: IntoIterator<Item = u8>,
+                #block_unpackable_iter
+
+            #error_context
+        }
+
+        impl #impl_generics ::msgpacker::Packable for #name #ty_generics #where_clause {
+            fn pack<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+            {
+                ::msgpacker::pack_ext(buf, self)
+            }
+
+            fn pack_canonical<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+            {
+                ::msgpacker::pack_ext_canonical(buf, self)
+            }
+
+            fn packed_len(&self) -> usize {
+                ::msgpacker::packed_len_ext(self)
+            }
+        }
+
+        impl #impl_generics ::msgpacker::Unpackable for #name #ty_generics #where_clause {
+            type Error = #error;
+
+            fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+                ::msgpacker::unpack_ext(buf)
+            }
+
+            fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+            where
+                I: IntoIterator<Item = u8>,
+            {
+                ::msgpacker::unpack_ext_iter(bytes)
+            }
+
+            #error_context
+        }
+    }
+}
+
+fn impl_fields_named(
+    name: Ident,
+    generics: Generics,
+    ext: Option<Expr>,
+    detailed: bool,
+    f: FieldsNamed,
+) -> impl Into<TokenStream> {
+    let lifetime = borrowed_lifetime(&generics);
+    let unpack = unpack_path(&lifetime);
     let mut values: Punctuated<FieldValue, Token![,]> = Punctuated::new();
     let block_packable: Block = parse_quote! {
         {
             let mut n = 0;
         }
     };
+    let mut block_packable_canonical = block_packable.clone();
+    let block_packed_len: Block = parse_quote! {
+        {
+            let mut n = 0;
+        }
+    };
     let block_unpackable: Block = parse_quote! {
         {
             let mut n = 0;
@@ -53,26 +320,46 @@ fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
         }
     };
 
-    let (
-        mut block_packable,
-        mut block_unpackable,
-        mut block_unpackable_iter
-    ) = f.named.into_pairs().map(|p| p.into_value()).fold(
-            (block_packable, block_unpackable, block_unpackable_iter),
-            |(mut block_packable, mut block_unpackable, mut block_unpackable_iter), field| {
+    let (mut block_packable, mut block_packed_len, mut block_unpackable, mut block_unpackable_iter) =
+        f.named.into_pairs().map(|p| p.into_value()).fold(
+            (
+                block_packable,
+                block_packed_len,
+                block_unpackable,
+                block_unpackable_iter,
+            ),
+            |(
+                mut block_packable,
+                mut block_packed_len,
+                mut block_unpackable,
+                mut block_unpackable_iter,
+            ),
+             field| {
                 let ident = field.ident.as_ref().cloned().unwrap();
+                let field_name = ident.to_string();
                 let ty = field.ty.clone();
 
                 let mut is_vec = false;
                 let mut is_vec_u8 = false;
 
                 match &ty {
-                    Type::Path(p) if p.path.segments.last().filter(|p| p.ident.to_string() == "Vec").is_some() => {
+                    Type::Path(p)
+                        if p.path
+                            .segments
+                            .last()
+                            .filter(|p| p.ident.to_string() == "Vec")
+                            .is_some() =>
+                    {
                         is_vec = true;
                         match &p.path.segments.last().unwrap().arguments {
                             PathArguments::AngleBracketed(a) if a.args.len() == 1 => {
                                 if let Some(GenericArgument::Type(Type::Path(p))) = a.args.first() {
-                                    if p.path.segments.last().filter(|p| p.ident.to_string() == "u8").is_some() {
+                                    if p.path
+                                        .segments
+                                        .last()
+                                        .filter(|p| p.ident.to_string() == "u8")
+                                        .is_some()
+                                    {
                                         is_vec_u8 = true;
                                     }
                                 }
@@ -89,61 +376,218 @@ fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
                         n += ::msgpacker::pack_map(buf, &self.#ident);
                     });
 
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_map_canonical(buf, &self.#ident);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_map(&self.#ident);
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        false,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_map(buf).map(|(nv, t)| {
+                                n += nv;
+                                buf = &buf[nv..];
+                                t
+                            })
+                        },
+                    );
                     block_unpackable.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::unpack_map(buf).map(|(nv, t)| {
-                            n += nv;
-                            buf = &buf[nv..];
-                            t
-                        })?;
+                        let #ident = #unpacked?;
                     });
 
+                    let unpacked = field_context(
+                        detailed,
+                        true,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_map_iter(bytes.by_ref()).map(|(nv, t)| {
+                                n += nv;
+                                t
+                            })
+                        },
+                    );
                     block_unpackable_iter.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::unpack_map_iter(bytes.by_ref()).map(|(nv, t)| {
-                            n += nv;
-                            t
-                        })?;
+                        let #ident = #unpacked?;
                     });
                 } else if contains_attribute(&field, "array") || is_vec && !is_vec_u8 {
                     block_packable.stmts.push(parse_quote! {
                         n += ::msgpacker::pack_array(buf, &self.#ident);
                     });
 
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_array_canonical(buf, &self.#ident);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_array(&self.#ident);
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        false,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_array(buf).map(|(nv, t)| {
+                                n += nv;
+                                buf = &buf[nv..];
+                                t
+                            })
+                        },
+                    );
                     block_unpackable.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::unpack_array(buf).map(|(nv, t)| {
-                            n += nv;
-                            buf = &buf[nv..];
-                            t
-                        })?;
+                        let #ident = #unpacked?;
                     });
 
+                    let unpacked = field_context(
+                        detailed,
+                        true,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_array_iter(bytes.by_ref()).map(|(nv, t)| {
+                                n += nv;
+                                t
+                            })
+                        },
+                    );
                     block_unpackable_iter.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::unpack_array_iter(bytes.by_ref()).map(|(nv, t)| {
-                            n += nv;
-                            t
-                        })?;
+                        let #ident = #unpacked?;
+                    });
+                } else if contains_attribute(&field, "timestamp") {
+                    block_packable.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_timestamp(buf, &self.#ident);
+                    });
+
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_timestamp(buf, &self.#ident);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_timestamp(&self.#ident);
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        false,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_timestamp(buf).map(|(nv, t)| {
+                                n += nv;
+                                buf = &buf[nv..];
+                                t
+                            })
+                        },
+                    );
+                    block_unpackable.stmts.push(parse_quote! {
+                        let #ident = #unpacked?;
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        true,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_timestamp_iter(bytes.by_ref()).map(|(nv, t)| {
+                                n += nv;
+                                t
+                            })
+                        },
+                    );
+                    block_unpackable_iter.stmts.push(parse_quote! {
+                        let #ident = #unpacked?;
+                    });
+                } else if contains_attribute(&field, "bin") {
+                    block_packable.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_bin(buf, &self.#ident);
+                    });
+
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_bin(buf, &self.#ident);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_bin(&self.#ident);
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        false,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_bin(buf).map(|(nv, t)| {
+                                n += nv;
+                                buf = &buf[nv..];
+                                t
+                            })
+                        },
+                    );
+                    block_unpackable.stmts.push(parse_quote! {
+                        let #ident = #unpacked?;
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        true,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::unpack_bin_iter(bytes.by_ref()).map(|(nv, t)| {
+                                n += nv;
+                                t
+                            })
+                        },
+                    );
+                    block_unpackable_iter.stmts.push(parse_quote! {
+                        let #ident = #unpacked?;
                     });
                 } else {
                     block_packable.stmts.push(parse_quote! {
                         n += <#ty as ::msgpacker::Packable>::pack(&self.#ident, buf);
                     });
 
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += <#ty as ::msgpacker::Packable>::pack_canonical(&self.#ident, buf);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += <#ty as ::msgpacker::Packable>::packed_len(&self.#ident);
+                    });
+
+                    let unpacked = field_context(
+                        detailed,
+                        false,
+                        &field_name,
+                        parse_quote! {
+                            #unpack(buf).map(|(nv, t)| {
+                                n += nv;
+                                buf = &buf[nv..];
+                                t
+                            })
+                        },
+                    );
                     block_unpackable.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::Unpackable::unpack(buf).map(|(nv, t)| {
-                            n += nv;
-                            buf = &buf[nv..];
-                            t
-                        })?;
+                        let #ident = #unpacked?;
                     });
 
+                    let unpacked = field_context(
+                        detailed,
+                        true,
+                        &field_name,
+                        parse_quote! {
+                            ::msgpacker::Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
+                                n += nv;
+                                t
+                            })
+                        },
+                    );
                     block_unpackable_iter.stmts.push(parse_quote! {
-                        let #ident = ::msgpacker::Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
-                            n += nv;
-                            t
-                        })?;
+                        let #ident = #unpacked?;
                     });
                 }
 
-
                 values.push(FieldValue {
                     attrs: vec![],
                     member: Member::Named(ident.clone()),
@@ -151,7 +595,12 @@ fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
                     expr: parse_quote! { #ident },
                 });
 
-                (block_packable, block_unpackable, block_unpackable_iter)
+                (
+                    block_packable,
+                    block_packed_len,
+                    block_unpackable,
+                    block_unpackable_iter,
+                )
             },
         );
 
@@ -159,6 +608,14 @@ fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
         return n;
     });
 
+    block_packable_canonical.stmts.push(parse_quote! {
+        return n;
+    });
+
+    block_packed_len.stmts.push(parse_quote! {
+        return n;
+    });
+
     block_unpackable.stmts.push(parse_quote! {
         return Ok((
             n,
@@ -177,35 +634,41 @@ fn impl_fields_named(name: Ident, f: FieldsNamed) -> impl Into<TokenStream> {
         ));
     });
 
-    quote! {
-        impl ::msgpacker::Packable for #name {
-            fn pack<T>(&self, buf: &mut T) -> usize
-            where
-                T: Extend<u8>,
-                #block_packable
-        }
-
-        impl ::msgpacker::Unpackable for #name {
-            type Error = ::msgpacker::Error;
-
-            fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error>
-                #block_unpackable
-
-            fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-            where
-                I: IntoIterator<Item = u8>,
-                #block_unpackable_iter
-        }
-    }
+    impl_struct(
+        &name,
+        &generics,
+        lifetime,
+        ext,
+        detailed,
+        block_packable,
+        block_packable_canonical,
+        block_packed_len,
+        block_unpackable,
+        block_unpackable_iter,
+    )
 }
 
-fn impl_fields_unnamed(name: Ident, f: FieldsUnnamed) -> impl Into<TokenStream> {
+fn impl_fields_unnamed(
+    name: Ident,
+    generics: Generics,
+    ext: Option<Expr>,
+    detailed: bool,
+    f: FieldsUnnamed,
+) -> impl Into<TokenStream> {
+    let lifetime = borrowed_lifetime(&generics);
+    let unpack = unpack_path(&lifetime);
     let mut values: Punctuated<Expr, Token![,]> = Punctuated::new();
     let block_packable: Block = parse_quote! {
         {
             let mut n = 0;
         }
     };
+    let mut block_packable_canonical = block_packable.clone();
+    let block_packed_len: Block = parse_quote! {
+        {
+            let mut n = 0;
+        }
+    };
     let block_unpackable: Block = parse_quote! {
         {
             let mut n = 0;
@@ -218,46 +681,127 @@ fn impl_fields_unnamed(name: Ident, f: FieldsUnnamed) -> impl Into<TokenStream>
         }
     };
 
-    let (mut block_packable, mut block_unpackable, mut block_unpackable_iter) = f
+    let (mut block_packable, mut block_packed_len, mut block_unpackable, mut block_unpackable_iter) = f
         .unnamed
         .into_pairs()
         .map(|p| p.into_value())
         .enumerate()
         .fold(
-            (block_packable, block_unpackable, block_unpackable_iter),
-            |(mut block_packable, mut block_unpackable, mut block_unpackable_iter), (i, field)| {
+            (block_packable, block_packed_len, block_unpackable, block_unpackable_iter),
+            |(mut block_packable, mut block_packed_len, mut block_unpackable, mut block_unpackable_iter), (i, field)| {
                 let ty = field.ty.clone();
                 let var: Expr = parse_str(format!("v{}", i).as_str()).unwrap();
+                let field_name = i.to_string();
                 let slf: Expr = parse_str(format!("self.{}", i).as_str()).unwrap();
 
                 if contains_attribute(&field, "map") {
                     todo!("unnamed map is not implemented for derive macro; implement the traits manually")
                 } else if contains_attribute(&field, "array") {
                     todo!("unnamed array is not implemented for derive macro; implement the traits manually")
-                } else {
+                } else if contains_attribute(&field, "timestamp") {
                     block_packable.stmts.push(parse_quote! {
-                        n += <#ty as ::msgpacker::Packable>::pack(&#slf, buf);
+                        n += ::msgpacker::pack_timestamp(buf, &#slf);
                     });
 
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_timestamp(buf, &#slf);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_timestamp(&#slf);
+                    });
+
+                    let unpacked = field_context(detailed, false, &field_name, parse_quote! {
+                        ::msgpacker::unpack_timestamp(buf).map(|(nv, t)| {
+                            n += nv;
+                            buf = &buf[nv..];
+                            t
+                        })
+                    });
                     block_unpackable.stmts.push(parse_quote! {
-                        let #var = ::msgpacker::Unpackable::unpack(buf).map(|(nv, t)| {
+                        let #var = #unpacked?;
+                    });
+
+                    let unpacked = field_context(detailed, true, &field_name, parse_quote! {
+                        ::msgpacker::unpack_timestamp_iter(bytes.by_ref()).map(|(nv, t)| {
+                            n += nv;
+                            t
+                        })
+                    });
+                    block_unpackable_iter.stmts.push(parse_quote! {
+                        let #var = #unpacked?;
+                    });
+                } else if contains_attribute(&field, "bin") {
+                    block_packable.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_bin(buf, &#slf);
+                    });
+
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += ::msgpacker::pack_bin(buf, &#slf);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += ::msgpacker::packed_len_bin(&#slf);
+                    });
+
+                    let unpacked = field_context(detailed, false, &field_name, parse_quote! {
+                        ::msgpacker::unpack_bin(buf).map(|(nv, t)| {
                             n += nv;
                             buf = &buf[nv..];
                             t
-                        })?;
+                        })
+                    });
+                    block_unpackable.stmts.push(parse_quote! {
+                        let #var = #unpacked?;
                     });
 
+                    let unpacked = field_context(detailed, true, &field_name, parse_quote! {
+                        ::msgpacker::unpack_bin_iter(bytes.by_ref()).map(|(nv, t)| {
+                            n += nv;
+                            t
+                        })
+                    });
                     block_unpackable_iter.stmts.push(parse_quote! {
-                        let #var = ::msgpacker::Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
+                        let #var = #unpacked?;
+                    });
+                } else {
+                    block_packable.stmts.push(parse_quote! {
+                        n += <#ty as ::msgpacker::Packable>::pack(&#slf, buf);
+                    });
+
+                    block_packable_canonical.stmts.push(parse_quote! {
+                        n += <#ty as ::msgpacker::Packable>::pack_canonical(&#slf, buf);
+                    });
+
+                    block_packed_len.stmts.push(parse_quote! {
+                        n += <#ty as ::msgpacker::Packable>::packed_len(&#slf);
+                    });
+
+                    let unpacked = field_context(detailed, false, &field_name, parse_quote! {
+                        #unpack(buf).map(|(nv, t)| {
+                            n += nv;
+                            buf = &buf[nv..];
+                            t
+                        })
+                    });
+                    block_unpackable.stmts.push(parse_quote! {
+                        let #var = #unpacked?;
+                    });
+
+                    let unpacked = field_context(detailed, true, &field_name, parse_quote! {
+                        ::msgpacker::Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
                             n += nv;
                             t
-                        })?;
+                        })
+                    });
+                    block_unpackable_iter.stmts.push(parse_quote! {
+                        let #var = #unpacked?;
                     });
                 }
 
                 values.push(var);
 
-                (block_packable, block_unpackable, block_unpackable_iter)
+                (block_packable, block_packed_len, block_unpackable, block_unpackable_iter)
             },
         );
 
@@ -265,6 +809,14 @@ fn impl_fields_unnamed(name: Ident, f: FieldsUnnamed) -> impl Into<TokenStream>
         return n;
     });
 
+    block_packable_canonical.stmts.push(parse_quote! {
+        return n;
+    });
+
+    block_packed_len.stmts.push(parse_quote! {
+        return n;
+    });
+
     block_unpackable.stmts.push(parse_quote! {
         return Ok((n, Self(#values)));
     });
@@ -273,29 +825,23 @@ fn impl_fields_unnamed(name: Ident, f: FieldsUnnamed) -> impl Into<TokenStream>
         return Ok((n, Self(#values)));
     });
 
-    quote! {
-        impl ::msgpacker::Packable for #name {
-            fn pack<T>(&self, buf: &mut T) -> usize
-            where
-                T: Extend<u8>,
-                #block_packable
-        }
-
-        impl ::msgpacker::Unpackable for #name {
-            type Error = ::msgpacker::Error;
-
-            fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error>
-                #block_unpackable
-
-            fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-            where
-                I: IntoIterator<Item = u8>,
-                #block_unpackable_iter
-        }
-    }
+    impl_struct(
+        &name,
+        &generics,
+        lifetime,
+        ext,
+        detailed,
+        block_packable,
+        block_packable_canonical,
+        block_packed_len,
+        block_unpackable,
+        block_unpackable_iter,
+    )
 }
 
-fn impl_fields_unit(name: Ident) -> impl Into<TokenStream> {
+fn impl_fields_unit(name: Ident, detailed: bool) -> impl Into<TokenStream> {
+    let error = error_type(detailed);
+    let error_context = error_context(detailed);
     quote! {
         impl ::msgpacker::Packable for #name {
             fn pack<T>(&self, _buf: &mut T) -> usize
@@ -304,10 +850,14 @@ fn impl_fields_unit(name: Ident) -> impl Into<TokenStream> {
             {
                 0
             }
+
+            fn packed_len(&self) -> usize {
+                0
+            }
         }
 
         impl ::msgpacker::Unpackable for #name {
-            type Error = ::msgpacker::Error;
+            type Error = #error;
 
             fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
                 Ok((0, Self))
@@ -319,11 +869,40 @@ fn impl_fields_unit(name: Ident) -> impl Into<TokenStream> {
             {
                 Ok((0, Self))
             }
+
+            #error_context
         }
     }
 }
 
-fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into<TokenStream> {
+/// Returns the packing, canonical packing, packed length, unpacking and iterator unpacking of an
+/// enum variant field bound to `binding`; fields with the attribute `#[msgpacker(bin)]` are packed
+/// as bin.
+fn variant_field(field: &Field, binding: &Expr) -> (Expr, Expr, Expr, Path, Path) {
+    if contains_attribute(field, "bin") {
+        (
+            parse_quote! { ::msgpacker::pack_bin(buf, #binding) },
+            parse_quote! { ::msgpacker::pack_bin(buf, #binding) },
+            parse_quote! { ::msgpacker::packed_len_bin(#binding) },
+            parse_quote! { ::msgpacker::unpack_bin },
+            parse_quote! { ::msgpacker::unpack_bin_iter },
+        )
+    } else {
+        (
+            parse_quote! { #binding.pack(buf) },
+            parse_quote! { #binding.pack_canonical(buf) },
+            parse_quote! { #binding.packed_len() },
+            parse_quote! { Unpackable::unpack },
+            parse_quote! { Unpackable::unpack_iter },
+        )
+    }
+}
+
+fn impl_fields_enum(
+    name: Ident,
+    detailed: bool,
+    v: Punctuated<Variant, Token![,]>,
+) -> impl Into<TokenStream> {
     if v.is_empty() {
         todo!("empty enum is not implemented for derive macro; implement the traits manually");
     }
@@ -333,6 +912,16 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
         }
     };
 
+    let mut block_packable_canonical: ExprMatch = parse_quote! {
+        match self {
+        }
+    };
+
+    let mut block_packed_len: ExprMatch = parse_quote! {
+        match self {
+        }
+    };
+
     let mut block_unpackable: ExprMatch = parse_quote! {
         match discriminant {
         }
@@ -349,11 +938,13 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
             .map(|(_, d)| d)
             .unwrap_or_else(|| parse_str(format!("{}", i).as_str()).unwrap());
 
-        // TODO check attributes of the field
+        // TODO check the attributes of the fields other than bin
         let ident = v.ident.clone();
         match v.fields {
             Fields::Named(f) => {
                 let mut blk: Block = parse_str("{}").unwrap();
+                let mut blk_canonical: Block = parse_str("{}").unwrap();
+                let mut blk_len: Block = parse_str("{}").unwrap();
                 let mut blk_unpack: Block = parse_str("{}").unwrap();
                 let mut blk_unpack_iter: Block = parse_str("{}").unwrap();
                 let mut blk_unpack_fields: Punctuated<FieldValue, Token![,]> = Punctuated::new();
@@ -362,29 +953,55 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
                     n += (#discriminant as u32).pack(buf);
                 });
 
+                blk_canonical.stmts.push(parse_quote! {
+                    n += (#discriminant as u32).pack_canonical(buf);
+                });
+
+                blk_len.stmts.push(parse_quote! {
+                    n += (#discriminant as u32).packed_len();
+                });
+
                 f.named
                     .iter()
-                    .filter_map(|n| n.ident.as_ref())
-                    .for_each(|field| {
+                    .filter_map(|f| f.ident.as_ref().map(|ident| (f, ident)))
+                    .for_each(|(f, field)| {
+                        let field_name = field.to_string();
+                        let (pack, pack_canonical, packed_len, unpack, unpack_iter) =
+                            variant_field(f, &parse_quote! { #field });
+
                         blk.stmts.push(parse_quote! {
-                            n += #field.pack(buf);
+                            n += #pack;
+                        });
+
+                        blk_canonical.stmts.push(parse_quote! {
+                            n += #pack_canonical;
+                        });
+
+                        blk_len.stmts.push(parse_quote! {
+                            n += #packed_len;
                         });
 
                         blk_unpack_fields.push(parse_quote! { #field });
 
-                        blk_unpack.stmts.push(parse_quote! {
-                            let #field = Unpackable::unpack(buf).map(|(nv, t)| {
+                        let unpacked = field_context(detailed, false, &field_name, parse_quote! {
+                            #unpack(buf).map(|(nv, t)| {
                                 n += nv;
                                 buf = &buf[nv..];
                                 t
-                            })?;
+                            })
+                        });
+                        blk_unpack.stmts.push(parse_quote! {
+                            let #field = #unpacked?;
                         });
 
-                        blk_unpack_iter.stmts.push(parse_quote! {
-                            let #field = Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
+                        let unpacked = field_context(detailed, true, &field_name, parse_quote! {
+                            #unpack_iter(bytes.by_ref()).map(|(nv, t)| {
                                 n += nv;
                                 t
-                            })?;
+                            })
+                        });
+                        blk_unpack_iter.stmts.push(parse_quote! {
+                            let #field = #unpacked?;
                         });
                     });
 
@@ -392,32 +1009,44 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
                     #name::#ident {} => #blk,
                 };
 
+                let mut arm_canonical: syn::Arm = parse_quote! {
+                    #name::#ident {} => #blk_canonical,
+                };
+
+                let mut arm_len: syn::Arm = parse_quote! {
+                    #name::#ident {} => #blk_len,
+                };
+
                 f.named
                     .iter()
                     .filter_map(|n| n.ident.as_ref())
                     .for_each(|field| {
-                        match &mut arm.pat {
-                            Pat::Struct(s) => {
-                                s.fields.push(FieldPat {
-                                    attrs: vec![],
-                                    member: Member::Named(field.clone()),
-                                    colon_token: None,
-                                    pat: Box::new(Pat::Ident(PatIdent {
+                        for arm in [&mut arm, &mut arm_canonical, &mut arm_len] {
+                            match &mut arm.pat {
+                                Pat::Struct(s) => {
+                                    s.fields.push(FieldPat {
                                         attrs: vec![],
-                                        by_ref: None,
-                                        mutability: None,
-                                        ident: field.clone(),
-                                        subpat: None,
-                                    })),
-                                });
+                                        member: Member::Named(field.clone()),
+                                        colon_token: None,
+                                        pat: Box::new(Pat::Ident(PatIdent {
+                                            attrs: vec![],
+                                            by_ref: None,
+                                            mutability: None,
+                                            ident: field.clone(),
+                                            subpat: None,
+                                        })),
+                                    });
+                                }
+                                _ => todo!(
+                                    "enum variant is not implemented for derive macro; implement the traits manually"
+                                ),
                             }
-                            _ => todo!(
-                                "enum variant is not implemented for derive macro; implement the traits manually"
-                            ),
                         }
                     });
 
                 block_packable.arms.push(arm);
+                block_packable_canonical.arms.push(arm_canonical);
+                block_packed_len.arms.push(arm_len);
 
                 blk_unpack.stmts.push(parse_quote! {
                     slf = #name::#ident { #blk_unpack_fields };
@@ -438,6 +1067,8 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
 
             Fields::Unnamed(f) => {
                 let mut blk: Block = parse_str("{}").unwrap();
+                let mut blk_canonical: Block = parse_str("{}").unwrap();
+                let mut blk_len: Block = parse_str("{}").unwrap();
                 let mut blk_unpack: Block = parse_str("{}").unwrap();
                 let mut blk_unpack_iter: Block = parse_str("{}").unwrap();
 
@@ -445,28 +1076,53 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
                     n += (#discriminant as u32).pack(buf);
                 });
 
+                blk_canonical.stmts.push(parse_quote! {
+                    n += (#discriminant as u32).pack_canonical(buf);
+                });
+
+                blk_len.stmts.push(parse_quote! {
+                    n += (#discriminant as u32).packed_len();
+                });
+
                 let mut tuple_arm: ExprTuple = parse_str("()").unwrap();
-                f.unnamed.iter().enumerate().for_each(|(ii, _field)| {
+                f.unnamed.iter().enumerate().for_each(|(ii, field)| {
                     let ti: Expr = parse_str(format!("t{}", ii).as_str()).unwrap();
+                    let field_name = ii.to_string();
+                    let (pack, pack_canonical, packed_len, unpack, unpack_iter) =
+                        variant_field(field, &ti);
                     tuple_arm.elems.push(ti.clone());
 
                     blk.stmts.push(parse_quote! {
-                        n += #ti.pack(buf);
+                        n += #pack;
                     });
 
-                    blk_unpack.stmts.push(parse_quote! {
-                        let #ti = Unpackable::unpack(buf).map(|(nv, t)| {
+                    blk_canonical.stmts.push(parse_quote! {
+                        n += #pack_canonical;
+                    });
+
+                    blk_len.stmts.push(parse_quote! {
+                        n += #packed_len;
+                    });
+
+                    let unpacked = field_context(detailed, false, &field_name, parse_quote! {
+                        #unpack(buf).map(|(nv, t)| {
                             n += nv;
                             buf = &buf[nv..];
                             t
-                        })?;
+                        })
+                    });
+                    blk_unpack.stmts.push(parse_quote! {
+                        let #ti = #unpacked?;
                     });
 
-                    blk_unpack_iter.stmts.push(parse_quote! {
-                        let #ti = Unpackable::unpack_iter(bytes.by_ref()).map(|(nv, t)| {
+                    let unpacked = field_context(detailed, true, &field_name, parse_quote! {
+                        #unpack_iter(bytes.by_ref()).map(|(nv, t)| {
                             n += nv;
                             t
-                        })?;
+                        })
+                    });
+                    blk_unpack_iter.stmts.push(parse_quote! {
+                        let #ti = #unpacked?;
                     });
                 });
 
@@ -482,6 +1138,14 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
                     #name::#ident #tuple_arm => #blk,
                 });
 
+                block_packable_canonical.arms.push(parse_quote! {
+                    #name::#ident #tuple_arm => #blk_canonical,
+                });
+
+                block_packed_len.arms.push(parse_quote! {
+                    #name::#ident #tuple_arm => #blk_len,
+                });
+
                 block_unpackable.arms.push(parse_quote! {
                     #discriminant => #blk_unpack,
                 });
@@ -498,6 +1162,18 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
                     }
                 });
 
+                block_packable_canonical.arms.push(parse_quote! {
+                    #name::#ident => {
+                        n += (#discriminant as u32).pack_canonical(buf);
+                    }
+                });
+
+                block_packed_len.arms.push(parse_quote! {
+                    #name::#ident => {
+                        n += (#discriminant as u32).packed_len();
+                    }
+                });
+
                 block_unpackable.arms.push(parse_quote! {
                     #discriminant => slf = #name::#ident,
                 });
@@ -511,16 +1187,18 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
 
     block_unpackable.arms.push(parse_quote! {
         _ => {
-            return Err(::msgpacker::Error::InvalidEnumVariant);
+            return Err(::msgpacker::Error::InvalidEnumVariant.into());
         }
     });
 
     block_unpackable_iter.arms.push(parse_quote! {
         _ => {
-            return Err(::msgpacker::Error::InvalidEnumVariant);
+            return Err(::msgpacker::Error::InvalidEnumVariant.into());
         }
     });
 
+    let error = error_type(detailed);
+    let error_context = error_context(detailed);
     quote! {
         impl ::msgpacker::Packable for #name {
             fn pack<T>(&self, buf: &mut T) -> usize
@@ -533,10 +1211,29 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
 
                 return n;
             }
+
+            fn pack_canonical<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+            {
+                let mut n = 0;
+
+                #block_packable_canonical;
+
+                return n;
+            }
+
+            fn packed_len(&self) -> usize {
+                let mut n = 0;
+
+                #block_packed_len;
+
+                return n;
+            }
         }
 
         impl ::msgpacker::Unpackable for #name {
-            type Error = ::msgpacker::Error;
+            type Error = #error;
 
             #[allow(unused_mut)]
             fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
@@ -561,6 +1258,8 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
 
                 Ok((n, slf))
             }
+
+            #error_context
         }
     }
 }
@@ -569,25 +1268,39 @@ fn impl_fields_enum(name: Ident, v: Punctuated<Variant, Token![,]>) -> impl Into
 pub fn msg_packer(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
+    let ext = ext_type(&input.attrs);
+    if let Err(e) = check_borrowed(&input, &ext) {
+        return e.to_compile_error().into();
+    }
+
     let name = input.ident;
+    let generics = input.generics;
+    let detailed = detailed_errors(&input.attrs);
     let data = input.data;
     match data {
         Data::Struct(DataStruct {
             fields: Fields::Named(f),
             ..
-        }) => impl_fields_named(name, f).into(),
+        }) => impl_fields_named(name, generics, ext, detailed, f).into(),
 
         Data::Struct(DataStruct {
             fields: Fields::Unnamed(f),
             ..
-        }) => impl_fields_unnamed(name, f).into(),
+        }) => impl_fields_unnamed(name, generics, ext, detailed, f).into(),
+
+        _ if ext.is_some() => syn::Error::new_spanned(
+            name,
+            "extension types must be structs with fields; implement the traits manually",
+        )
+        .to_compile_error()
+        .into(),
 
         Data::Struct(DataStruct {
             fields: Fields::Unit,
             ..
-        }) => impl_fields_unit(name).into(),
+        }) => impl_fields_unit(name, detailed).into(),
 
-        Data::Enum(DataEnum { variants, .. }) => impl_fields_enum(name, variants).into(),
+        Data::Enum(DataEnum { variants, .. }) => impl_fields_enum(name, detailed, variants).into(),
 
         Data::Union(DataUnion { .. }) => {
             todo!(
@@ -596,3 +1309,19 @@ pub fn msg_packer(input: TokenStream) -> TokenStream {
         }
     }
 }
+
+/// Generates a typed client and a dispatcher for an RPC service declared as a trait.
+///
+/// Every method must take `&self` and owned arguments, that are sent as the params tuple of the
+/// call. A method returning `Result<T, E>` answers with `T` as the result or `E` as the error;
+/// any other return type is always a result. Arguments, results and errors are each wrapped in
+/// `msgpacker_rpc::Array`, so types that pack as several values can be used as is.
+///
+/// The trait gains an `into_dispatcher` method that serves an implementation, and a
+/// `{Trait}Client` wrapper is generated with a method per call for the blocking and tokio
+/// clients of `msgpacker-rpc`.
+#[proc_macro_attribute]
+pub fn service(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemTrait);
+    service::expand(item).into()
+}
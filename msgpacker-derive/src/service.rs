@@ -0,0 +1,246 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, FnArg, GenericArgument, Ident, ItemTrait, Pat, PathArguments, ReturnType,
+    TraitItem, TraitItemFn, Type,
+};
+
+/// A method of the service, as seen by the generated client and dispatcher.
+struct Method {
+    ident: Ident,
+    name: String,
+    args: Vec<(Ident, Type)>,
+    output: Type,
+    /// Error type of a method returning a `Result`, returned as the error of the call.
+    error: Option<Type>,
+}
+
+/// Splits a `Result<T, E>` return type into `T` and `E`.
+fn result_types(ty: &Type) -> Option<(Type, Type)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    });
+    match (types.next(), types.next(), types.next()) {
+        (Some(ok), Some(err), None) => Some((ok, err)),
+        _ => None,
+    }
+}
+
+fn method(f: &TraitItemFn) -> syn::Result<Method> {
+    let sig = &f.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "service methods can't be async; implement the dispatcher manually",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "service methods can't be generic; implement the dispatcher manually",
+        ));
+    }
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => (),
+        Some(receiver @ FnArg::Receiver(_)) => {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "service methods must take `&self`; implement the dispatcher manually",
+            ))
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                sig,
+                "service methods must take `&self`; implement the dispatcher manually",
+            ))
+        }
+    }
+    let args = inputs
+        .enumerate()
+        .map(|(i, arg)| {
+            let FnArg::Typed(arg) = arg else {
+                unreachable!("the receiver is the first argument")
+            };
+            if let Type::Reference(_) = *arg.ty {
+                return Err(syn::Error::new_spanned(
+                    &arg.ty,
+                    "service arguments must be owned types; implement the dispatcher manually",
+                ));
+            }
+            let ident = match &*arg.pat {
+                Pat::Ident(p) => p.ident.clone(),
+                _ => format_ident!("arg{}", i),
+            };
+            Ok((ident, (*arg.ty).clone()))
+        })
+        .collect::<syn::Result<_>>()?;
+    let (output, error) = match &sig.output {
+        ReturnType::Default => (parse_quote!(()), None),
+        ReturnType::Type(_, ty) => match result_types(ty) {
+            Some((ok, err)) => (ok, Some(err)),
+            None => ((**ty).clone(), None),
+        },
+    };
+    Ok(Method {
+        ident: sig.ident.clone(),
+        name: sig.ident.to_string(),
+        args,
+        output,
+        error,
+    })
+}
+
+/// Generates the client methods of the service, awaiting the calls if `asyncness` is set.
+///
+/// Arguments, results and errors are wrapped in `Array`, so their wire shape depends only on
+/// their types.
+fn client_methods(methods: &[Method], asyncness: bool) -> impl ToTokens {
+    let methods = methods.iter().map(|m| {
+        let Method {
+            ident, name, args, ..
+        } = m;
+        let (idents, types): (Vec<_>, Vec<_>) = args.iter().cloned().unzip();
+        let output = &m.output;
+        let (ret, map) = match &m.error {
+            None => (quote!(#output), quote!(result)),
+            Some(error) => (
+                quote!(::core::result::Result<#output, #error>),
+                quote!(fallible),
+            ),
+        };
+        let params =
+            quote!(::msgpacker_rpc::__private::params((#(::msgpacker_rpc::Array(&#idents),)*)));
+        let (asyncness, call) = match asyncness {
+            true => (
+                quote!(async),
+                quote!(self.client.call_raw(#name, #params?).await),
+            ),
+            false => (quote!(), quote!(self.client.call_raw(#name, #params?))),
+        };
+        let doc = format!("Calls the remote `{name}` method.");
+        quote! {
+            #[doc = #doc]
+            pub #asyncness fn #ident(&self, #(#idents: #types),*)
+                -> ::core::result::Result<#ret, ::msgpacker_rpc::Error>
+            {
+                ::msgpacker_rpc::__private::#map(#call)
+            }
+        }
+    });
+    quote!(#(#methods)*)
+}
+
+pub fn expand(mut item: ItemTrait) -> impl Into<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &item.generics,
+            "generic services are not implemented; implement the dispatcher manually",
+        )
+        .to_compile_error();
+    }
+    let methods = item
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Fn(f) => Some(method(f)),
+            _ => None,
+        })
+        .collect::<syn::Result<Vec<_>>>();
+    let methods = match methods {
+        Ok(methods) => methods,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let registrations = methods.iter().map(|m| {
+        let Method {
+            ident, name, args, ..
+        } = m;
+        let (idents, types): (Vec<_>, Vec<_>) = args.iter().cloned().unzip();
+        let call = match &m.error {
+            None => quote! {
+                ::core::result::Result::Ok::<_, ()>(
+                    ::msgpacker_rpc::Array(__service.#ident(#(#idents),*))
+                )
+            },
+            Some(_) => quote! {
+                __service
+                    .#ident(#(#idents),*)
+                    .map(::msgpacker_rpc::Array)
+                    .map_err(::msgpacker_rpc::Array)
+            },
+        };
+        let pattern = quote!((#(::msgpacker_rpc::Array(#idents),)*));
+        let params = quote!((#(::msgpacker_rpc::Array<#types>,)*));
+        quote! {
+            let __service = ::std::sync::Arc::clone(&__shared);
+            __dispatcher.register(#name, move |#pattern: #params| #call);
+        }
+    });
+    item.items.push(parse_quote! {
+        /// Creates a dispatcher that serves the methods of the service to remote clients.
+        fn into_dispatcher(self) -> ::msgpacker_rpc::Dispatcher
+        where
+            Self: Sized + Send + Sync + 'static,
+        {
+            let __shared = ::std::sync::Arc::new(self);
+            let mut __dispatcher = ::msgpacker_rpc::Dispatcher::new();
+            #(#registrations)*
+            __dispatcher
+        }
+    });
+
+    let vis = &item.vis;
+    let client = format_ident!("{}Client", item.ident);
+    let client_doc = format!("Typed client of the [{}] service.", item.ident);
+    let blocking = client_methods(&methods, false);
+    let tokio = client_methods(&methods, true);
+    quote! {
+        #item
+
+        #[doc = #client_doc]
+        #[derive(Debug)]
+        #vis struct #client<C> {
+            client: C,
+        }
+
+        impl<C> #client<C> {
+            /// Wraps a connected RPC client.
+            pub fn new(client: C) -> Self {
+                Self { client }
+            }
+
+            /// Returns the wrapped RPC client.
+            pub fn into_inner(self) -> C {
+                self.client
+            }
+        }
+
+        impl<W> #client<::msgpacker_rpc::blocking::Client<W>>
+        where
+            W: ::std::io::Write,
+        {
+            #blocking
+        }
+
+        ::msgpacker_rpc::__if_tokio! {
+            impl<W> #client<::msgpacker_rpc::tokio::Client<W>>
+            where
+                W: ::msgpacker_rpc::__private::AsyncWrite + ::core::marker::Unpin,
+            {
+                #tokio
+            }
+        }
+    }
+}
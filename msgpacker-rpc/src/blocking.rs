@@ -0,0 +1,176 @@
+//! Client and server over blocking [Read] and [Write] streams.
+
+use crate::codec::{Calls, Decoder, CHUNK, MAX_MESSAGE_LEN};
+use crate::params::params_to_vec;
+use crate::{
+    unpack_value, Dispatcher, Error, Message, Notification, RemoteError, Request, ToParams,
+};
+use msgpacker::{Packable, Unpackable};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+
+type Sender = mpsc::Sender<Result<Vec<u8>, Vec<u8>>>;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads the next message, or `None` at the end of the stream.
+fn read<R>(reader: &mut R, decoder: &mut Decoder) -> Result<Option<Message>, Error>
+where
+    R: Read,
+{
+    let mut chunk = [0u8; CHUNK];
+    loop {
+        if let Some(message) = decoder.next()? {
+            return Ok(Some(message));
+        }
+        let n = match reader.read(&mut chunk) {
+            Ok(0) => return decoder.finish().map(|_| None),
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        decoder.extend(&chunk[..n]);
+    }
+}
+
+fn write<W>(writer: &mut W, message: &Message) -> Result<(), Error>
+where
+    W: Write,
+{
+    writer.write_all(&message.pack_to_vec())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Client of a remote end, correlating responses with calls by message id.
+///
+/// Calls can be made concurrently from multiple threads; each one blocks until its own response
+/// arrives, regardless of the order in which the remote end answers them.
+#[derive(Debug)]
+pub struct Client<W> {
+    writer: Mutex<W>,
+    next_id: AtomicU32,
+    calls: Arc<Mutex<Calls<Sender>>>,
+}
+
+impl<W> Client<W>
+where
+    W: Write,
+{
+    /// Creates a new client that writes to `writer`, and spawns a thread that reads the
+    /// responses from `reader`, of up to [MAX_MESSAGE_LEN] bytes.
+    ///
+    /// The thread runs until the end of `reader` or the first error reading it. The calls in
+    /// flight and the later calls then fail with that error, or with [Error::Closed] at the end
+    /// of `reader`.
+    pub fn new<R>(reader: R, writer: W) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        Self::with_max_message_len(reader, writer, MAX_MESSAGE_LEN)
+    }
+
+    /// Creates a new client like [Client::new], that fails with [Error::MessageTooLarge] when a
+    /// response is larger than `max_message_len` bytes.
+    pub fn with_max_message_len<R>(reader: R, writer: W, max_message_len: usize) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let receiver = Arc::clone(&calls);
+        thread::spawn(move || receive(reader, receiver, max_message_len));
+        Self {
+            writer: Mutex::new(writer),
+            next_id: AtomicU32::new(0),
+            calls,
+        }
+    }
+
+    /// Calls a remote method, waiting for its result.
+    pub fn call<P, R>(&self, method: &str, params: P) -> Result<R, Error>
+    where
+        P: ToParams,
+        R: Unpackable,
+        msgpacker::Error: From<R::Error>,
+    {
+        let params = params_to_vec(&params)?;
+        let result = self.call_raw(method, params)?;
+        Ok(unpack_value(&result)?)
+    }
+
+    /// Calls a remote method with the packed params, returning the packed result.
+    pub fn call_raw(&self, method: &str, params: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = mpsc::channel();
+        lock(&self.calls).insert(id, sender)?;
+        let request = Request {
+            id,
+            method: method.into(),
+            params,
+        };
+        if let Err(e) = write(&mut *lock(&self.writer), &request.into()) {
+            lock(&self.calls).remove(id);
+            return Err(e);
+        }
+        match receiver.recv() {
+            Ok(result) => result.map_err(|e| RemoteError(e).into()),
+            Err(_) => Err(lock(&self.calls).error()),
+        }
+    }
+
+    /// Sends a notification to a remote method.
+    pub fn notify<P>(&self, method: &str, params: P) -> Result<(), Error>
+    where
+        P: ToParams,
+    {
+        let notification = Notification {
+            method: method.into(),
+            params: params_to_vec(&params)?,
+        };
+        write(&mut *lock(&self.writer), &notification.into())
+    }
+}
+
+fn receive<R>(mut reader: R, calls: Arc<Mutex<Calls<Sender>>>, max_message_len: usize)
+where
+    R: Read,
+{
+    let mut decoder = Decoder::new(max_message_len);
+    // a malformed stream can't be resynchronized, so it ends the connection as well
+    let error = loop {
+        match read(&mut reader, &mut decoder) {
+            Ok(Some(Message::Response(response))) => {
+                if let Some(sender) = lock(&calls).remove(response.id) {
+                    sender.send(response.result).ok();
+                }
+            }
+            Ok(Some(_)) => (),
+            Ok(None) => break Error::Closed,
+            Err(e) => break e,
+        }
+    };
+    lock(&calls).close(error);
+}
+
+/// Serves the messages read from `reader` with the dispatcher, writing the responses to
+/// `writer`.
+///
+/// Requests are handled in the order they arrive. Returns at the end of `reader`, or fails with
+/// [Error::MessageTooLarge] on a message larger than [Dispatcher::max_message_len].
+pub fn serve<R, W>(dispatcher: &Dispatcher, mut reader: R, mut writer: W) -> Result<(), Error>
+where
+    R: Read,
+    W: Write,
+{
+    let mut decoder = Decoder::new(dispatcher.max_message_len());
+    while let Some(message) = read(&mut reader, &mut decoder)? {
+        if let Some(response) = dispatcher.handle(message) {
+            write(&mut writer, &response.into())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::{Error, Message};
+use msgpacker::{Unpackable, ValueScanner};
+use std::collections::HashMap;
+
+/// Size of the chunks read from the stream.
+pub(crate) const CHUNK: usize = 4096;
+
+/// Default maximum length of a received message, in bytes.
+pub const MAX_MESSAGE_LEN: usize = 1 << 24;
+
+/// Splits the bytes read from a stream into messages.
+///
+/// The bytes of an incomplete message are scanned once, as they arrive, and the read messages are
+/// dropped from the buffer before the next bytes are appended.
+#[derive(Debug)]
+pub(crate) struct Decoder {
+    buf: Vec<u8>,
+    start: usize,
+    scanned: usize,
+    scanner: ValueScanner,
+    max_len: usize,
+}
+
+impl Decoder {
+    /// Creates a decoder of messages up to `max_len` bytes.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            scanned: 0,
+            scanner: ValueScanner::new(),
+            max_len,
+        }
+    }
+
+    /// Appends bytes read from the stream.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buf.drain(..self.start);
+        self.scanned -= self.start;
+        self.start = 0;
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete message, or `None` if more bytes are needed.
+    ///
+    /// Fails with [Error::MessageTooLarge] as soon as the message exceeds the maximum length.
+    pub fn next(&mut self) -> Result<Option<Message>, Error> {
+        let Some(n) = self.scanner.feed(&self.buf[self.scanned..])? else {
+            self.scanned = self.buf.len();
+            self.check(self.scanned - self.start)?;
+            return Ok(None);
+        };
+        let end = self.scanned + n;
+        self.check(end - self.start)?;
+        let message = Message::unpack_exact(&self.buf[self.start..end])?;
+        self.start = end;
+        self.scanned = end;
+        Ok(Some(message))
+    }
+
+    /// Checks the end of the stream, failing if it cuts a message.
+    pub fn finish(&self) -> Result<(), Error> {
+        match self.start == self.buf.len() {
+            true => Ok(()),
+            false => Err(msgpacker::Error::BufferTooShort.into()),
+        }
+    }
+
+    fn check(&self, len: usize) -> Result<(), Error> {
+        match len > self.max_len {
+            true => Err(Error::MessageTooLarge { max: self.max_len }),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Senders of the responses to the calls in flight, by message id.
+///
+/// Once the connection is closed, the calls fail with the error that closed it.
+#[derive(Debug)]
+pub(crate) struct Calls<S> {
+    senders: HashMap<u32, S>,
+    closed: Option<Error>,
+}
+
+impl<S> Default for Calls<S> {
+    fn default() -> Self {
+        Self {
+            senders: HashMap::new(),
+            closed: None,
+        }
+    }
+}
+
+impl<S> Calls<S> {
+    /// Adds a call in flight, failing if the connection is closed.
+    pub fn insert(&mut self, id: u32, sender: S) -> Result<(), Error> {
+        match &self.closed {
+            Some(e) => Err(e.duplicate()),
+            None => {
+                self.senders.insert(id, sender);
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the call in flight with the id.
+    pub fn remove(&mut self, id: u32) -> Option<S> {
+        self.senders.remove(&id)
+    }
+
+    /// Returns the error that closed the connection, or [Error::Closed] if it is still open.
+    pub fn error(&self) -> Error {
+        self.closed.as_ref().map_or(Error::Closed, Error::duplicate)
+    }
+
+    /// Drops the senders of the calls in flight, and refuses new calls with the error.
+    pub fn close(&mut self, error: Error) {
+        self.closed.get_or_insert(error);
+        self.senders.clear();
+    }
+}
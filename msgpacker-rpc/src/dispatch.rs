@@ -0,0 +1,125 @@
+use crate::value::single_value;
+use crate::{pack_value, FromParams, Message, Response, MAX_MESSAGE_LEN};
+use msgpacker::Packable;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Packs a result or an error, or returns the error to answer with if it isn't a single value.
+fn packed<T>(value: &T) -> Result<Vec<u8>, Vec<u8>>
+where
+    T: Packable,
+{
+    let mut buf = Vec::new();
+    pack_value(value, &mut buf);
+    match single_value(&buf) {
+        Ok(()) => Ok(buf),
+        Err(e) => Err(format!("invalid response: {e}").pack_to_vec()),
+    }
+}
+
+/// Handler of a method, that maps the packed params to the packed result or error.
+pub type Handler = dyn Fn(&[u8]) -> Result<Vec<u8>, Vec<u8>> + Send + Sync;
+
+/// Table of methods served to the remote end.
+///
+/// Calls of methods that aren't registered, or with params that don't match the handler, fail
+/// with a string error.
+pub struct Dispatcher {
+    methods: HashMap<String, Box<Handler>>,
+    max_message_len: usize,
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self {
+            methods: HashMap::new(),
+            max_message_len: MAX_MESSAGE_LEN,
+        }
+    }
+}
+
+impl Dispatcher {
+    /// Creates an empty table, that serves messages up to [MAX_MESSAGE_LEN] bytes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum length of a message read by the server, in bytes.
+    pub fn max_message_len(&self) -> usize {
+        self.max_message_len
+    }
+
+    /// Sets the maximum length of a message read by the server.
+    pub fn set_max_message_len(&mut self, max_message_len: usize) -> &mut Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Registers a method, replacing any previous handler with the same name.
+    ///
+    /// Results and errors are packed with [pack_value]. A result or error that doesn't pack as a
+    /// single value is answered with a string error instead.
+    pub fn register<P, R, E, F>(&mut self, method: impl Into<String>, handler: F) -> &mut Self
+    where
+        P: FromParams,
+        R: Packable,
+        E: Packable,
+        F: Fn(P) -> Result<R, E> + Send + Sync + 'static,
+    {
+        self.register_raw(method, move |params| {
+            let params = P::unpack_params(params)
+                .map_err(|e| format!("invalid params: {e}").pack_to_vec())?;
+            match handler(params) {
+                Ok(result) => packed(&result),
+                Err(error) => Err(packed(&error)?),
+            }
+        })
+    }
+
+    /// Registers a method that handles the packed params, replacing any previous handler with
+    /// the same name.
+    pub fn register_raw<F>(&mut self, method: impl Into<String>, handler: F) -> &mut Self
+    where
+        F: Fn(&[u8]) -> Result<Vec<u8>, Vec<u8>> + Send + Sync + 'static,
+    {
+        self.methods.insert(method.into(), Box::new(handler));
+        self
+    }
+
+    /// Checks if the method is registered.
+    pub fn contains(&self, method: &str) -> bool {
+        self.methods.contains_key(method)
+    }
+
+    /// Calls a method with the packed params, returning the packed result or error.
+    pub fn call(&self, method: &str, params: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+        match self.methods.get(method) {
+            Some(handler) => handler(params),
+            None => Err(format!("method not found: {method}").pack_to_vec()),
+        }
+    }
+
+    /// Handles a received message, returning the response to send back, if any.
+    ///
+    /// Responses aren't expected by a server, and are ignored.
+    pub fn handle(&self, message: Message) -> Option<Response> {
+        match message {
+            Message::Request(request) => Some(Response {
+                id: request.id,
+                result: self.call(&request.method, &request.params),
+            }),
+            Message::Notification(notification) => {
+                // a notification has no response to carry the error
+                self.call(&notification.method, &notification.params).ok();
+                None
+            }
+            Message::Response(_) => None,
+        }
+    }
+}
+
+impl fmt::Debug for Dispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.methods.keys()).finish()
+    }
+}
@@ -0,0 +1,109 @@
+use crate::unpack_value;
+use msgpacker::{debug::dump, Event, Token, Tokenizer, Unpackable};
+use std::{fmt, io};
+
+/// Error value returned by the remote end of a call.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteError(pub Vec<u8>);
+
+impl RemoteError {
+    /// Packed bytes of the error value.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Unpacks the error value with [unpack_value].
+    pub fn unpack<T>(&self) -> Result<T, msgpacker::Error>
+    where
+        T: Unpackable,
+        msgpacker::Error: From<T::Error>,
+    {
+        unpack_value(&self.0)
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match Tokenizer::new(&self.0).next() {
+            Some(Ok(Event {
+                token: Token::Str(s),
+                ..
+            })) => f.write_str(s),
+            _ => write!(f, "{:?}", dump(&self.0)),
+        }
+    }
+}
+
+/// Failure of a call, or of the connection.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The underlying stream failed.
+    Io(io::Error),
+    /// A message or a value is malformed.
+    Protocol(msgpacker::Error),
+    /// The remote end answered with an error.
+    Remote(RemoteError),
+    /// A received message is larger than the maximum length.
+    MessageTooLarge {
+        /// Maximum length of a message, in bytes.
+        max: usize,
+    },
+    /// The connection was closed before the response arrived.
+    Closed,
+}
+
+impl Error {
+    /// Returns a copy of the error, to report the failure of a connection to every call.
+    ///
+    /// I/O errors keep their kind and message, but not their source.
+    pub(crate) fn duplicate(&self) -> Self {
+        match self {
+            Error::Io(e) => Error::Io(io::Error::new(e.kind(), e.to_string())),
+            Error::Protocol(e) => Error::Protocol(*e),
+            Error::Remote(e) => Error::Remote(e.clone()),
+            Error::MessageTooLarge { max } => Error::MessageTooLarge { max: *max },
+            Error::Closed => Error::Closed,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Protocol(e) => write!(f, "protocol error: {e}"),
+            Error::Remote(e) => write!(f, "remote error: {e}"),
+            Error::MessageTooLarge { max } => write!(f, "message larger than {max} bytes"),
+            Error::Closed => f.write_str("connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Protocol(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<msgpacker::Error> for Error {
+    fn from(e: msgpacker::Error) -> Self {
+        Error::Protocol(e)
+    }
+}
+
+impl From<RemoteError> for Error {
+    fn from(e: RemoteError) -> Self {
+        Error::Remote(e)
+    }
+}
@@ -0,0 +1,26 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+pub mod blocking;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+
+mod codec;
+mod dispatch;
+mod error;
+mod message;
+mod params;
+mod service;
+mod value;
+
+pub use codec::MAX_MESSAGE_LEN;
+pub use dispatch::{Dispatcher, Handler};
+pub use error::{Error, RemoteError};
+pub use message::{Message, Notification, Request, Response};
+pub use params::{FromParams, ToParams};
+pub use value::{pack_value, unpack_value, Array};
+
+pub use msgpacker_derive::service;
+
+#[doc(hidden)]
+pub use service::__private;
@@ -0,0 +1,216 @@
+use msgpacker::{
+    encode::{write_array_len, write_nil, write_uint},
+    Error, Format, Packable, Token, Tokenizer, Unpackable, ValueScanner,
+};
+
+const REQUEST: u64 = 0;
+const RESPONSE: u64 = 1;
+const NOTIFICATION: u64 = 2;
+
+/// Packed nil, the error of a successful response and the result of a failed one.
+const NIL: &[u8] = &[Format::NIL];
+
+/// Call of a remote method that expects a [Response].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Request {
+    /// Sequence number that correlates the request with its response.
+    pub id: u32,
+    /// Name of the called method.
+    pub method: String,
+    /// Packed array of the arguments.
+    pub params: Vec<u8>,
+}
+
+/// Outcome of a [Request].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Response {
+    /// Sequence number of the answered request.
+    pub id: u32,
+    /// Packed result of the call, or the packed error if the call failed.
+    pub result: Result<Vec<u8>, Vec<u8>>,
+}
+
+/// Call of a remote method that expects no response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Notification {
+    /// Name of the called method.
+    pub method: String,
+    /// Packed array of the arguments.
+    pub params: Vec<u8>,
+}
+
+/// A MessagePack-RPC message.
+///
+/// Packs as `[0, msgid, method, params]`, `[1, msgid, error, result]` or `[2, method, params]`.
+/// Params, results and errors are kept as packed bytes; an empty buffer is packed as nil.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Message {
+    /// Call that expects a response.
+    Request(Request),
+    /// Outcome of a call.
+    Response(Response),
+    /// Call that expects no response.
+    Notification(Notification),
+}
+
+impl From<Request> for Message {
+    fn from(request: Request) -> Self {
+        Message::Request(request)
+    }
+}
+
+impl From<Response> for Message {
+    fn from(response: Response) -> Self {
+        Message::Response(response)
+    }
+}
+
+impl From<Notification> for Message {
+    fn from(notification: Notification) -> Self {
+        Message::Notification(notification)
+    }
+}
+
+/// Writes a packed value, or nil if it is empty.
+fn write_raw<T>(buf: &mut T, value: &[u8]) -> usize
+where
+    T: Extend<u8>,
+{
+    if value.is_empty() {
+        return write_nil(buf);
+    }
+    buf.extend(value.iter().copied());
+    value.len()
+}
+
+impl Packable for Message {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Message::Request(r) => {
+                write_array_len(buf, 4)
+                    + write_uint(buf, REQUEST)
+                    + write_uint(buf, r.id as u64)
+                    + r.method.pack(buf)
+                    + write_raw(buf, &r.params)
+            }
+            Message::Response(r) => {
+                let (error, result) = match &r.result {
+                    Ok(result) => (NIL, result.as_slice()),
+                    Err(error) => (error.as_slice(), NIL),
+                };
+                write_array_len(buf, 4)
+                    + write_uint(buf, RESPONSE)
+                    + write_uint(buf, r.id as u64)
+                    + write_raw(buf, error)
+                    + write_raw(buf, result)
+            }
+            Message::Notification(n) => {
+                write_array_len(buf, 3)
+                    + write_uint(buf, NOTIFICATION)
+                    + n.method.pack(buf)
+                    + write_raw(buf, &n.params)
+            }
+        }
+    }
+}
+
+fn next<'a>(tokens: &mut Tokenizer<'a>) -> Result<Token<'a>, Error> {
+    Ok(tokens.next().ok_or(Error::BufferTooShort)??.token)
+}
+
+fn uint(tokens: &mut Tokenizer<'_>) -> Result<u64, Error> {
+    match next(tokens)? {
+        Token::Uint(v) => Ok(v),
+        Token::Int(v) => u64::try_from(v).map_err(|_| Error::IntegerOverflow),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+fn id(tokens: &mut Tokenizer<'_>) -> Result<u32, Error> {
+    u32::try_from(uint(tokens)?).map_err(|_| Error::IntegerOverflow)
+}
+
+fn method(tokens: &mut Tokenizer<'_>) -> Result<String, Error> {
+    match next(tokens)? {
+        Token::Str(s) => Ok(s.into()),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+/// Reads the bytes of a complete value, including its nested elements.
+pub(crate) fn value<'a>(tokens: &mut Tokenizer<'a>) -> Result<&'a [u8], Error> {
+    let buf = tokens.remaining();
+    let start = tokens.offset();
+    let mut pending = 1usize;
+    while pending > 0 {
+        pending = pending - 1
+            + match next(tokens)? {
+                Token::ArrayStart(len) => len,
+                Token::MapStart(len) => 2 * len,
+                _ => 0,
+            };
+    }
+    Ok(&buf[..tokens.offset() - start])
+}
+
+/// Reads the bytes of a complete value from the iterator.
+pub(crate) fn value_iter<I>(bytes: I) -> Result<Vec<u8>, Error>
+where
+    I: Iterator<Item = u8>,
+{
+    let mut scanner = ValueScanner::new();
+    let mut buf = Vec::new();
+    for byte in bytes {
+        buf.push(byte);
+        if scanner.feed(&[byte])?.is_some() {
+            return Ok(buf);
+        }
+    }
+    Err(Error::BufferTooShort)
+}
+
+impl Unpackable for Message {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let mut tokens = Tokenizer::new(buf);
+        let len = match next(&mut tokens)? {
+            Token::ArrayStart(len) => len,
+            _ => return Err(Error::UnexpectedFormatTag),
+        };
+        let message = match (uint(&mut tokens)?, len) {
+            (REQUEST, 4) => Message::Request(Request {
+                id: id(&mut tokens)?,
+                method: method(&mut tokens)?,
+                params: value(&mut tokens)?.to_vec(),
+            }),
+            (RESPONSE, 4) => {
+                let id = id(&mut tokens)?;
+                let error = value(&mut tokens)?;
+                let result = value(&mut tokens)?;
+                let result = match error {
+                    NIL => Ok(result.to_vec()),
+                    error => Err(error.to_vec()),
+                };
+                Message::Response(Response { id, result })
+            }
+            (NOTIFICATION, 3) => Message::Notification(Notification {
+                method: method(&mut tokens)?,
+                params: value(&mut tokens)?.to_vec(),
+            }),
+            _ => return Err(Error::InvalidEnumVariant),
+        };
+        Ok((tokens.offset(), message))
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let buf = value_iter(bytes.into_iter())?;
+        Self::unpack_exact(&buf).map(|message| (buf.len(), message))
+    }
+}
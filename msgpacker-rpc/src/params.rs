@@ -0,0 +1,87 @@
+use crate::message::value;
+use crate::value::{pack_value, single_value, unpack_value};
+use msgpacker::{encode::write_array_len, Error, Packable, Token, Tokenizer, Unpackable};
+
+/// Arguments of a call, packed as an array with one element per argument.
+///
+/// Implemented for tuples of up to 8 [Packable] elements, so `(a, b)` is packed as `[a, b]`. A
+/// single argument is written as `(a,)`, and no arguments as `()`. Each element is packed with
+/// [pack_value], and must pack as a single value for the array to be well formed; the clients
+/// refuse to send params that aren't.
+pub trait ToParams {
+    /// Packs the arguments as an array, returning the amount of written bytes.
+    fn pack_params<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>;
+}
+
+/// Arguments of a call, unpacked from an array with one element per argument.
+///
+/// Implemented for tuples of up to 8 [Unpackable] elements. The array must have exactly one
+/// element per member of the tuple, and each element is unpacked with [unpack_value].
+pub trait FromParams: Sized {
+    /// Unpacks the arguments from a packed array.
+    fn unpack_params(buf: &[u8]) -> Result<Self, Error>;
+}
+
+/// Packs the params of a call, checking that each element is a single value.
+///
+/// An element that packs as several values leaves values after the end of the array, reported as
+/// [Error::TrailingBytes].
+pub(crate) fn params_to_vec<P>(params: &P) -> Result<Vec<u8>, Error>
+where
+    P: ToParams + ?Sized,
+{
+    let mut buf = Vec::new();
+    params.pack_params(&mut buf);
+    single_value(&buf)?;
+    Ok(buf)
+}
+
+/// Reads the array header of the params.
+fn array_header<'a>(buf: &'a [u8], len: usize) -> Result<Tokenizer<'a>, Error> {
+    let mut tokens = Tokenizer::new(buf);
+    match tokens.next().ok_or(Error::BufferTooShort)??.token {
+        Token::ArrayStart(n) if n == len => Ok(tokens),
+        Token::ArrayStart(_) => Err(Error::Custom("unexpected number of params")),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+macro_rules! impl_params {
+    ($len:expr; $($t:ident $i:tt),*) => {
+        impl<$($t),*> ToParams for ($($t,)*)
+        where
+            $($t: Packable,)*
+        {
+            #[allow(unused_variables)]
+            fn pack_params<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+            {
+                write_array_len(buf, $len) $(+ pack_value(&self.$i, buf))*
+            }
+        }
+
+        impl<$($t),*> FromParams for ($($t,)*)
+        where
+            $($t: Unpackable, Error: From<<$t as Unpackable>::Error>,)*
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn unpack_params(buf: &[u8]) -> Result<Self, Error> {
+                let mut tokens = array_header(buf, $len)?;
+                Ok(($(unpack_value::<$t>(value(&mut tokens)?)?,)*))
+            }
+        }
+    };
+}
+
+impl_params!(0;);
+impl_params!(1; A 0);
+impl_params!(2; A 0, B 1);
+impl_params!(3; A 0, B 1, C 2);
+impl_params!(4; A 0, B 1, C 2, D 3);
+impl_params!(5; A 0, B 1, C 2, D 3, E 4);
+impl_params!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+impl_params!(7; A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_params!(8; A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
@@ -0,0 +1,68 @@
+//! Support code of the [service](crate::service) macro.
+
+/// Expands to its input only if the `tokio` feature is enabled.
+///
+/// Evaluates the feature in this crate, rather than in the crate invoking the service macro.
+#[doc(hidden)]
+#[cfg(feature = "tokio")]
+#[macro_export]
+macro_rules! __if_tokio {
+    ($($tokens:tt)*) => {
+        $($tokens)*
+    };
+}
+
+/// Expands to its input only if the `tokio` feature is enabled.
+///
+/// Evaluates the feature in this crate, rather than in the crate invoking the service macro.
+#[doc(hidden)]
+#[cfg(not(feature = "tokio"))]
+#[macro_export]
+macro_rules! __if_tokio {
+    ($($tokens:tt)*) => {};
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use crate::params::params_to_vec;
+    use crate::{unpack_value, Array, Error, ToParams};
+    use msgpacker::Unpackable;
+
+    #[cfg(feature = "tokio")]
+    pub use ::tokio::io::AsyncWrite;
+
+    /// Packs the arguments of a call.
+    pub fn params<P>(params: P) -> Result<Vec<u8>, Error>
+    where
+        P: ToParams,
+    {
+        Ok(params_to_vec(&params)?)
+    }
+
+    /// Unpacks the result of a call, wrapped in [Array].
+    pub fn result<T>(result: Result<Vec<u8>, Error>) -> Result<T, Error>
+    where
+        T: Unpackable,
+        msgpacker::Error: From<T::Error>,
+    {
+        let Array(result) = unpack_value(&result?)?;
+        Ok(result)
+    }
+
+    /// Unpacks the result of a call, or its error if it has the type of the method errors, both
+    /// wrapped in [Array].
+    pub fn fallible<T, E>(result: Result<Vec<u8>, Error>) -> Result<Result<T, E>, Error>
+    where
+        T: Unpackable,
+        E: Unpackable,
+        msgpacker::Error: From<T::Error> + From<E::Error>,
+    {
+        match result {
+            Err(Error::Remote(e)) => match e.unpack::<Array<E>>() {
+                Ok(Array(error)) => Ok(Err(error)),
+                Err(_) => Err(Error::Remote(e)),
+            },
+            result => self::result(result).map(Ok),
+        }
+    }
+}
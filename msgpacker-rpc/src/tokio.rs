@@ -0,0 +1,174 @@
+//! Client and server over [tokio] streams.
+
+use crate::codec::{Calls, Decoder, CHUNK, MAX_MESSAGE_LEN};
+use crate::params::params_to_vec;
+use crate::{
+    unpack_value, Dispatcher, Error, Message, Notification, RemoteError, Request, ToParams,
+};
+use ::tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use ::tokio::sync::{oneshot, Mutex as AsyncMutex};
+use msgpacker::{Packable, Unpackable};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Sender = oneshot::Sender<Result<Vec<u8>, Vec<u8>>>;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads the next message, or `None` at the end of the stream.
+async fn read<R>(reader: &mut R, decoder: &mut Decoder) -> Result<Option<Message>, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut chunk = [0u8; CHUNK];
+    loop {
+        if let Some(message) = decoder.next()? {
+            return Ok(Some(message));
+        }
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return decoder.finish().map(|_| None);
+        }
+        decoder.extend(&chunk[..n]);
+    }
+}
+
+async fn write<W>(writer: &mut W, message: &Message) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer.write_all(&message.pack_to_vec()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Client of a remote end, correlating responses with calls by message id.
+///
+/// Calls can be awaited concurrently; each one completes when its own response arrives,
+/// regardless of the order in which the remote end answers them.
+#[derive(Debug)]
+pub struct Client<W> {
+    writer: AsyncMutex<W>,
+    next_id: AtomicU32,
+    calls: Arc<Mutex<Calls<Sender>>>,
+}
+
+impl<W> Client<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Creates a new client that writes to `writer`, and spawns a task that reads the responses
+    /// from `reader`, of up to [MAX_MESSAGE_LEN] bytes.
+    ///
+    /// Must be called from a runtime. The task runs until the end of `reader` or the first error
+    /// reading it. The calls in flight and the later calls then fail with that error, or with
+    /// [Error::Closed] at the end of `reader`.
+    pub fn new<R>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        Self::with_max_message_len(reader, writer, MAX_MESSAGE_LEN)
+    }
+
+    /// Creates a new client like [Client::new], that fails with [Error::MessageTooLarge] when a
+    /// response is larger than `max_message_len` bytes.
+    pub fn with_max_message_len<R>(reader: R, writer: W, max_message_len: usize) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        ::tokio::spawn(receive(reader, Arc::clone(&calls), max_message_len));
+        Self {
+            writer: AsyncMutex::new(writer),
+            next_id: AtomicU32::new(0),
+            calls,
+        }
+    }
+
+    /// Calls a remote method, waiting for its result.
+    pub async fn call<P, R>(&self, method: &str, params: P) -> Result<R, Error>
+    where
+        P: ToParams,
+        R: Unpackable,
+        msgpacker::Error: From<R::Error>,
+    {
+        let params = params_to_vec(&params)?;
+        let result = self.call_raw(method, params).await?;
+        Ok(unpack_value(&result)?)
+    }
+
+    /// Calls a remote method with the packed params, returning the packed result.
+    pub async fn call_raw(&self, method: &str, params: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.calls).insert(id, sender)?;
+        let request = Request {
+            id,
+            method: method.into(),
+            params,
+        };
+        if let Err(e) = write(&mut *self.writer.lock().await, &request.into()).await {
+            lock(&self.calls).remove(id);
+            return Err(e);
+        }
+        match receiver.await {
+            Ok(result) => result.map_err(|e| RemoteError(e).into()),
+            Err(_) => Err(lock(&self.calls).error()),
+        }
+    }
+
+    /// Sends a notification to a remote method.
+    pub async fn notify<P>(&self, method: &str, params: P) -> Result<(), Error>
+    where
+        P: ToParams,
+    {
+        let notification = Notification {
+            method: method.into(),
+            params: params_to_vec(&params)?,
+        };
+        write(&mut *self.writer.lock().await, &notification.into()).await
+    }
+}
+
+async fn receive<R>(mut reader: R, calls: Arc<Mutex<Calls<Sender>>>, max_message_len: usize)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut decoder = Decoder::new(max_message_len);
+    // a malformed stream can't be resynchronized, so it ends the connection as well
+    let error = loop {
+        match read(&mut reader, &mut decoder).await {
+            Ok(Some(Message::Response(response))) => {
+                if let Some(sender) = lock(&calls).remove(response.id) {
+                    sender.send(response.result).ok();
+                }
+            }
+            Ok(Some(_)) => (),
+            Ok(None) => break Error::Closed,
+            Err(e) => break e,
+        }
+    };
+    lock(&calls).close(error);
+}
+
+/// Serves the messages read from `reader` with the dispatcher, writing the responses to
+/// `writer`.
+///
+/// Requests are handled in the order they arrive, on the calling task. Returns at the end of
+/// `reader`, or fails with [Error::MessageTooLarge] on a message larger than
+/// [Dispatcher::max_message_len].
+pub async fn serve<R, W>(dispatcher: &Dispatcher, mut reader: R, mut writer: W) -> Result<(), Error>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut decoder = Decoder::new(dispatcher.max_message_len());
+    while let Some(message) = read(&mut reader, &mut decoder).await? {
+        if let Some(response) = dispatcher.handle(message) {
+            write(&mut writer, &response.into()).await?;
+        }
+    }
+    Ok(())
+}
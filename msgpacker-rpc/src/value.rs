@@ -0,0 +1,127 @@
+use crate::message::value_iter;
+use msgpacker::{
+    encode::{write_array_len, write_nil},
+    Error, Format, Packable, Token, Tokenizer, Unpackable, ValueScanner,
+};
+
+/// Packs a value as a single protocol value, returning the amount of written bytes.
+///
+/// Params, results and errors must be single values. The value is packed as is, so types that
+/// pack as a sequence of values, such as tuples, options and derived structs, must be wrapped in
+/// [Array]. Types that pack as nothing, such as `()`, are packed as nil.
+pub fn pack_value<T, B>(value: &T, buf: &mut B) -> usize
+where
+    T: Packable + ?Sized,
+    B: Extend<u8>,
+{
+    match value.pack(buf) {
+        0 => write_nil(buf),
+        n => n,
+    }
+}
+
+/// Unpacks a value written by [pack_value], that must span the whole buffer.
+///
+/// A nil is unpacked as a type that packs as nothing, if the type doesn't accept it as is.
+pub fn unpack_value<T>(buf: &[u8]) -> Result<T, Error>
+where
+    T: Unpackable,
+    Error: From<T::Error>,
+{
+    match T::unpack_exact(buf) {
+        Ok(value) => Ok(value),
+        Err(e) if buf == [Format::NIL] => T::unpack_exact(&[]).map_err(|_| Error::from(e)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Checks that the buffer holds exactly one packed value.
+///
+/// Returns [Error::TrailingBytes] if more values follow the first one, and
+/// [Error::BufferTooShort] if the value is incomplete.
+pub(crate) fn single_value(buf: &[u8]) -> Result<(), Error> {
+    match ValueScanner::new().feed(buf)? {
+        Some(n) if n == buf.len() => Ok(()),
+        Some(consumed) => Err(Error::TrailingBytes {
+            consumed,
+            total: buf.len(),
+        }),
+        None => Err(Error::BufferTooShort),
+    }
+}
+
+/// Counts the values packed in the buffer.
+fn count(mut buf: &[u8]) -> usize {
+    let mut scanner = ValueScanner::new();
+    let mut count = 0;
+    while let Ok(Some(n)) = scanner.feed(buf) {
+        buf = &buf[n..];
+        count += 1;
+    }
+    count
+}
+
+/// Packs a sequence of values as a single array of them.
+///
+/// Tuples, options and derived structs pack as the concatenation of their values, which can't be
+/// sent as a param, result or error. The wrapper packs them as an array instead, so
+/// `Array((1, "a"))` is sent as `[1, "a"]`, `Array(Some(2))` as `[1, 2]` and `Array(None)` as
+/// `[0]`. The array is unpacked only if its length matches the amount of values read by the
+/// wrapped type.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Array<T>(pub T);
+
+impl<T> From<T> for Array<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Packable for Array<T>
+where
+    T: Packable,
+{
+    fn pack<B>(&self, buf: &mut B) -> usize
+    where
+        B: Extend<u8>,
+    {
+        let bytes = self.0.pack_to_vec();
+        let n = write_array_len(buf, count(&bytes) as u32);
+        let len = bytes.len();
+        buf.extend(bytes);
+        n + len
+    }
+}
+
+impl<T> Unpackable for Array<T>
+where
+    T: Unpackable,
+{
+    type Error = T::Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let mut tokens = Tokenizer::new(buf);
+        let len = match tokens.next() {
+            Some(Ok(event)) => match event.token {
+                Token::ArrayStart(len) => len,
+                _ => return Err(Error::UnexpectedFormatTag.into()),
+            },
+            Some(Err(e)) => return Err(e.into()),
+            None => return Err(Error::BufferTooShort.into()),
+        };
+        let header = buf.len() - tokens.remaining().len();
+        let (n, value) = T::unpack(&buf[header..])?;
+        if count(&buf[header..header + n]) != len {
+            return Err(Error::UnexpectedFormatTag.into());
+        }
+        Ok((header + n, Self(value)))
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let buf = value_iter(bytes.into_iter())?;
+        Self::unpack_exact(&buf).map(|value| (buf.len(), value))
+    }
+}
@@ -0,0 +1,358 @@
+use msgpacker::prelude::*;
+use msgpacker_rpc::blocking::{serve, Client};
+use msgpacker_rpc::{Dispatcher, Error, Message, Request, Response, ToParams};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+fn dispatcher(counter: Arc<AtomicU64>) -> Dispatcher {
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .register("add", |(a, b): (u64, u64)| Ok::<_, ()>(a + b))
+        .register("concat", |(a, b): (String, String)| Ok::<_, ()>(a + &b))
+        .register("div", |(a, b): (u64, u64)| match b {
+            0 => Err("division by zero"),
+            b => Ok(a / b),
+        })
+        .register("incr", move |(n,): (u64,)| {
+            counter.fetch_add(n, Ordering::SeqCst);
+            Ok::<_, ()>(())
+        });
+    dispatcher
+}
+
+fn tcp(dispatcher: Dispatcher) -> Client<TcpStream> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(&dispatcher, stream.try_clone().unwrap(), stream).unwrap();
+    });
+    let stream = TcpStream::connect(address).unwrap();
+    Client::new(stream.try_clone().unwrap(), stream)
+}
+
+#[test]
+fn calls() {
+    let counter = Arc::new(AtomicU64::new(0));
+    let client = tcp(dispatcher(counter.clone()));
+
+    assert_eq!(5, client.call::<_, u64>("add", (2u64, 3u64)).unwrap());
+    assert_eq!(
+        "ab",
+        client
+            .call::<_, String>("concat", ("a", "b".to_string()))
+            .unwrap()
+    );
+    client.call::<_, ()>("incr", (7u8,)).unwrap();
+    assert_eq!(7, counter.load(Ordering::SeqCst));
+
+    match client.call::<_, u64>("div", (1u8, 0u8)) {
+        Err(Error::Remote(e)) => {
+            assert_eq!("division by zero", e.to_string());
+            assert_eq!(Ok("division by zero".to_string()), e.unpack());
+        }
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, u64>("sub", (1u8, 0u8)) {
+        Err(Error::Remote(e)) => assert_eq!("method not found: sub", e.to_string()),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, u64>("add", (1u8,)) {
+        Err(Error::Remote(e)) => {
+            assert_eq!("invalid params: unexpected number of params", e.to_string())
+        }
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, String>("add", (1u8, 2u8)) {
+        Err(Error::Protocol(msgpacker::Error::UnexpectedFormatTag)) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+#[test]
+fn values_must_be_single() {
+    let mut dispatcher = dispatcher(Arc::new(AtomicU64::new(0)));
+    dispatcher
+        .register("pair", |(): ()| Ok::<_, ()>((1u8, 2u8)))
+        .register("find", |(): ()| Err::<(), _>(Some(3u8)));
+    let client = tcp(dispatcher);
+
+    let trailing = msgpacker::Error::TrailingBytes {
+        consumed: 2,
+        total: 3,
+    };
+    match client.call::<_, u64>("add", (Some(5u8),)) {
+        Err(Error::Protocol(e)) => assert_eq!(trailing, e),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.notify("incr", (Some(5u8),)) {
+        Err(Error::Protocol(e)) => assert_eq!(trailing, e),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, ()>("pair", ()) {
+        Err(Error::Remote(e)) => assert_eq!(
+            "invalid response: trailing bytes: consumed 1 of 2",
+            e.to_string()
+        ),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, ()>("find", ()) {
+        Err(Error::Remote(e)) => assert_eq!(
+            "invalid response: trailing bytes: consumed 1 of 2",
+            e.to_string()
+        ),
+        e => panic!("unexpected result {e:?}"),
+    }
+    assert_eq!(5, client.call::<_, u64>("add", (2u64, 3u64)).unwrap());
+}
+
+#[test]
+fn notifications() {
+    let counter = Arc::new(AtomicU64::new(0));
+    let client = tcp(dispatcher(counter.clone()));
+
+    for n in 1..=10u64 {
+        client.notify("incr", (n,)).unwrap();
+    }
+    client.notify("unknown", ()).unwrap();
+    // requests are served in order, so the notifications were handled once the call returns
+    assert_eq!(2, client.call::<_, u64>("add", (1u8, 1u8)).unwrap());
+    assert_eq!(55, counter.load(Ordering::SeqCst));
+}
+
+#[test]
+fn concurrent_calls() {
+    let client = Arc::new(tcp(dispatcher(Arc::default())));
+    let threads: Vec<_> = (0..8u64)
+        .map(|t| {
+            let client = client.clone();
+            thread::spawn(move || {
+                for i in 0..100u64 {
+                    let sum: u64 = client.call("add", (t, i)).unwrap();
+                    assert_eq!(t + i, sum);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_pair() {
+    use std::os::unix::net::UnixStream;
+
+    let (server, client) = UnixStream::pair().unwrap();
+    let dispatcher = dispatcher(Arc::default());
+    thread::spawn(move || serve(&dispatcher, server.try_clone().unwrap(), server).unwrap());
+    let client = Client::new(client.try_clone().unwrap(), client);
+    assert_eq!(
+        "xy",
+        client.call::<_, String>("concat", ("x", "y")).unwrap()
+    );
+}
+
+/// Reads `count` messages from the stream.
+fn read_messages(stream: &mut TcpStream, count: usize) -> Vec<Message> {
+    let mut buf = Vec::new();
+    let mut messages = Vec::new();
+    let mut chunk = [0u8; 256];
+    while messages.len() < count {
+        let n = stream.read(&mut chunk).unwrap();
+        assert_ne!(0, n);
+        buf.extend_from_slice(&chunk[..n]);
+        while let Ok((n, message)) = Message::unpack(&buf) {
+            buf.drain(..n);
+            messages.push(message);
+        }
+    }
+    messages
+}
+
+#[test]
+fn out_of_order_responses() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut requests = read_messages(&mut stream, 2);
+        requests.reverse();
+        for request in requests {
+            let Message::Request(request) = request else {
+                panic!("unexpected message {request:?}");
+            };
+            let response = Message::from(Response {
+                id: request.id,
+                result: Ok(request.method.pack_to_vec()),
+            });
+            stream.write_all(&response.pack_to_vec()).unwrap();
+        }
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    let client = Arc::new(Client::new(stream.try_clone().unwrap(), stream));
+    let calls: Vec<_> = ["first", "second"]
+        .into_iter()
+        .map(|method| {
+            let client = client.clone();
+            thread::spawn(move || {
+                let result: String = client.call(method, ()).unwrap();
+                assert_eq!(method, result);
+            })
+        })
+        .collect();
+    for call in calls {
+        call.join().unwrap();
+    }
+    server.join().unwrap();
+
+    // the server is gone, so the connection is closed
+    match client.call::<_, ()>("third", ()) {
+        Err(Error::Closed) | Err(Error::Io(_)) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+#[test]
+fn closed_with_calls_in_flight() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_messages(&mut stream, 1);
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    let client = Client::new(stream.try_clone().unwrap(), stream);
+    match client.call::<_, ()>("never", ()) {
+        Err(Error::Closed) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+#[test]
+fn malformed_stream_fails_every_call() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        read_messages(&mut stream, 1);
+        stream.write_all(&[0xc1]).unwrap();
+        read_messages(&mut stream, 1);
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    let client = Client::new(stream.try_clone().unwrap(), stream);
+    for method in ["pending", "later"] {
+        match client.call::<_, ()>(method, ()) {
+            Err(Error::Protocol(msgpacker::Error::UnexpectedFormatTag)) => (),
+            e => panic!("unexpected result {e:?}"),
+        }
+    }
+}
+
+#[test]
+fn large_messages() {
+    let mut repeat = Dispatcher::new();
+    repeat.register("repeat", |(n,): (usize,)| Ok::<_, ()>("x".repeat(n)));
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(&repeat, stream.try_clone().unwrap(), stream).unwrap();
+    });
+
+    let stream = TcpStream::connect(address).unwrap();
+    let client = Client::with_max_message_len(stream.try_clone().unwrap(), stream, 16);
+    assert_eq!("xx", client.call::<_, String>("repeat", (2,)).unwrap());
+    for n in [100, 1] {
+        match client.call::<_, String>("repeat", (n,)) {
+            Err(Error::MessageTooLarge { max: 16 }) => (),
+            e => panic!("unexpected result {e:?}"),
+        }
+    }
+
+    let mut dispatcher = dispatcher(Arc::default());
+    dispatcher.set_max_message_len(8);
+    let request = Message::from(Request {
+        id: 0,
+        method: "concat".into(),
+        params: params(("abc", "def")),
+    });
+    match serve(&dispatcher, request.pack_to_vec().as_slice(), Vec::new()) {
+        Err(Error::MessageTooLarge { max: 8 }) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+fn params<P>(params: P) -> Vec<u8>
+where
+    P: ToParams,
+{
+    let mut buf = Vec::new();
+    params.pack_params(&mut buf);
+    buf
+}
+
+/// Reader that returns a single byte per read.
+struct OneByte<'a>(&'a [u8]);
+
+impl Read for OneByte<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match (self.0.split_first(), buf.first_mut()) {
+            (Some((byte, rest)), Some(b)) => {
+                *b = *byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn messages_split_across_reads() {
+    let mut requests = Vec::new();
+    for (id, (a, b)) in [(1u64, 2u64), (300, 70000), (u64::MAX - 1, 1)]
+        .into_iter()
+        .enumerate()
+    {
+        Message::from(Request {
+            id: id as u32,
+            method: "add".into(),
+            params: params((a, b)),
+        })
+        .pack(&mut requests);
+    }
+    let mut responses = Vec::new();
+    serve(
+        &dispatcher(Arc::default()),
+        OneByte(&requests),
+        &mut responses,
+    )
+    .unwrap();
+
+    let mut buf = responses.as_slice();
+    for (id, sum) in [(0, 3u64), (1, 70300), (2, u64::MAX)] {
+        let (n, response) = Message::unpack(buf).unwrap();
+        buf = &buf[n..];
+        assert_eq!(
+            Message::from(Response {
+                id,
+                result: Ok(sum.pack_to_vec()),
+            }),
+            response
+        );
+    }
+    assert!(buf.is_empty());
+
+    match serve(&Dispatcher::new(), OneByte(&requests[..5]), Vec::new()) {
+        Err(Error::Protocol(msgpacker::Error::BufferTooShort)) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
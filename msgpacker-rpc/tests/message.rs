@@ -0,0 +1,229 @@
+use msgpacker::prelude::*;
+use msgpacker::{Token, Tokenizer};
+use msgpacker_rpc::{
+    pack_value, unpack_value, Array, FromParams, Message, Notification, Request, Response, ToParams,
+};
+use proptest::prelude::*;
+
+fn case(message: Message) {
+    let bytes = message.pack_to_vec();
+    assert_eq!(bytes.len(), message.packed_len());
+    assert_eq!(
+        (bytes.len(), message.clone()),
+        Message::unpack(&bytes).unwrap()
+    );
+    assert_eq!((bytes.len(), message), Message::unpack_iter(bytes).unwrap());
+}
+
+fn params<P>(params: P) -> Vec<u8>
+where
+    P: ToParams,
+{
+    let mut buf = Vec::new();
+    assert_eq!(params.pack_params(&mut buf), buf.len());
+    buf
+}
+
+#[test]
+fn spec_layout() {
+    let request = Message::from(Request {
+        id: 1,
+        method: "add".into(),
+        params: params((1u8, 2u8)),
+    });
+    assert_eq!(
+        vec![0x94, 0x00, 0x01, 0xa3, b'a', b'd', b'd', 0x92, 0x01, 0x02],
+        request.pack_to_vec()
+    );
+
+    let response = Message::from(Response {
+        id: 1,
+        result: Ok(3u8.pack_to_vec()),
+    });
+    assert_eq!(vec![0x94, 0x01, 0x01, 0xc0, 0x03], response.pack_to_vec());
+
+    let error = Message::from(Response {
+        id: 2,
+        result: Err("e".pack_to_vec()),
+    });
+    assert_eq!(
+        vec![0x94, 0x01, 0x02, 0xa1, b'e', 0xc0],
+        error.pack_to_vec()
+    );
+
+    let notification = Message::from(Notification {
+        method: "n".into(),
+        params: params(()),
+    });
+    assert_eq!(
+        vec![0x93, 0x02, 0xa1, b'n', 0x90],
+        notification.pack_to_vec()
+    );
+}
+
+#[test]
+fn empty_values_are_nil() {
+    let response = Message::from(Response {
+        id: 0,
+        result: Ok(().pack_to_vec()),
+    });
+    let bytes = response.pack_to_vec();
+    assert_eq!(vec![0x94, 0x01, 0x00, 0xc0, 0xc0], bytes);
+    let (_, response) = Message::unpack(&bytes).unwrap();
+    assert_eq!(
+        Message::from(Response {
+            id: 0,
+            result: Ok(vec![0xc0]),
+        }),
+        response
+    );
+}
+
+#[test]
+fn malformed() {
+    // not an array
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        Message::unpack(&[0x01])
+    );
+    // unknown type
+    assert_eq!(
+        Err(msgpacker::Error::InvalidEnumVariant),
+        Message::unpack(&[0x93, 0x03, 0xa1, b'n', 0x90])
+    );
+    // request with the length of a notification
+    assert_eq!(
+        Err(msgpacker::Error::InvalidEnumVariant),
+        Message::unpack(&[0x93, 0x00, 0xa1, b'n', 0x90])
+    );
+    // method is not a string
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        Message::unpack(&[0x93, 0x02, 0x01, 0x90])
+    );
+
+    let bytes = Message::from(Request {
+        id: 300,
+        method: "method".into(),
+        params: params((vec![1u32, 2, 3], "x")),
+    })
+    .pack_to_vec();
+    for i in 0..bytes.len() {
+        assert_eq!(
+            Err(msgpacker::Error::BufferTooShort),
+            Message::unpack(&bytes[..i])
+        );
+        assert_eq!(
+            Err(msgpacker::Error::BufferTooShort),
+            Message::unpack_iter(bytes[..i].iter().copied())
+        );
+    }
+}
+
+#[test]
+fn params_arity() {
+    let buf = params((1u8, "a", true));
+    assert_eq!(
+        Ok((1u8, "a".to_string(), true)),
+        <(u8, String, bool)>::unpack_params(&buf)
+    );
+    assert!(<(u8, String)>::unpack_params(&buf).is_err());
+    assert!(<(u8, String, bool, u8)>::unpack_params(&buf).is_err());
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        <(u8,)>::unpack_params(&[0x01])
+    );
+    assert_eq!(Ok(()), <()>::unpack_params(&params(())));
+}
+
+/// Length of the first value of the buffer.
+fn value_len(buf: &[u8]) -> usize {
+    let mut tokens = Tokenizer::new(buf);
+    let mut pending = 1;
+    while pending > 0 {
+        pending = pending - 1
+            + match tokens.next().unwrap().unwrap().token {
+                Token::ArrayStart(len) => len,
+                Token::MapStart(len) => 2 * len,
+                _ => 0,
+            };
+    }
+    tokens.offset()
+}
+
+fn value_case<T>(x: T) -> Vec<u8>
+where
+    T: Packable + Unpackable + PartialEq + core::fmt::Debug,
+    msgpacker::Error: From<T::Error>,
+{
+    let mut buf = Vec::new();
+    assert_eq!(pack_value(&x, &mut buf), buf.len());
+    assert_eq!(x, unpack_value::<T>(&buf).unwrap());
+    buf
+}
+
+#[test]
+fn single_values() {
+    assert_eq!(vec![0xc0], value_case(()));
+    assert_eq!(vec![0x05], value_case(5u8));
+    assert_eq!(vec![0x92, 0x01, 0x02], value_case(vec![1u8, 2]));
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        unpack_value::<String>(&[0x92, 0x01, 0x02])
+    );
+
+    // the encoding depends on the type only, so multi-value types must be wrapped
+    assert_eq!(vec![0x90], value_case(Array(())));
+    assert_eq!(
+        vec![0x92, 0x01, 0xa1, b'a'],
+        value_case(Array((1u8, "a".to_string())))
+    );
+    assert_eq!(vec![0x91, 0x00], value_case(Array(Option::<u8>::None)));
+    assert_eq!(vec![0x92, 0x01, 0x02], value_case(Array(Some(2u8))));
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        unpack_value::<Array<(u8, u8)>>(&[0x93, 0x01, 0x02, 0x03])
+    );
+    assert_eq!(
+        Err(msgpacker::Error::UnexpectedFormatTag),
+        unpack_value::<Array<Option<u8>>>(&[0x92, 0x00, 0x02])
+    );
+    assert!(unpack_value::<(u8, u8)>(&[0x92, 0x01, 0x02]).is_err());
+}
+
+proptest! {
+    #[test]
+    fn request(id: u32, method: String, a: Vec<u64>, b: i32, c: String) {
+        case(Message::from(Request { id, method, params: params((a, b, c)) }));
+    }
+
+    #[test]
+    fn response(id: u32, result: Result<Vec<String>, (u8, i64)>) {
+        let result = match result {
+            Ok(r) => Ok(r.pack_to_vec()),
+            Err(e) => Err(params(e)),
+        };
+        case(Message::from(Response { id, result }));
+    }
+
+    #[test]
+    fn notification(method: String, a: f64, b: Vec<bool>) {
+        let a = if a.is_nan() { 0.0 } else { a };
+        case(Message::from(Notification { method, params: params((a, b)) }));
+    }
+
+    #[test]
+    fn values(a: (u16, Option<String>, Vec<bool>), b: Option<(i8, i8)>, c: Vec<u64>) {
+        for buf in [value_case(Array(a)), value_case(Array(b)), value_case(c)] {
+            prop_assert_eq!(buf.len(), value_len(&buf));
+        }
+    }
+
+    #[test]
+    fn params_round_trip(a: u8, b: String, c: Vec<i64>, d: bool, e: f32) {
+        let e = if e.is_nan() { 0.0 } else { e };
+        let x = (a, b, c, d, e);
+        let buf = params(x.clone());
+        prop_assert_eq!(x, FromParams::unpack_params(&buf).unwrap());
+    }
+}
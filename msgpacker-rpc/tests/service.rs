@@ -0,0 +1,144 @@
+use msgpacker::prelude::*;
+use msgpacker_rpc::blocking::{serve, Client};
+use msgpacker_rpc::{Array, Dispatcher, Error};
+use std::collections::BTreeMap;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+pub struct Item {
+    pub id: u64,
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+#[msgpacker_rpc::service]
+pub trait Inventory {
+    fn get(&self, id: u64) -> Option<Item>;
+    fn put(&self, item: Item) -> Result<(), String>;
+    fn rename(&self, id: u64, name: String) -> Result<Item, String>;
+    fn next(&self, name: String) -> Item;
+    fn count(&self) -> usize;
+    fn clear(&self);
+}
+
+#[derive(Default)]
+struct Store(Mutex<BTreeMap<u64, Item>>);
+
+impl Inventory for Store {
+    fn get(&self, id: u64) -> Option<Item> {
+        self.0.lock().unwrap().get(&id).cloned()
+    }
+
+    fn put(&self, item: Item) -> Result<(), String> {
+        match self.0.lock().unwrap().insert(item.id, item.clone()) {
+            Some(_) => Err(format!("duplicate id {}", item.id)),
+            None => Ok(()),
+        }
+    }
+
+    fn rename(&self, id: u64, name: String) -> Result<Item, String> {
+        let mut items = self.0.lock().unwrap();
+        let item = items
+            .get_mut(&id)
+            .ok_or_else(|| format!("unknown id {id}"))?;
+        item.name = name;
+        Ok(item.clone())
+    }
+
+    fn next(&self, name: String) -> Item {
+        let items = self.0.lock().unwrap();
+        Item {
+            id: items.keys().last().map_or(1, |id| id + 1),
+            name,
+            tags: vec![],
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    fn clear(&self) {
+        self.0.lock().unwrap().clear()
+    }
+}
+
+fn connect(dispatcher: Dispatcher) -> InventoryClient<Client<TcpStream>> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve(&dispatcher, stream.try_clone().unwrap(), stream).unwrap();
+    });
+    let stream = TcpStream::connect(address).unwrap();
+    InventoryClient::new(Client::new(stream.try_clone().unwrap(), stream))
+}
+
+#[test]
+fn typed_calls() {
+    let inventory = connect(Store::default().into_dispatcher());
+    let item = Item {
+        id: 1,
+        name: "bolt".into(),
+        tags: vec!["steel".into(), "m6".into()],
+    };
+
+    assert_eq!(None, inventory.get(1).unwrap());
+    assert_eq!(Ok(()), inventory.put(item.clone()).unwrap());
+    assert_eq!(Some(item.clone()), inventory.get(1).unwrap());
+    assert_eq!(
+        Err("duplicate id 1".to_string()),
+        inventory.put(item.clone()).unwrap()
+    );
+    assert_eq!(1, inventory.count().unwrap());
+    assert_eq!(
+        Item {
+            id: 2,
+            name: "washer".into(),
+            tags: vec![],
+        },
+        inventory.next("washer".into()).unwrap()
+    );
+
+    let renamed = inventory.rename(1, "nut".into()).unwrap().unwrap();
+    assert_eq!("nut", renamed.name);
+    assert_eq!(
+        Err("unknown id 2".to_string()),
+        inventory.rename(2, "nut".into()).unwrap()
+    );
+
+    inventory.clear().unwrap();
+    assert_eq!(0, inventory.count().unwrap());
+}
+
+#[test]
+fn interop_with_untyped_calls() {
+    let mut dispatcher = Store::default().into_dispatcher();
+    dispatcher.register("version", |(): ()| Ok::<_, ()>("1.0"));
+    let inventory = connect(dispatcher);
+    let client = inventory.into_inner();
+
+    // the service wraps every argument, result and error in an array of the values it packs as,
+    // such as the option prefix and the fields of the item
+    let item = Item {
+        id: 7,
+        name: "gear".into(),
+        tags: vec![],
+    };
+    let put = client.call::<_, Array<()>>("put", (Array(item.clone()),));
+    assert_eq!(Array(()), put.unwrap());
+    let raw = client.call_raw("get", vec![0x91, 0x91, 0x07]).unwrap();
+    let mut expected = vec![0x94, 0x01];
+    item.pack(&mut expected);
+    assert_eq!(expected, raw);
+
+    assert_eq!("1.0", client.call::<_, String>("version", ()).unwrap());
+    match client.call::<_, ()>("count", (1u8,)) {
+        Err(Error::Remote(e)) => {
+            assert_eq!("invalid params: unexpected number of params", e.to_string())
+        }
+        e => panic!("unexpected result {e:?}"),
+    }
+}
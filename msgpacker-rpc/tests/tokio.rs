@@ -0,0 +1,211 @@
+use msgpacker::prelude::*;
+use msgpacker_rpc::tokio::{serve, Client};
+use msgpacker_rpc::{Dispatcher, Error, Message, Response};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+use tokio::net::{TcpListener, TcpStream};
+
+fn dispatcher() -> Dispatcher {
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .register("add", |(a, b): (u64, u64)| Ok::<_, ()>(a + b))
+        .register("div", |(a, b): (u64, u64)| match b {
+            0 => Err("division by zero"),
+            b => Ok(a / b),
+        });
+    dispatcher
+}
+
+fn duplex() -> Client<tokio::io::WriteHalf<DuplexStream>> {
+    let (client, server) = tokio::io::duplex(64);
+    tokio::spawn(async move {
+        let (reader, writer) = tokio::io::split(server);
+        serve(&dispatcher(), reader, writer).await.unwrap();
+    });
+    let (reader, writer) = tokio::io::split(client);
+    Client::new(reader, writer)
+}
+
+#[tokio::test]
+async fn calls() {
+    let client = duplex();
+    assert_eq!(5, client.call::<_, u64>("add", (2u8, 3u8)).await.unwrap());
+    client.notify("add", (1u8, 1u8)).await.unwrap();
+    match client.call::<_, u64>("div", (1u8, 0u8)).await {
+        Err(Error::Remote(e)) => assert_eq!("division by zero", e.to_string()),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.call::<_, u64>("sub", (1u8, 0u8)).await {
+        Err(Error::Remote(e)) => assert_eq!("method not found: sub", e.to_string()),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+#[tokio::test]
+async fn params_must_be_single_values() {
+    let client = duplex();
+    let trailing = msgpacker::Error::TrailingBytes {
+        consumed: 2,
+        total: 3,
+    };
+    match client.call::<_, u64>("add", (Some(5u8),)).await {
+        Err(Error::Protocol(e)) => assert_eq!(trailing, e),
+        e => panic!("unexpected result {e:?}"),
+    }
+    match client.notify("add", (Some(5u8),)).await {
+        Err(Error::Protocol(e)) => assert_eq!(trailing, e),
+        e => panic!("unexpected result {e:?}"),
+    }
+    assert_eq!(5, client.call::<_, u64>("add", (2u8, 3u8)).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_calls_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, writer) = stream.into_split();
+        serve(&dispatcher(), reader, writer).await.unwrap();
+    });
+
+    let (reader, writer) = TcpStream::connect(address).await.unwrap().into_split();
+    let client = Arc::new(Client::new(reader, writer));
+    let tasks: Vec<_> = (0..64u64)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let sum: u64 = client.call("add", (i, 1000u64)).await.unwrap();
+                assert_eq!(i + 1000, sum);
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn out_of_order_responses() {
+    let (client, mut server) = tokio::io::duplex(1024);
+    let server = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let mut requests = Vec::new();
+        while requests.len() < 2 {
+            let mut chunk = [0u8; 64];
+            let n = server.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            while let Ok((n, message)) = Message::unpack(&buf) {
+                buf.drain(..n);
+                requests.push(message);
+            }
+        }
+        for request in requests.into_iter().rev() {
+            let Message::Request(request) = request else {
+                panic!("unexpected message {request:?}");
+            };
+            let response = Message::from(Response {
+                id: request.id,
+                result: Ok(request.method.pack_to_vec()),
+            });
+            server.write_all(&response.pack_to_vec()).await.unwrap();
+        }
+    });
+
+    let (reader, writer) = tokio::io::split(client);
+    let client = Client::new(reader, writer);
+    let (first, second) = tokio::join!(
+        client.call::<_, String>("first", ()),
+        client.call::<_, String>("second", ())
+    );
+    assert_eq!("first", first.unwrap());
+    assert_eq!("second", second.unwrap());
+    server.await.unwrap();
+
+    match client.call::<_, ()>("third", ()).await {
+        Err(Error::Closed) | Err(Error::Io(_)) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
+
+#[msgpacker_rpc::service]
+trait Counter {
+    fn add(&self, n: i64) -> i64;
+    fn checked_sub(&self, n: i64) -> Result<i64, String>;
+}
+
+#[derive(Default)]
+struct AtomicCounter(std::sync::atomic::AtomicI64);
+
+impl Counter for AtomicCounter {
+    fn add(&self, n: i64) -> i64 {
+        self.0.fetch_add(n, std::sync::atomic::Ordering::SeqCst) + n
+    }
+
+    fn checked_sub(&self, n: i64) -> Result<i64, String> {
+        let value = self.0.load(std::sync::atomic::Ordering::SeqCst);
+        match value.checked_sub(n).filter(|v| *v >= 0) {
+            Some(v) => {
+                self.0.store(v, std::sync::atomic::Ordering::SeqCst);
+                Ok(v)
+            }
+            None => Err(format!("can't subtract {n} from {value}")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn service_over_duplex() {
+    let (client, server) = tokio::io::duplex(64);
+    tokio::spawn(async move {
+        let (reader, writer) = tokio::io::split(server);
+        let dispatcher = AtomicCounter::default().into_dispatcher();
+        serve(&dispatcher, reader, writer).await.unwrap();
+    });
+    let (reader, writer) = tokio::io::split(client);
+    let counter = CounterClient::new(Client::new(reader, writer));
+
+    assert_eq!(5, counter.add(5).await.unwrap());
+    assert_eq!(3, counter.add(-2).await.unwrap());
+    assert_eq!(Ok(1), counter.checked_sub(2).await.unwrap());
+    assert_eq!(
+        Err("can't subtract 2 from 1".to_string()),
+        counter.checked_sub(2).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn malformed_stream_fails_every_call() {
+    let (client, mut server) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        let mut chunk = [0u8; 64];
+        assert_ne!(0, server.read(&mut chunk).await.unwrap());
+        server.write_all(&[0xc1]).await.unwrap();
+        // keeps the stream open until the client is dropped
+        while server.read(&mut chunk).await.is_ok_and(|n| n > 0) {}
+    });
+
+    let (reader, writer) = tokio::io::split(client);
+    let client = Client::new(reader, writer);
+    for method in ["pending", "later"] {
+        match client.call::<_, ()>(method, ()).await {
+            Err(Error::Protocol(msgpacker::Error::UnexpectedFormatTag)) => (),
+            e => panic!("unexpected result {e:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn large_messages() {
+    let (client, server) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        let (reader, writer) = tokio::io::split(server);
+        serve(&dispatcher(), reader, writer).await.ok();
+    });
+    let (reader, writer) = tokio::io::split(client);
+    let client = Client::with_max_message_len(reader, writer, 4);
+    match client.call::<_, u64>("add", (u32::MAX, 1u8)).await {
+        Err(Error::MessageTooLarge { max: 4 }) => (),
+        e => panic!("unexpected result {e:?}"),
+    }
+}
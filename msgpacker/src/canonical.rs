@@ -0,0 +1,110 @@
+use super::{
+    encode::write_ext_header,
+    extension::unpack_ext_header,
+    helpers::{take_buffer, take_byte, take_num},
+    pack::{pack_array_header, pack_bin, pack_map_header},
+    unpack::{unpack_bytes, unpack_int, unpack_str},
+    Error, Format, Limit, Packable, UnpackLimits,
+};
+use alloc::vec::Vec;
+
+/// Re-encodes a sequence of packed values into the canonical form of
+/// [Packable::pack_canonical](crate::Packable::pack_canonical).
+///
+/// Map entries are sorted by the canonical bytes of their keys. Arrays keep their order, since
+/// sets can't be told apart from arrays once packed. The input must be a valid sequence of
+/// MessagePack values, nested at most [UnpackLimits::max_depth] levels by default.
+pub fn canonicalize(mut buf: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(buf.len());
+    let depth = UnpackLimits::default().max_depth;
+    while !buf.is_empty() {
+        canonical(&mut buf, &mut out, depth)?;
+    }
+    Ok(out)
+}
+
+fn canonical(buf: &mut &[u8], out: &mut Vec<u8>, depth: usize) -> Result<(), Error> {
+    let format = *buf.first().ok_or(Error::BufferTooShort)?;
+    match format {
+        0x00..=Format::POSITIVE_FIXINT
+        | 0xe0..=0xff
+        | Format::UINT8..=Format::UINT64
+        | Format::INT8..=Format::INT64 => {
+            let (n, v) = unpack_int(buf)?;
+            *buf = &buf[n..];
+            match u64::try_from(v) {
+                Ok(v) => v.pack(out),
+                Err(_) => (v as i64).pack(out),
+            };
+        }
+        Format::NIL | Format::FALSE | Format::TRUE => {
+            *buf = &buf[1..];
+            out.push(format);
+        }
+        Format::FLOAT32 => {
+            *buf = &buf[1..];
+            take_num(buf, f32::from_be_bytes)?.pack_canonical(out);
+        }
+        Format::FLOAT64 => {
+            *buf = &buf[1..];
+            take_num(buf, f64::from_be_bytes)?.pack_canonical(out);
+        }
+        Format::BIN8..=Format::BIN32 => {
+            let (n, bytes) = unpack_bytes(buf)?;
+            *buf = &buf[n..];
+            pack_bin(out, bytes);
+        }
+        0xa0..=0xbf | Format::STR8..=Format::STR32 => {
+            let (n, str) = unpack_str(buf)?;
+            *buf = &buf[n..];
+            str.pack(out);
+        }
+        0x90..=0x9f | Format::ARRAY16 | Format::ARRAY32 => {
+            let len = container_len(buf, depth)?;
+            pack_array_header(out, len);
+            for _ in 0..len {
+                canonical(buf, out, depth - 1)?;
+            }
+        }
+        0x80..=0x8f | Format::MAP16 | Format::MAP32 => {
+            let len = container_len(buf, depth)?;
+            let mut entries = Vec::with_capacity(len);
+            for _ in 0..len {
+                let (mut key, mut value) = (Vec::new(), Vec::new());
+                canonical(buf, &mut key, depth - 1)?;
+                canonical(buf, &mut value, depth - 1)?;
+                entries.push((key, value));
+            }
+            entries.sort_unstable();
+            pack_map_header(out, len);
+            for (key, value) in entries {
+                out.extend(key);
+                out.extend(value);
+            }
+        }
+        Format::FIXEXT1..=Format::FIXEXT16 | Format::EXT8..=Format::EXT32 => {
+            let (_, t, len) = unpack_ext_header(buf)?;
+            let payload = take_buffer(buf, len)?;
+            write_ext_header(out, t, len as u32);
+            out.extend_from_slice(payload);
+        }
+        _ => return Err(Error::UnexpectedFormatTag),
+    }
+    Ok(())
+}
+
+/// Reads an array or map header, rejecting lengths that can't fit in the remaining bytes.
+fn container_len(buf: &mut &[u8], depth: usize) -> Result<usize, Error> {
+    if depth == 0 {
+        return Err(Error::LimitExceeded(Limit::Depth));
+    }
+    let len = match take_byte(buf)? {
+        format @ 0x80..=0x9f => format as usize & 0x0f,
+        Format::ARRAY16 | Format::MAP16 => take_num(buf, u16::from_be_bytes)? as usize,
+        _ => take_num(buf, u32::from_be_bytes)? as usize,
+    };
+    if len > buf.len() {
+        return Err(Error::BufferTooShort);
+    }
+    Ok(len)
+}
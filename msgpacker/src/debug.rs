@@ -0,0 +1,277 @@
+//! Human readable renderings of packed bytes, for debugging.
+
+use crate::{Error, Event, Format, Limit, Token, Tokenizer, UnpackLimits};
+use core::fmt;
+
+/// Maximum amount of bytes of a single element printed in the hex column.
+const HEX_BYTES: usize = 8;
+
+/// Maximum amount of characters of a string printed in full.
+const STR_CHARS: usize = 32;
+
+/// Returns a printable rendering of a sequence of packed values.
+///
+/// The [Display](fmt::Display) rendering is an annotated hex dump, with one line per element and
+/// the nesting shown by indentation:
+///
+/// ```text
+/// 0000  dc 00 03                    array16 len=3
+/// 0003  01                            positive fixint 1
+/// ```
+///
+/// The [Debug](fmt::Debug) rendering is a compact, single line notation of the values, such as
+/// `[1, "a", {true: nil}]`, that fits assertion messages. Both renderings stop at the first
+/// malformed element, marking its offset and the error.
+pub fn dump(buf: &[u8]) -> Dump<'_> {
+    Dump { buf, base: 0 }
+}
+
+/// Printable rendering of packed bytes, created by [dump].
+#[derive(Clone, Copy)]
+pub struct Dump<'a> {
+    buf: &'a [u8],
+    base: usize,
+}
+
+/// Failure while rendering: either the formatter or the input failed.
+enum Failure {
+    Fmt,
+    Input(usize, Error),
+}
+
+impl From<fmt::Error> for Failure {
+    fn from(_: fmt::Error) -> Self {
+        Failure::Fmt
+    }
+}
+
+/// Reads the next event, failing at the end of the input or past the maximum depth.
+fn next<'a>(tokens: &mut Tokenizer<'a>, depth: usize) -> Result<Event<'a>, Failure> {
+    if depth >= UnpackLimits::default().max_depth {
+        return Err(Failure::Input(
+            tokens.offset(),
+            Error::LimitExceeded(Limit::Depth),
+        ));
+    }
+    match tokens.next() {
+        Some(Ok(event)) => Ok(event),
+        Some(Err(e)) => Err(Failure::Input(tokens.offset(), e)),
+        None => Err(Failure::Input(tokens.offset(), Error::BufferTooShort)),
+    }
+}
+
+/// Returns the name of the format of the tag.
+fn format_name(tag: u8) -> &'static str {
+    match tag {
+        0x00..=Format::POSITIVE_FIXINT => "positive fixint",
+        Format::FIXMAP..=0x8f => "fixmap",
+        Format::FIXARRAY..=0x9f => "fixarray",
+        Format::FIXSTR..=0xbf => "fixstr",
+        Format::NIL => "nil",
+        Format::FALSE => "false",
+        Format::TRUE => "true",
+        Format::BIN8 => "bin8",
+        Format::BIN16 => "bin16",
+        Format::BIN32 => "bin32",
+        Format::EXT8 => "ext8",
+        Format::EXT16 => "ext16",
+        Format::EXT32 => "ext32",
+        Format::FLOAT32 => "float32",
+        Format::FLOAT64 => "float64",
+        Format::UINT8 => "uint8",
+        Format::UINT16 => "uint16",
+        Format::UINT32 => "uint32",
+        Format::UINT64 => "uint64",
+        Format::INT8 => "int8",
+        Format::INT16 => "int16",
+        Format::INT32 => "int32",
+        Format::INT64 => "int64",
+        Format::FIXEXT1 => "fixext1",
+        Format::FIXEXT2 => "fixext2",
+        Format::FIXEXT4 => "fixext4",
+        Format::FIXEXT8 => "fixext8",
+        Format::FIXEXT16 => "fixext16",
+        Format::STR8 => "str8",
+        Format::STR16 => "str16",
+        Format::STR32 => "str32",
+        Format::ARRAY16 => "array16",
+        Format::ARRAY32 => "array32",
+        Format::MAP16 => "map16",
+        Format::MAP32 => "map32",
+        Format::NEGATIVE_FIXINT.. => "negative fixint",
+        _ => "never used",
+    }
+}
+
+/// Writes up to `max` bytes as lowercase hex, separated by `sep`, followed by `..` if truncated.
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8], sep: &str, max: usize) -> fmt::Result {
+    for (i, b) in bytes.iter().take(max).enumerate() {
+        if i > 0 {
+            f.write_str(sep)?;
+        }
+        write!(f, "{b:02x}")?;
+    }
+    if bytes.len() > max {
+        f.write_str(if sep.is_empty() { ".." } else { " .." })?;
+    }
+    Ok(())
+}
+
+/// Writes a string, quoted and escaped, truncating long strings.
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    match s.char_indices().nth(STR_CHARS) {
+        Some((i, _)) => write!(f, "{:?}..", &s[..i]),
+        None => write!(f, "{s:?}"),
+    }
+}
+
+impl<'a> Dump<'a> {
+    /// Adds `base` to the printed offsets, such as the offset of the buffer in a larger stream.
+    pub fn with_base(mut self, base: usize) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Writes the annotated line of a value and its nested elements.
+    fn lines(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        tokens: &mut Tokenizer<'a>,
+        depth: usize,
+    ) -> Result<(), Failure> {
+        let Event { offset, token } = next(tokens, depth)?;
+        let bytes = &self.buf[offset..tokens.offset()];
+
+        // the hex column fits `HEX_BYTES` bytes, the truncation mark and two spaces
+        let n = bytes.len().min(HEX_BYTES);
+        let width = n * 3 - 1 + if bytes.len() > HEX_BYTES { 3 } else { 0 };
+        write!(f, "{:04x}  ", self.base + offset)?;
+        write_hex(f, bytes, " ", HEX_BYTES)?;
+        write!(
+            f,
+            "{:pad$}",
+            "",
+            pad = HEX_BYTES * 3 + 4 - width + depth * 2
+        )?;
+
+        f.write_str(format_name(bytes[0]))?;
+        match token {
+            Token::Nil | Token::Bool(_) => (),
+            Token::Uint(v) => write!(f, " {v}")?,
+            Token::Int(v) => write!(f, " {v}")?,
+            Token::F32(v) => write!(f, " {v:?}")?,
+            Token::F64(v) => write!(f, " {v:?}")?,
+            Token::Str(s) => {
+                write!(f, " len={} ", s.len())?;
+                write_str(f, s)?;
+            }
+            Token::Bin(b) => write!(f, " len={}", b.len())?,
+            Token::Ext(t, data) => write!(f, " type={t} len={}", data.len())?,
+            Token::ArrayStart(len) => {
+                writeln!(f, " len={len}")?;
+                for _ in 0..len {
+                    self.lines(f, tokens, depth + 1)?;
+                }
+                return Ok(());
+            }
+            Token::MapStart(len) => {
+                writeln!(f, " len={len}")?;
+                for _ in 0..len * 2 {
+                    self.lines(f, tokens, depth + 1)?;
+                }
+                return Ok(());
+            }
+        }
+        writeln!(f)?;
+        Ok(())
+    }
+
+    /// Writes the compact notation of a value.
+    fn compact(
+        f: &mut fmt::Formatter<'_>,
+        tokens: &mut Tokenizer<'a>,
+        depth: usize,
+    ) -> Result<(), Failure> {
+        match next(tokens, depth)?.token {
+            Token::Nil => f.write_str("nil")?,
+            Token::Bool(b) => write!(f, "{b}")?,
+            Token::Uint(v) => write!(f, "{v}")?,
+            Token::Int(v) => write!(f, "{v}")?,
+            Token::F32(v) => write!(f, "{v:?}")?,
+            Token::F64(v) => write!(f, "{v:?}")?,
+            Token::Str(s) => write_str(f, s)?,
+            Token::Bin(b) => {
+                f.write_str("bin(")?;
+                write_hex(f, b, "", 2 * HEX_BYTES)?;
+                f.write_str(")")?;
+            }
+            Token::Ext(t, data) => {
+                write!(f, "ext({t}, ")?;
+                write_hex(f, data, "", 2 * HEX_BYTES)?;
+                f.write_str(")")?;
+            }
+            Token::ArrayStart(len) => {
+                f.write_str("[")?;
+                for i in 0..len {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Self::compact(f, tokens, depth + 1)?;
+                }
+                f.write_str("]")?;
+            }
+            Token::MapStart(len) => {
+                f.write_str("{")?;
+                for i in 0..len {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Self::compact(f, tokens, depth + 1)?;
+                    f.write_str(": ")?;
+                    Self::compact(f, tokens, depth + 1)?;
+                }
+                f.write_str("}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Dump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = Tokenizer::new(self.buf);
+        while !tokens.remaining().is_empty() {
+            match self.lines(f, &mut tokens, 0) {
+                Ok(()) => (),
+                Err(Failure::Fmt) => return Err(fmt::Error),
+                Err(Failure::Input(offset, e)) => {
+                    write!(f, "{:04x}  ", self.base + offset)?;
+                    write_hex(f, &self.buf[offset..], " ", HEX_BYTES)?;
+                    return writeln!(f, "  <- error: {e}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Debug for Dump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = Tokenizer::new(self.buf);
+        let mut first = true;
+        while !tokens.remaining().is_empty() {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+            match Self::compact(f, &mut tokens, 0) {
+                Ok(()) => (),
+                Err(Failure::Fmt) => return Err(fmt::Error),
+                Err(Failure::Input(offset, e)) => {
+                    return write!(f, "<error at {:#x}: {e}>", self.base + offset);
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+//! Low-level writers of the protocol primitives.
+//!
+//! These are the building blocks of the [Packable](crate::Packable) implementations of this
+//! crate, and can be used to write headers of manually framed values. Every function writes the
+//! smallest encoding of the provided value, returning the amount of written bytes.
+
+use core::iter;
+
+pub use crate::format::Format;
+
+/// Writes a nil.
+pub fn write_nil<T>(buf: &mut T) -> usize
+where
+    T: Extend<u8>,
+{
+    buf.extend(iter::once(Format::NIL));
+    1
+}
+
+/// Writes a boolean.
+pub fn write_bool<T>(buf: &mut T, value: bool) -> usize
+where
+    T: Extend<u8>,
+{
+    buf.extend(iter::once(if value { Format::TRUE } else { Format::FALSE }));
+    1
+}
+
+/// Writes an unsigned integer.
+pub fn write_uint<T>(buf: &mut T, value: u64) -> usize
+where
+    T: Extend<u8>,
+{
+    if value <= Format::POSITIVE_FIXINT as u64 {
+        buf.extend(iter::once(value as u8));
+        1
+    } else if value <= u8::MAX as u64 {
+        buf.extend([Format::UINT8, value as u8]);
+        2
+    } else if value <= u16::MAX as u64 {
+        buf.extend(iter::once(Format::UINT16).chain((value as u16).to_be_bytes()));
+        3
+    } else if value <= u32::MAX as u64 {
+        buf.extend(iter::once(Format::UINT32).chain((value as u32).to_be_bytes()));
+        5
+    } else {
+        buf.extend(iter::once(Format::UINT64).chain(value.to_be_bytes()));
+        9
+    }
+}
+
+/// Writes a signed integer.
+///
+/// Non-negative values are written with the unsigned formats, as in [write_uint].
+pub fn write_int<T>(buf: &mut T, value: i64) -> usize
+where
+    T: Extend<u8>,
+{
+    if value >= 0 {
+        write_uint(buf, value as u64)
+    } else if value >= -32 {
+        buf.extend(iter::once(value as u8));
+        1
+    } else if value >= i8::MIN as i64 {
+        buf.extend([Format::INT8, value as u8]);
+        2
+    } else if value >= i16::MIN as i64 {
+        buf.extend(iter::once(Format::INT16).chain((value as i16).to_be_bytes()));
+        3
+    } else if value >= i32::MIN as i64 {
+        buf.extend(iter::once(Format::INT32).chain((value as i32).to_be_bytes()));
+        5
+    } else {
+        buf.extend(iter::once(Format::INT64).chain(value.to_be_bytes()));
+        9
+    }
+}
+
+/// Writes the header of an array with `len` elements.
+pub fn write_array_len<T>(buf: &mut T, len: u32) -> usize
+where
+    T: Extend<u8>,
+{
+    if len <= 15 {
+        buf.extend(iter::once(Format::FIXARRAY | len as u8));
+        1
+    } else if len <= u16::MAX as u32 {
+        buf.extend(iter::once(Format::ARRAY16).chain((len as u16).to_be_bytes()));
+        3
+    } else {
+        buf.extend(iter::once(Format::ARRAY32).chain(len.to_be_bytes()));
+        5
+    }
+}
+
+/// Writes the header of a map with `len` entries.
+pub fn write_map_len<T>(buf: &mut T, len: u32) -> usize
+where
+    T: Extend<u8>,
+{
+    if len <= 15 {
+        buf.extend(iter::once(Format::FIXMAP | len as u8));
+        1
+    } else if len <= u16::MAX as u32 {
+        buf.extend(iter::once(Format::MAP16).chain((len as u16).to_be_bytes()));
+        3
+    } else {
+        buf.extend(iter::once(Format::MAP32).chain(len.to_be_bytes()));
+        5
+    }
+}
+
+/// Writes the header of a string with `len` bytes. The bytes must be written afterwards.
+pub fn write_str_header<T>(buf: &mut T, len: u32) -> usize
+where
+    T: Extend<u8>,
+{
+    if len <= 31 {
+        buf.extend(iter::once(Format::FIXSTR | len as u8));
+        1
+    } else if len <= u8::MAX as u32 {
+        buf.extend([Format::STR8, len as u8]);
+        2
+    } else if len <= u16::MAX as u32 {
+        buf.extend(iter::once(Format::STR16).chain((len as u16).to_be_bytes()));
+        3
+    } else {
+        buf.extend(iter::once(Format::STR32).chain(len.to_be_bytes()));
+        5
+    }
+}
+
+/// Writes the header of a byte array with `len` bytes. The bytes must be written afterwards.
+pub fn write_bin_header<T>(buf: &mut T, len: u32) -> usize
+where
+    T: Extend<u8>,
+{
+    if len <= u8::MAX as u32 {
+        buf.extend([Format::BIN8, len as u8]);
+        2
+    } else if len <= u16::MAX as u32 {
+        buf.extend(iter::once(Format::BIN16).chain((len as u16).to_be_bytes()));
+        3
+    } else {
+        buf.extend(iter::once(Format::BIN32).chain(len.to_be_bytes()));
+        5
+    }
+}
+
+/// Writes the header of an extension with a payload of `len` bytes. The payload must be written
+/// afterwards.
+pub fn write_ext_header<T>(buf: &mut T, ext_type: i8, len: u32) -> usize
+where
+    T: Extend<u8>,
+{
+    let t = ext_type as u8;
+    match len {
+        1 => buf.extend([Format::FIXEXT1, t]),
+        2 => buf.extend([Format::FIXEXT2, t]),
+        4 => buf.extend([Format::FIXEXT4, t]),
+        8 => buf.extend([Format::FIXEXT8, t]),
+        16 => buf.extend([Format::FIXEXT16, t]),
+        _ if len <= u8::MAX as u32 => buf.extend([Format::EXT8, len as u8, t]),
+        _ if len <= u16::MAX as u32 => buf.extend(
+            iter::once(Format::EXT16)
+                .chain((len as u16).to_be_bytes())
+                .chain(iter::once(t)),
+        ),
+        _ => buf.extend(
+            iter::once(Format::EXT32)
+                .chain(len.to_be_bytes())
+                .chain(iter::once(t)),
+        ),
+    }
+    ext_header_len(len as usize)
+}
+
+/// Returns the amount of bytes [write_ext_header] would write for a payload of `len` bytes.
+pub(crate) fn ext_header_len(len: usize) -> usize {
+    match len {
+        1 | 2 | 4 | 8 | 16 => 2,
+        _ if len <= u8::MAX as usize => 3,
+        _ if len <= u16::MAX as usize => 4,
+        _ => 6,
+    }
+}
@@ -1,7 +1,9 @@
+use crate::Limit;
 use core::fmt;
 
-/// Deserialization errors for the protocol implementation.
+/// Serialization and deserialization errors for the protocol implementation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Error {
     /// The provided buffer is too short and yielded an unexpected EOF.
     BufferTooShort,
@@ -9,19 +11,412 @@ pub enum Error {
     InvalidEnumVariant,
     /// The extension is not in accordance to the protocol definition.
     InvalidExtension,
+    /// The extension type id doesn't match the one of the target type.
+    ExtensionTypeMismatch {
+        /// Type id of the target type.
+        expected: i8,
+        /// Type id found in the buffer.
+        found: i8,
+    },
     /// The string is not a valid UTF-8.
     InvalidUtf8,
     /// The protocol format tag is not valid.
     UnexpectedFormatTag,
     /// The provided bin length is not valid.
     UnexpectedBinLength,
+    /// The provided output buffer is too short to hold the packed value.
+    BufferOverflow,
+    /// The encoded length is larger than the capacity of the fixed-size container.
+    CapacityExceeded,
+    /// The encoded integer doesn't fit the target type.
+    IntegerOverflow,
+    /// The encoded number can't be represented exactly by the target float type.
+    PrecisionLoss,
+    /// The string is not a valid decimal integer.
+    InvalidDecimal,
+    /// A required field is absent.
+    MissingField(&'static str),
+    /// The field is not known to the target type.
+    UnknownField,
+    /// The map contains the same key more than once.
+    DuplicateMapKey,
+    /// The input violates the configured decoding limits.
+    LimitExceeded(Limit),
+    /// The value was unpacked without consuming the whole buffer.
+    TrailingBytes {
+        /// Amount of bytes read by the value.
+        consumed: usize,
+        /// Length of the buffer.
+        total: usize,
+    },
+    /// The length of a frame is larger than the maximum.
+    FrameTooLarge {
+        /// Length of the frame.
+        len: usize,
+        /// Maximum allowed length.
+        max: usize,
+    },
+    /// The underlying reader or writer failed.
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
+    /// Error raised by a user implementation.
+    ///
+    /// A message built at runtime is carried by `DetailedError::custom` under `alloc`.
+    Custom(&'static str),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+        match self {
+            Error::BufferTooShort => f.write_str("unexpected end of input"),
+            Error::InvalidEnumVariant => f.write_str("invalid enum variant"),
+            Error::InvalidExtension => f.write_str("invalid extension"),
+            Error::ExtensionTypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "extension type mismatch: expected {expected}, found {found}"
+                )
+            }
+            Error::InvalidUtf8 => f.write_str("invalid UTF-8 string"),
+            Error::UnexpectedFormatTag => f.write_str("unexpected format tag"),
+            Error::UnexpectedBinLength => f.write_str("unexpected bin length"),
+            Error::BufferOverflow => f.write_str("output buffer is too short"),
+            Error::CapacityExceeded => f.write_str("length exceeds the container capacity"),
+            Error::IntegerOverflow => f.write_str("integer out of range for the target type"),
+            Error::PrecisionLoss => f.write_str("number can't be represented exactly"),
+            Error::InvalidDecimal => f.write_str("invalid decimal integer"),
+            Error::MissingField(field) => write!(f, "missing field `{field}`"),
+            Error::UnknownField => f.write_str("unknown field"),
+            Error::DuplicateMapKey => f.write_str("duplicate map key"),
+            Error::LimitExceeded(limit) => write!(f, "{limit} exceeded"),
+            Error::TrailingBytes { consumed, total } => {
+                write!(f, "trailing bytes: consumed {consumed} of {total}")
+            }
+            Error::FrameTooLarge { len, max } => {
+                write!(f, "frame of {len} bytes exceeds the maximum of {max}")
+            }
+            #[cfg(feature = "std")]
+            Error::Io(kind) => write!(f, "I/O error: {kind}"),
+            Error::Custom(message) => f.write_str(message),
+        }
     }
 }
 
 #[cfg(feature = "std")]
 impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::from(&e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&std::io::Error> for Error {
+    fn from(e: &std::io::Error) -> Self {
+        Error::Io(e.kind())
+    }
+}
+
+/// A piece of the location of a failure, attached to an unpack error as it propagates out of a
+/// nested value.
+///
+/// Passed to [Unpackable::error_context](crate::Unpackable::error_context), that forwards it to
+/// [ErrorContext::with_context] for errors that track their location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Context {
+    /// Amount of bytes read before the failing value.
+    Offset(usize),
+    /// Format tag at the start of the failing value.
+    Tag(u8),
+    /// Index of the failing element of an array.
+    Index(usize),
+    /// Index of the map entry whose key failed to unpack.
+    Key(usize),
+    /// Index of the map entry whose value failed to unpack.
+    Value(usize),
+    /// Name of the failing field.
+    Field(&'static str),
+}
+
+/// Location context attached to unpack errors as they propagate out of nested values.
+///
+/// All methods default to a no-op, so errors that don't track their location, such as [Error],
+/// stay unchanged.
+pub trait ErrorContext: Sized {
+    /// Attaches a piece of context, dispatching to the matching method.
+    fn with_context(self, context: Context) -> Self {
+        match context {
+            Context::Offset(offset) => self.with_offset(offset),
+            Context::Tag(tag) => self.with_tag(tag),
+            Context::Index(index) => self.with_index(index),
+            Context::Key(index) => self.with_key(index),
+            Context::Value(index) => self.with_value(index),
+            Context::Field(field) => self.with_field(field),
+        }
+    }
+
+    /// Shifts the byte offset of the error by the amount of bytes read before the failing value.
+    fn with_offset(self, _offset: usize) -> Self {
+        self
+    }
+
+    /// Records the format tag at the start of the failing value, unless one is already set.
+    fn with_tag(self, _tag: u8) -> Self {
+        self
+    }
+
+    /// Records the tag at the start of the failing value buffer, and shifts the offset by the
+    /// amount of bytes read before it.
+    fn with_location(self, buf: &[u8], offset: usize) -> Self {
+        match buf.first() {
+            Some(tag) => self.with_tag(*tag),
+            None => self,
+        }
+        .with_offset(offset)
+    }
+
+    /// Prepends the index of the failing element of an array.
+    fn with_index(self, _index: usize) -> Self {
+        self
+    }
+
+    /// Prepends the index of the map entry whose key failed to unpack.
+    fn with_key(self, _index: usize) -> Self {
+        self
+    }
+
+    /// Prepends the index of the map entry whose value failed to unpack.
+    fn with_value(self, _index: usize) -> Self {
+        self
+    }
+
+    /// Prepends the name of the failing field.
+    fn with_field(self, _field: &'static str) -> Self {
+        self
+    }
+}
+
+impl ErrorContext for Error {}
+
+#[cfg(feature = "alloc")]
+pub mod alloc {
+    use super::*;
+    use ::alloc::string::{String, ToString};
+    use ::alloc::vec::Vec;
+    use core::hash::{Hash, Hasher};
+    #[cfg(feature = "std")]
+    use std::sync::Arc;
+
+    /// A segment of the path to the value that failed to unpack.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum PathSegment {
+        /// Named or positional field of a struct or enum variant.
+        Field(&'static str),
+        /// Element of an array.
+        Index(usize),
+        /// Key of the n-th map entry.
+        Key(usize),
+        /// Value of the n-th map entry.
+        Value(usize),
+    }
+
+    /// An [Error] with the byte offset, format tag and path of the value that failed to unpack.
+    ///
+    /// The context is collected by [crate::unpack_array], [crate::unpack_map] and the
+    /// implementations derived with `#[msgpacker(detailed_errors)]`; values nested inside types
+    /// with a plain [Error] are reported at the start of the outermost value that tracks context.
+    ///
+    /// It may also carry an owned message, and, with `std`, the error that caused it.
+    #[derive(Debug, Clone)]
+    pub struct DetailedError {
+        kind: Error,
+        offset: usize,
+        tag: Option<u8>,
+        // innermost segment first
+        path: Vec<PathSegment>,
+        message: Option<String>,
+        #[cfg(feature = "std")]
+        source: Option<Arc<dyn std::error::Error + Send + Sync>>,
+    }
+
+    impl DetailedError {
+        /// Creates a [Error::Custom] error with an owned message.
+        pub fn custom<M>(message: M) -> Self
+        where
+            M: fmt::Display,
+        {
+            let mut e = Self::from(Error::Custom("custom error"));
+            e.message = Some(message.to_string());
+            e
+        }
+
+        /// Attaches the error that caused this one, returned by [std::error::Error::source].
+        #[cfg(feature = "std")]
+        pub fn with_source<E>(mut self, source: E) -> Self
+        where
+            E: std::error::Error + Send + Sync + 'static,
+        {
+            self.source = Some(Arc::new(source));
+            self
+        }
+
+        /// The underlying error.
+        pub fn kind(&self) -> Error {
+            self.kind
+        }
+
+        /// Byte offset of the failing value, relative to the start of the unpacked buffer.
+        pub fn offset(&self) -> usize {
+            self.offset
+        }
+
+        /// Format tag at the start of the failing value, if available.
+        ///
+        /// Iterator based unpacking doesn't record tags.
+        pub fn tag(&self) -> Option<u8> {
+            self.tag
+        }
+
+        /// Path from the root value to the failing value.
+        pub fn path(&self) -> impl DoubleEndedIterator<Item = &PathSegment> {
+            self.path.iter().rev()
+        }
+
+        /// Owned message of the error, if any.
+        pub fn message(&self) -> Option<&str> {
+            self.message.as_deref()
+        }
+    }
+
+    impl From<Error> for DetailedError {
+        fn from(kind: Error) -> Self {
+            Self {
+                kind,
+                offset: 0,
+                tag: None,
+                path: Vec::new(),
+                message: None,
+                #[cfg(feature = "std")]
+                source: None,
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl From<std::io::Error> for DetailedError {
+        fn from(e: std::io::Error) -> Self {
+            Self::from(Error::from(&e)).with_source(e)
+        }
+    }
+
+    impl From<DetailedError> for Error {
+        fn from(e: DetailedError) -> Self {
+            e.kind
+        }
+    }
+
+    impl PartialEq for DetailedError {
+        fn eq(&self, other: &Self) -> bool {
+            self.kind == other.kind
+                && self.offset == other.offset
+                && self.tag == other.tag
+                && self.path == other.path
+                && self.message == other.message
+        }
+    }
+
+    impl Eq for DetailedError {}
+
+    impl Hash for DetailedError {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.kind.hash(state);
+            self.offset.hash(state);
+            self.tag.hash(state);
+            self.path.hash(state);
+            self.message.hash(state);
+        }
+    }
+
+    impl PartialEq<Error> for DetailedError {
+        fn eq(&self, other: &Error) -> bool {
+            self.kind == *other
+        }
+    }
+
+    impl ErrorContext for DetailedError {
+        fn with_offset(mut self, offset: usize) -> Self {
+            self.offset += offset;
+            self
+        }
+
+        fn with_tag(mut self, tag: u8) -> Self {
+            self.tag.get_or_insert(tag);
+            self
+        }
+
+        fn with_index(mut self, index: usize) -> Self {
+            self.path.push(PathSegment::Index(index));
+            self
+        }
+
+        fn with_key(mut self, index: usize) -> Self {
+            self.path.push(PathSegment::Key(index));
+            self
+        }
+
+        fn with_value(mut self, index: usize) -> Self {
+            self.path.push(PathSegment::Value(index));
+            self
+        }
+
+        fn with_field(mut self, field: &'static str) -> Self {
+            self.path.push(PathSegment::Field(field));
+            self
+        }
+    }
+
+    impl fmt::Display for PathSegment {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                PathSegment::Field(field) => write!(f, ".{field}"),
+                PathSegment::Index(index) => write!(f, "[{index}]"),
+                PathSegment::Key(index) => write!(f, "[{index}].key"),
+                PathSegment::Value(index) => write!(f, "[{index}].value"),
+            }
+        }
+    }
+
+    impl fmt::Display for DetailedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.message {
+                Some(message) => f.write_str(message)?,
+                None => write!(f, "{}", self.kind)?,
+            }
+            if let Some(tag) = self.tag {
+                write!(f, " (tag {tag:#04x})")?;
+            }
+            write!(f, " at byte {}", self.offset)?;
+            if !self.path.is_empty() {
+                f.write_str(" in ")?;
+                for (i, segment) in self.path().enumerate() {
+                    match segment {
+                        PathSegment::Field(field) if i == 0 => f.write_str(field)?,
+                        segment => write!(f, "{segment}")?,
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for DetailedError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source
+                .as_deref()
+                .map(|e| e as &(dyn std::error::Error + 'static))
+        }
+    }
+}
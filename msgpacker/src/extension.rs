@@ -1,306 +1,391 @@
 use super::{
-    error::Error,
-    helpers::{take_buffer, take_buffer_iter, take_byte, take_byte_iter, take_num, take_num_iter},
-    Format, Packable, Unpackable,
+    encode::{ext_header_len, write_ext_header},
+    error::{Context, Error},
+    helpers::{take_buffer, take_byte, take_byte_iter, take_num, take_num_iter, ByteCounter},
+    limits::scope,
+    Format, Packable, UnpackableBorrowed,
 };
-use alloc::{vec, vec::Vec};
-use core::{iter, time::Duration};
-
-/// Custom extension definition as reference to a bytes source.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Extension {
-    /// n-bytes custom extension
-    Ext(i8, Vec<u8>),
-    /// Protocol reserved extension to represent timestamps
-    Timestamp(Duration),
-}
 
-impl Extension {
-    /// Protocol constant for a timestamp extension
-    pub const TIMESTAMP: i8 = -1;
-}
+/// A type packed as the payload of an application extension with a fixed type id.
+///
+/// The `FIXEXT*`/`EXT*` framing is added by [pack_ext] and checked by [unpack_ext], that can be
+/// used to implement [Packable](crate::Packable) and [Unpackable](crate::Unpackable). The derive
+/// macro does both for structs with the `#[msgpacker(ext = 7)]` attribute.
+pub trait ExtensionType: Sized {
+    /// Extension type id. Negative ids are reserved by the protocol.
+    const EXT_TYPE: i8;
 
-impl Packable for Extension {
-    #[allow(unreachable_code)]
-    fn pack<T>(&self, buf: &mut T) -> usize
+    /// Error returned when the payload can't be unpacked.
+    type Error: From<Error>;
+
+    /// Packs the payload, without the extension header, returning the amount of written bytes.
+    fn pack_payload<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>;
+
+    /// Packs the payload in the canonical form, without the extension header, returning the
+    /// amount of written bytes.
+    ///
+    /// See [Packable::pack_canonical]; the default implementation calls
+    /// [ExtensionType::pack_payload].
+    fn pack_payload_canonical<T>(&self, buf: &mut T) -> usize
     where
         T: Extend<u8>,
     {
-        match self {
-            Extension::Ext(t, b) if b.len() == 1 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT1)
-                        .chain(iter::once(*t as u8))
-                        .chain(iter::once(b[0])),
-                );
-                3
-            }
+        self.pack_payload(buf)
+    }
 
-            Extension::Ext(t, b) if b.len() == 2 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT2)
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                4
-            }
+    /// Returns the length of the payload, in bytes.
+    fn payload_len(&self) -> usize;
 
-            Extension::Ext(t, b) if b.len() == 4 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT4)
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                6
-            }
+    /// Unpacks the payload from a buffer holding exactly the payload bytes, returning the amount
+    /// of read bytes.
+    fn unpack_payload(buf: &[u8]) -> Result<(usize, Self), Self::Error>;
 
-            Extension::Ext(t, b) if b.len() == 8 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT8)
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                10
-            }
+    /// Unpacks the payload from an iterator yielding exactly the payload bytes, returning the
+    /// amount of read bytes.
+    fn unpack_payload_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>;
 
-            Extension::Ext(t, b) if b.len() == 16 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT16)
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                18
-            }
+    /// Attaches a piece of the location of a failure to an error of the payload, such as the
+    /// length of the extension header read before it.
+    ///
+    /// Defaults to returning the error unchanged, like
+    /// [Unpackable::error_context](crate::Unpackable::error_context).
+    fn error_context(error: Self::Error, context: Context) -> Self::Error {
+        let _ = context;
+        error
+    }
+}
 
-            Extension::Ext(t, b) if b.len() <= u8::MAX as usize => {
-                buf.extend(
-                    iter::once(Format::EXT8)
-                        .chain(iter::once(b.len() as u8))
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                3 + b.len()
-            }
+/// Unpacks an extension header, returning its length, the extension type and the payload length.
+pub(crate) fn unpack_ext_header(buf: &mut &[u8]) -> Result<(usize, i8, usize), Error> {
+    let (n, len) = match take_byte(buf)? {
+        Format::FIXEXT1 => (2, 1),
+        Format::FIXEXT2 => (2, 2),
+        Format::FIXEXT4 => (2, 4),
+        Format::FIXEXT8 => (2, 8),
+        Format::FIXEXT16 => (2, 16),
+        Format::EXT8 => (3, take_byte(buf)? as usize),
+        Format::EXT16 => (4, take_num(buf, u16::from_be_bytes)? as usize),
+        Format::EXT32 => (6, take_num(buf, u32::from_be_bytes)? as usize),
+        _ => return Err(Error::InvalidExtension),
+    };
+    let t = take_byte(buf)? as i8;
+    scope::bytes(len)?;
+    Ok((n, t, len))
+}
 
-            Extension::Ext(t, b) if b.len() <= u16::MAX as usize => {
-                buf.extend(
-                    iter::once(Format::EXT16)
-                        .chain((b.len() as u16).to_be_bytes().iter().copied())
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                4 + b.len()
-            }
+/// Unpacks an extension header, returning its length, the extension type and the payload length.
+pub(crate) fn unpack_ext_header_iter<I>(bytes: &mut I) -> Result<(usize, i8, usize), Error>
+where
+    I: Iterator<Item = u8>,
+{
+    let (n, len) = match take_byte_iter(bytes.by_ref())? {
+        Format::FIXEXT1 => (2, 1),
+        Format::FIXEXT2 => (2, 2),
+        Format::FIXEXT4 => (2, 4),
+        Format::FIXEXT8 => (2, 8),
+        Format::FIXEXT16 => (2, 16),
+        Format::EXT8 => (3, take_byte_iter(bytes.by_ref())? as usize),
+        Format::EXT16 => (
+            4,
+            take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+        ),
+        Format::EXT32 => (
+            6,
+            take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+        ),
+        _ => return Err(Error::InvalidExtension),
+    };
+    let t = take_byte_iter(bytes.by_ref())? as i8;
+    scope::bytes(len)?;
+    Ok((n, t, len))
+}
 
-            Extension::Ext(t, b) if b.len() <= u32::MAX as usize => {
-                buf.extend(
-                    iter::once(Format::EXT32)
-                        .chain((b.len() as u32).to_be_bytes().iter().copied())
-                        .chain(iter::once(*t as u8))
-                        .chain(b.iter().copied()),
-                );
-                6 + b.len()
-            }
+/// Packs a typed extension into the extendable buffer, returning the amount of written bytes.
+#[allow(unreachable_code)]
+pub fn pack_ext<T, E>(buf: &mut T, ext: &E) -> usize
+where
+    T: Extend<u8>,
+    E: ExtensionType,
+{
+    let len = ext.payload_len();
+    if len > u32::MAX as usize {
+        #[cfg(feature = "strict")]
+        panic!("strict serialization enabled; the buffer is too large");
+        return 0;
+    }
+    let n = write_ext_header(buf, E::EXT_TYPE, len as u32);
+    n + ext.pack_payload(buf)
+}
 
-            Extension::Ext(_, _) => {
-                #[cfg(feature = "strict")]
-                panic!("strict serialization enabled; the buffer is too large");
-                0
-            }
+/// Packs a typed extension in the canonical form, returning the amount of written bytes.
+///
+/// The canonical payload may be shorter than [ExtensionType::payload_len], so it is packed twice:
+/// once to measure it for the header, and once into the buffer.
+#[allow(unreachable_code)]
+pub fn pack_ext_canonical<T, E>(buf: &mut T, ext: &E) -> usize
+where
+    T: Extend<u8>,
+    E: ExtensionType,
+{
+    let mut counter = ByteCounter::default();
+    let len = ext.pack_payload_canonical(&mut counter);
+    if len > u32::MAX as usize {
+        #[cfg(feature = "strict")]
+        panic!("strict serialization enabled; the buffer is too large");
+        return 0;
+    }
+    let n = write_ext_header(buf, E::EXT_TYPE, len as u32);
+    n + ext.pack_payload_canonical(buf)
+}
 
-            Extension::Timestamp(d) if d.as_secs() <= u32::MAX as u64 && d.subsec_nanos() == 0 => {
-                buf.extend(
-                    iter::once(Format::FIXEXT4)
-                        .chain(iter::once(Self::TIMESTAMP as u8))
-                        .chain((d.as_secs() as u32).to_be_bytes().iter().copied()),
-                );
-                6
-            }
+/// Returns the amount of bytes [pack_ext] would write for the typed extension.
+pub fn packed_len_ext<E>(ext: &E) -> usize
+where
+    E: ExtensionType,
+{
+    let len = ext.payload_len();
+    if len > u32::MAX as usize {
+        0
+    } else {
+        ext_header_len(len) + len
+    }
+}
 
-            Extension::Timestamp(d)
-                if d.as_secs() < 1u64 << 34 && d.subsec_nanos() < 1u32 << 30 =>
-            {
-                let secs = d.as_secs();
-                let secs_nanos = ((secs >> 32) & 0b11) as u32;
-                let secs = secs as u32;
-
-                let nanos = d.subsec_nanos() << 2;
-                let nanos = nanos | secs_nanos;
-
-                buf.extend(
-                    iter::once(Format::FIXEXT8)
-                        .chain(iter::once(Self::TIMESTAMP as u8))
-                        .chain(nanos.to_be_bytes().iter().copied())
-                        .chain(secs.to_be_bytes().iter().copied()),
-                );
-                10
-            }
+/// Unpacks a typed extension from the buffer, returning the amount of read bytes.
+///
+/// Returns [Error::ExtensionTypeMismatch] if the extension carries another type id, and
+/// [Error::InvalidExtension] if the payload isn't consumed entirely.
+pub fn unpack_ext<E>(mut buf: &[u8]) -> Result<(usize, E), E::Error>
+where
+    E: ExtensionType,
+{
+    let (n, t, len) = unpack_ext_header(&mut buf)?;
+    if t != E::EXT_TYPE {
+        return Err(Error::ExtensionTypeMismatch {
+            expected: E::EXT_TYPE,
+            found: t,
+        }
+        .into());
+    }
+    let payload = take_buffer(&mut buf, len)?;
+    let (m, ext) =
+        E::unpack_payload(payload).map_err(|e| E::error_context(e, Context::Offset(n)))?;
+    if m != len {
+        return Err(Error::InvalidExtension.into());
+    }
+    Ok((n + len, ext))
+}
 
-            Extension::Timestamp(d) => {
-                buf.extend(
-                    iter::once(Format::EXT8)
-                        .chain(iter::once(12))
-                        .chain(iter::once(Self::TIMESTAMP as u8))
-                        .chain(d.subsec_nanos().to_be_bytes().iter().copied())
-                        .chain(d.as_secs().to_be_bytes().iter().copied()),
-                );
-                15
-            }
+/// Unpacks a typed extension from the iterator, returning the amount of read bytes.
+///
+/// Returns [Error::ExtensionTypeMismatch] if the extension carries another type id, and
+/// [Error::InvalidExtension] if the payload isn't consumed entirely.
+pub fn unpack_ext_iter<I, E>(bytes: I) -> Result<(usize, E), E::Error>
+where
+    I: IntoIterator<Item = u8>,
+    E: ExtensionType,
+{
+    let mut bytes = bytes.into_iter();
+    let (n, t, len) = unpack_ext_header_iter(&mut bytes)?;
+    if t != E::EXT_TYPE {
+        return Err(Error::ExtensionTypeMismatch {
+            expected: E::EXT_TYPE,
+            found: t,
+        }
+        .into());
+    }
+    let mut payload = bytes.take(len);
+    let (m, ext) = E::unpack_payload_iter(payload.by_ref())
+        .map_err(|e| E::error_context(e, Context::Offset(n)))?;
+    if m != len {
+        return match m + payload.count() < len {
+            true => Err(Error::BufferTooShort.into()),
+            false => Err(Error::InvalidExtension.into()),
+        };
+    }
+    Ok((n + len, ext))
+}
+
+/// Extension borrowing its payload from the unpacked buffer.
+///
+/// Unlike [Extension](crate::Extension), it doesn't require `alloc` and doesn't interpret
+/// the protocol reserved types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtensionRef<'a> {
+    /// Extension type id.
+    pub ext_type: i8,
+    /// Payload of the extension.
+    pub data: &'a [u8],
+}
+
+impl<'a> Packable for ExtensionRef<'a> {
+    #[allow(unreachable_code)]
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        if self.data.len() > u32::MAX as usize {
+            #[cfg(feature = "strict")]
+            panic!("strict serialization enabled; the buffer is too large");
+            return 0;
+        }
+        let n = write_ext_header(buf, self.ext_type, self.data.len() as u32);
+        buf.extend(self.data.iter().copied());
+        n + self.data.len()
+    }
+
+    fn packed_len(&self) -> usize {
+        if self.data.len() > u32::MAX as usize {
+            0
+        } else {
+            ext_header_len(self.data.len()) + self.data.len()
         }
     }
 }
 
-impl Unpackable for Extension {
+impl<'a> UnpackableBorrowed<'a> for ExtensionRef<'a> {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            Format::FIXEXT1 => {
-                let t = take_byte(&mut buf)? as i8;
-                let x = take_byte(&mut buf)?;
-                Ok((3, Extension::Ext(t, vec![x])))
-            }
-            Format::FIXEXT2 => {
-                let t = take_byte(&mut buf)? as i8;
-                let b = take_buffer(&mut buf, 2)?;
-                Ok((4, Extension::Ext(t, b.to_vec())))
+    fn unpack_borrowed(mut buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+        let (n, ext_type, len) = unpack_ext_header(&mut buf)?;
+        let data = take_buffer(&mut buf, len)?;
+        Ok((n + len, ExtensionRef { ext_type, data }))
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub mod alloc {
+    use super::*;
+    use crate::helpers::take_buffer_iter;
+    use crate::timestamp::Timestamp;
+    use crate::Unpackable;
+    use ::alloc::vec::Vec;
+    use core::time::Duration;
+
+    /// Custom extension definition as reference to a bytes source.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum Extension {
+        /// n-bytes custom extension
+        Ext(i8, Vec<u8>),
+        /// Protocol reserved extension to represent timestamps
+        Timestamp(Duration),
+    }
+
+    impl Extension {
+        /// Protocol constant for a timestamp extension
+        pub const TIMESTAMP: i8 = -1;
+
+        fn timestamp(secs: u64, nanos: u32) -> Result<Self, Error> {
+            if nanos >= Timestamp::NANOS_PER_SEC {
+                return Err(Error::InvalidExtension);
             }
-            Format::FIXEXT4 => {
-                let t = take_byte(&mut buf)? as i8;
-                if t == Self::TIMESTAMP {
+            Ok(Extension::Timestamp(Duration::new(secs, nanos)))
+        }
+
+        fn from_payload(t: i8, b: Vec<u8>) -> Result<Self, Error> {
+            let mut buf = b.as_slice();
+            match (t, b.len()) {
+                (Self::TIMESTAMP, 4) => {
                     let secs = take_num(&mut buf, u32::from_be_bytes)?;
-                    Ok((6, Extension::Timestamp(Duration::from_secs(secs as u64))))
-                } else {
-                    let b = take_buffer(&mut buf, 4)?;
-                    Ok((6, Extension::Ext(t, b.to_vec())))
+                    Ok(Extension::Timestamp(Duration::from_secs(secs as u64)))
                 }
-            }
-            Format::FIXEXT8 => {
-                let t = take_byte(&mut buf)? as i8;
-                if t == Self::TIMESTAMP {
+                (Self::TIMESTAMP, 8) => {
                     let data = take_num(&mut buf, u64::from_be_bytes)?;
 
                     let nanos = (data >> 34) as u32;
                     let secs = data & ((1u64 << 34) - 1);
 
-                    Ok((10, Extension::Timestamp(Duration::new(secs, nanos))))
-                } else {
-                    let b = take_buffer(&mut buf, 8)?;
-                    Ok((10, Extension::Ext(t, b.to_vec())))
+                    Self::timestamp(secs, nanos)
                 }
-            }
-            Format::FIXEXT16 => {
-                let t = take_byte(&mut buf)? as i8;
-                let b = take_buffer(&mut buf, 16)?;
-                Ok((18, Extension::Ext(t, b.to_vec())))
-            }
-            Format::EXT8 => {
-                let len = take_byte(&mut buf)? as usize;
-                let t = take_byte(&mut buf)? as i8;
-                if len == 12 && t == Self::TIMESTAMP {
+                (Self::TIMESTAMP, 12) => {
                     let nanos = take_num(&mut buf, u32::from_be_bytes)?;
                     let secs = take_num(&mut buf, u64::from_be_bytes)?;
-                    Ok((15, Extension::Timestamp(Duration::new(secs, nanos))))
-                } else {
-                    let b = take_buffer(&mut buf, len)?;
-                    Ok((3 + len, Extension::Ext(t, b.to_vec())))
+                    Self::timestamp(secs, nanos)
                 }
+                _ => Ok(Extension::Ext(t, b)),
             }
-            Format::EXT16 => {
-                let len = take_num(&mut buf, u16::from_be_bytes)? as usize;
-                let t = take_byte(&mut buf)? as i8;
-                let b = take_buffer(&mut buf, len)?;
-                Ok((4 + len, Extension::Ext(t, b.to_vec())))
-            }
-            Format::EXT32 => {
-                let len = take_num(&mut buf, u32::from_be_bytes)? as usize;
-                let t = take_byte(&mut buf)? as i8;
-                let b = take_buffer(&mut buf, len)?;
-                Ok((6 + len, Extension::Ext(t, b.to_vec())))
-            }
-            _ => Err(Error::InvalidExtension),
         }
     }
 
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            Format::FIXEXT1 => {
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                let x = take_byte_iter(bytes.by_ref())?;
-                Ok((3, Extension::Ext(t, vec![x])))
-            }
-            Format::FIXEXT2 => {
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                let b = take_buffer_iter(bytes.by_ref(), 2)?;
-                Ok((4, Extension::Ext(t, b)))
-            }
-            Format::FIXEXT4 => {
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                if t == Self::TIMESTAMP {
-                    let secs = take_num_iter(bytes.by_ref(), u32::from_be_bytes)?;
-                    Ok((6, Extension::Timestamp(Duration::from_secs(secs as u64))))
-                } else {
-                    let b = take_buffer_iter(bytes.by_ref(), 4)?;
-                    Ok((6, Extension::Ext(t, b)))
+    impl Packable for Extension {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            match self {
+                Extension::Ext(t, b) => ExtensionRef {
+                    ext_type: *t,
+                    data: b,
                 }
-            }
-            Format::FIXEXT8 => {
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                if t == Self::TIMESTAMP {
-                    let data = take_num_iter(bytes.by_ref(), u64::from_be_bytes)?;
+                .pack(buf),
 
-                    let nanos = (data >> 34) as u32;
-                    let secs = data & ((1u64 << 34) - 1);
+                Extension::Timestamp(d)
+                    if d.as_secs() <= u32::MAX as u64 && d.subsec_nanos() == 0 =>
+                {
+                    write_ext_header(buf, Self::TIMESTAMP, 4);
+                    buf.extend((d.as_secs() as u32).to_be_bytes());
+                    6
+                }
+
+                Extension::Timestamp(d)
+                    if d.as_secs() < 1u64 << 34 && d.subsec_nanos() < 1u32 << 30 =>
+                {
+                    let secs = d.as_secs();
+                    let secs_nanos = ((secs >> 32) & 0b11) as u32;
+                    let secs = secs as u32;
 
-                    Ok((10, Extension::Timestamp(Duration::new(secs, nanos))))
-                } else {
-                    let b = take_buffer_iter(bytes.by_ref(), 8)?;
-                    Ok((10, Extension::Ext(t, b)))
+                    let nanos = d.subsec_nanos() << 2;
+                    let nanos = nanos | secs_nanos;
+
+                    write_ext_header(buf, Self::TIMESTAMP, 8);
+                    buf.extend(nanos.to_be_bytes().into_iter().chain(secs.to_be_bytes()));
+                    10
                 }
-            }
-            Format::FIXEXT16 => {
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                let b = take_buffer_iter(bytes.by_ref(), 16)?;
-                Ok((18, Extension::Ext(t, b)))
-            }
-            Format::EXT8 => {
-                let len = take_byte_iter(bytes.by_ref())? as usize;
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                if len == 12 && t == Self::TIMESTAMP {
-                    let nanos = take_num_iter(bytes.by_ref(), u32::from_be_bytes)?;
-                    let secs = take_num_iter(bytes.by_ref(), u64::from_be_bytes)?;
-                    Ok((15, Extension::Timestamp(Duration::new(secs, nanos))))
-                } else {
-                    let b = take_buffer_iter(bytes.by_ref(), len)?;
-                    Ok((3 + len, Extension::Ext(t, b)))
+
+                Extension::Timestamp(d) => {
+                    write_ext_header(buf, Self::TIMESTAMP, 12);
+                    buf.extend(
+                        d.subsec_nanos()
+                            .to_be_bytes()
+                            .into_iter()
+                            .chain(d.as_secs().to_be_bytes()),
+                    );
+                    15
                 }
             }
-            Format::EXT16 => {
-                let len = take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize;
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                let b = take_buffer_iter(bytes.by_ref(), len)?;
-                Ok((4 + len, Extension::Ext(t, b)))
-            }
-            Format::EXT32 => {
-                let len = take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize;
-                let t = take_byte_iter(bytes.by_ref())? as i8;
-                let b = take_buffer_iter(bytes.by_ref(), len)?;
-                Ok((6 + len, Extension::Ext(t, b)))
-            }
-            _ => Err(Error::InvalidExtension),
+        }
+    }
+
+    impl Unpackable for Extension {
+        type Error = Error;
+
+        fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            let (n, t, len) = unpack_ext_header(&mut buf)?;
+            let b = take_buffer(&mut buf, len)?;
+            Ok((n + len, Self::from_payload(t, b.to_vec())?))
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            let mut bytes = bytes.into_iter();
+            let (n, t, len) = unpack_ext_header_iter(&mut bytes)?;
+            let b = take_buffer_iter(bytes, len)?;
+            Ok((n + len, Self::from_payload(t, b)?))
         }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
-    use super::*;
+    use super::alloc::*;
+    use crate::{Packable, Unpackable};
+    use core::time::Duration;
     use proptest::prelude::*;
 
     proptest! {
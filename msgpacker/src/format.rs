@@ -1,39 +1,81 @@
+/// The format markers of the protocol.
+///
+/// The fix formats carry their value in the low bits of the marker; the constants of these
+/// formats hold the marker with the value bits cleared.
+#[derive(Debug, Clone, Copy)]
 pub struct Format {}
 
 impl Format {
+    /// Nil.
+    pub const NIL: u8 = 0xc0;
+    /// Boolean `true`.
     pub const TRUE: u8 = 0xc3;
+    /// Boolean `false`.
     pub const FALSE: u8 = 0xc2;
+    /// Mask of a positive integer up to 127, stored in the marker.
     pub const POSITIVE_FIXINT: u8 = 0x7f;
+    /// Prefix of a negative integer down to -32, stored in the low 5 bits of the marker.
+    pub const NEGATIVE_FIXINT: u8 = 0xe0;
+    /// Prefix of a map with up to 15 entries, stored in the low 4 bits of the marker.
+    pub const FIXMAP: u8 = 0x80;
+    /// Prefix of an array with up to 15 elements, stored in the low 4 bits of the marker.
+    pub const FIXARRAY: u8 = 0x90;
+    /// Prefix of a string with up to 31 bytes, stored in the low 5 bits of the marker.
+    pub const FIXSTR: u8 = 0xa0;
+    /// 8-bit unsigned integer.
     pub const UINT8: u8 = 0xcc;
+    /// 16-bit unsigned integer.
     pub const UINT16: u8 = 0xcd;
+    /// 32-bit unsigned integer.
     pub const UINT32: u8 = 0xce;
+    /// 64-bit unsigned integer.
     pub const UINT64: u8 = 0xcf;
+    /// 8-bit signed integer.
     pub const INT8: u8 = 0xd0;
+    /// 16-bit signed integer.
     pub const INT16: u8 = 0xd1;
+    /// 32-bit signed integer.
     pub const INT32: u8 = 0xd2;
+    /// 64-bit signed integer.
     pub const INT64: u8 = 0xd3;
+    /// Single precision float.
     pub const FLOAT32: u8 = 0xca;
+    /// Double precision float.
     pub const FLOAT64: u8 = 0xcb;
+    /// Byte array with an 8-bit length.
     pub const BIN8: u8 = 0xc4;
+    /// Byte array with a 16-bit length.
     pub const BIN16: u8 = 0xc5;
+    /// Byte array with a 32-bit length.
     pub const BIN32: u8 = 0xc6;
+    /// String with an 8-bit length.
     pub const STR8: u8 = 0xd9;
+    /// String with a 16-bit length.
     pub const STR16: u8 = 0xda;
+    /// String with a 32-bit length.
     pub const STR32: u8 = 0xdb;
+    /// Array with a 16-bit length.
     pub const ARRAY16: u8 = 0xdc;
+    /// Array with a 32-bit length.
     pub const ARRAY32: u8 = 0xdd;
+    /// Map with a 16-bit length.
     pub const MAP16: u8 = 0xde;
+    /// Map with a 32-bit length.
     pub const MAP32: u8 = 0xdf;
-}
-
-#[cfg(feature = "alloc")]
-impl Format {
+    /// Extension with a 1-byte payload.
     pub const FIXEXT1: u8 = 0xd4;
+    /// Extension with a 2-byte payload.
     pub const FIXEXT2: u8 = 0xd5;
+    /// Extension with a 4-byte payload.
     pub const FIXEXT4: u8 = 0xd6;
+    /// Extension with an 8-byte payload.
     pub const FIXEXT8: u8 = 0xd7;
+    /// Extension with a 16-byte payload.
     pub const FIXEXT16: u8 = 0xd8;
+    /// Extension with an 8-bit length.
     pub const EXT8: u8 = 0xc7;
+    /// Extension with a 16-bit length.
     pub const EXT16: u8 = 0xc8;
+    /// Extension with a 32-bit length.
     pub const EXT32: u8 = 0xc9;
 }
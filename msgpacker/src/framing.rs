@@ -0,0 +1,318 @@
+//! Length-prefixed frames over blocking streams.
+//!
+//! Every frame is a length header followed by the packed bytes of a single value. The header lets
+//! a reader allocate the whole frame at once, and skip a frame that is too large or that fails to
+//! unpack without losing track of the stream.
+
+use crate::{Error, Packable, SliceWriter, Unpackable};
+use std::io::{self, ErrorKind, Read, Write};
+
+/// Default maximum length of the payload of a frame.
+pub const MAX_FRAME_LEN: usize = 1 << 24;
+
+/// Largest amount of bytes of a length header, reserved in front of the packed value.
+const MAX_HEADER_LEN: usize = (usize::BITS as usize).div_ceil(7);
+
+/// Encoding of the length header of a frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+    /// Four bytes, big-endian.
+    #[default]
+    U32Be,
+    /// Unsigned LEB128, seven bits per byte starting with the least significant ones.
+    Varint,
+}
+
+impl LengthPrefix {
+    /// Largest length the header can encode.
+    pub const fn max_len(&self) -> usize {
+        match self {
+            LengthPrefix::U32Be => u32::MAX as usize,
+            LengthPrefix::Varint => usize::MAX,
+        }
+    }
+
+    /// Writes the header of a frame with `len` bytes, returning the amount of written bytes.
+    ///
+    /// The length must not be larger than [LengthPrefix::max_len].
+    pub fn write<T>(&self, buf: &mut T, len: usize) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            LengthPrefix::U32Be => {
+                buf.extend((len as u32).to_be_bytes());
+                4
+            }
+            LengthPrefix::Varint => {
+                let mut len = len;
+                let mut n = 0;
+                loop {
+                    n += 1;
+                    if len < 0x80 {
+                        buf.extend([len as u8]);
+                        return n;
+                    }
+                    buf.extend([len as u8 | 0x80]);
+                    len >>= 7;
+                }
+            }
+        }
+    }
+
+    /// Reads the header of a frame, or `None` if the stream ends before it.
+    ///
+    /// Returns [Error::IntegerOverflow] if a varint doesn't fit in a `usize`.
+    pub fn read<R>(&self, reader: &mut R) -> Result<Option<usize>, Error>
+    where
+        R: Read,
+    {
+        let Some(first) = read_byte(reader)? else {
+            return Ok(None);
+        };
+        match self {
+            LengthPrefix::U32Be => {
+                let mut bytes = [first, 0, 0, 0];
+                reader.read_exact(&mut bytes[1..])?;
+                Ok(Some(u32::from_be_bytes(bytes) as usize))
+            }
+            LengthPrefix::Varint => {
+                let mut byte = first;
+                let mut len = 0usize;
+                let mut shift = 0;
+                loop {
+                    let bits = (byte & 0x7f) as usize;
+                    if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                        return Err(Error::IntegerOverflow);
+                    }
+                    len |= bits << shift;
+                    if byte & 0x80 == 0 {
+                        return Ok(Some(len));
+                    }
+                    shift += 7;
+                    byte = read_byte(reader)?.ok_or(Error::Io(ErrorKind::UnexpectedEof))?;
+                }
+            }
+        }
+    }
+}
+
+/// Reads a single byte, or `None` at the end of the stream.
+fn read_byte<R>(reader: &mut R) -> Result<Option<u8>, Error>
+where
+    R: Read,
+{
+    let mut byte = [0u8];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Writes packed values as length-prefixed frames.
+///
+/// Frames are written with a single call to [Write::write_all]; the writer isn't flushed.
+#[derive(Debug)]
+pub struct FramedWriter<W> {
+    writer: W,
+    prefix: LengthPrefix,
+    max_frame_len: usize,
+    buf: Vec<u8>,
+}
+
+impl<W> FramedWriter<W>
+where
+    W: Write,
+{
+    /// Creates a writer with [LengthPrefix::U32Be] headers and frames up to [MAX_FRAME_LEN]
+    /// bytes.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            prefix: LengthPrefix::default(),
+            max_frame_len: MAX_FRAME_LEN,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Sets the encoding of the length headers.
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Sets the maximum length of the payload of a frame.
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Packs the value into a frame, returning the amount of written bytes, header included.
+    ///
+    /// Returns [Error::FrameTooLarge], without writing anything, if the packed value is larger
+    /// than the maximum frame length.
+    pub fn write<T>(&mut self, value: &T) -> Result<usize, Error>
+    where
+        T: Packable + ?Sized,
+    {
+        // the value is packed once, and the header is written with the length of the packed bytes
+        self.buf.clear();
+        self.buf.resize(MAX_HEADER_LEN, 0);
+        value.pack(&mut self.buf);
+        let len = self.buf.len() - MAX_HEADER_LEN;
+        self.check(len)?;
+
+        let mut header = [0u8; MAX_HEADER_LEN];
+        let n = self.prefix.write(&mut SliceWriter::new(&mut header), len);
+        let start = MAX_HEADER_LEN - n;
+        self.buf[start..MAX_HEADER_LEN].copy_from_slice(&header[..n]);
+        self.writer.write_all(&self.buf[start..])?;
+        Ok(self.buf.len() - start)
+    }
+
+    /// Writes already packed bytes as a frame, returning the amount of written bytes, header
+    /// included.
+    ///
+    /// Returns [Error::FrameTooLarge], without writing anything, if the payload is larger than
+    /// the maximum frame length.
+    pub fn write_frame(&mut self, payload: &[u8]) -> Result<usize, Error> {
+        self.check(payload.len())?;
+        self.buf.clear();
+        self.prefix.write(&mut self.buf, payload.len());
+        self.buf.extend_from_slice(payload);
+        self.writer.write_all(&self.buf)?;
+        Ok(self.buf.len())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.writer.flush()?)
+    }
+
+    fn check(&self, len: usize) -> Result<(), Error> {
+        let max = self.max_frame_len.min(self.prefix.max_len());
+        match len > max {
+            true => Err(Error::FrameTooLarge { len, max }),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<W> FramedWriter<W> {
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads values from length-prefixed frames.
+///
+/// The payload of each frame is read into a buffer that is reused across frames, so it is
+/// allocated at most once per frame, up to the maximum frame length.
+///
+/// Errors of a single frame don't break the stream: a frame larger than the maximum is skipped by
+/// its declared length, and a frame that fails to unpack is already consumed, so the next read
+/// starts at the following frame. A malformed header or an I/O error can't be recovered from.
+#[derive(Debug)]
+pub struct FramedReader<R> {
+    reader: R,
+    prefix: LengthPrefix,
+    max_frame_len: usize,
+    buf: Vec<u8>,
+}
+
+impl<R> FramedReader<R>
+where
+    R: Read,
+{
+    /// Creates a reader of [LengthPrefix::U32Be] headers and frames up to [MAX_FRAME_LEN] bytes.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            prefix: LengthPrefix::default(),
+            max_frame_len: MAX_FRAME_LEN,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Sets the encoding of the length headers.
+    pub fn with_prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Sets the maximum length of the payload of a frame.
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Reads the value of the next frame, or `None` at the end of the stream.
+    ///
+    /// The value must span the whole frame, or [Error::TrailingBytes] is returned.
+    pub fn read<T>(&mut self) -> Result<Option<T>, T::Error>
+    where
+        T: Unpackable,
+        T::Error: From<Error>,
+    {
+        match self.read_frame()? {
+            Some(payload) => T::unpack_exact(payload).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the payload of the next frame, or `None` at the end of the stream.
+    ///
+    /// Returns [Error::FrameTooLarge] if the declared length is larger than the maximum, after
+    /// skipping the frame.
+    pub fn read_frame(&mut self) -> Result<Option<&[u8]>, Error> {
+        let Some(len) = self.prefix.read(&mut self.reader)? else {
+            return Ok(None);
+        };
+        if len > self.max_frame_len {
+            let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())?;
+            if skipped != len as u64 {
+                return Err(Error::Io(ErrorKind::UnexpectedEof));
+            }
+            return Err(Error::FrameTooLarge {
+                len,
+                max: self.max_frame_len,
+            });
+        }
+        self.buf.clear();
+        self.buf.resize(len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        Ok(Some(&self.buf))
+    }
+}
+
+impl<R> FramedReader<R> {
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
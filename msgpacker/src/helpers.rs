@@ -1,5 +1,20 @@
 use super::Error;
 
+/// An extendable sink that discards the bytes, counting them.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ByteCounter {
+    pub count: usize,
+}
+
+impl Extend<u8> for ByteCounter {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = u8>,
+    {
+        self.count += iter.into_iter().count();
+    }
+}
+
 pub fn take_byte_iter<I>(mut bytes: I) -> Result<u8, Error>
 where
     I: Iterator<Item = u8>,
@@ -27,7 +42,6 @@ pub fn take_num<V, const N: usize>(buf: &mut &[u8], f: fn([u8; N]) -> V) -> Resu
     Ok(f(val))
 }
 
-#[cfg(feature = "alloc")]
 pub fn take_buffer<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
     if buf.len() < len {
         return Err(Error::BufferTooShort);
@@ -0,0 +1,316 @@
+//! Conversions between packed values and [serde_json::Value].
+//!
+//! The MessagePack data model is mapped to JSON with the following rules:
+//!
+//! - nil, booleans and strings map to their JSON counterparts.
+//! - Integers are preserved exactly over the whole `u64` and `i64` ranges. JSON integers are
+//!   packed with the smallest form, and other numbers as `float64`.
+//! - Floats are widened to `f64`. NaN and infinities, that JSON can't represent, become `null`.
+//! - Bins become a base64 string or an array of bytes, according to [BinFormat]. JSON has no bin
+//!   type, so they are packed back as a string or an array.
+//! - Extensions become the tagged object `{"$ext": <type>, "data": <bin>}`, with the payload
+//!   encoded as a bin. Objects of exactly this shape are packed back as extensions, accepting
+//!   either bin encoding.
+//! - Map keys that are not strings are stringified with their JSON text, so the integer key `1`
+//!   becomes `"1"` and the key `[1, 2]` becomes `"[1,2]"`. They are packed back as strings.
+//!
+//! [Packable] and [Unpackable] are implemented for [serde_json::Value] with the default
+//! [JsonOptions].
+
+use crate::{
+    encode::{
+        write_array_len, write_bool, write_ext_header, write_int, write_map_len, write_nil,
+        write_uint,
+    },
+    extension::unpack_ext_header_iter,
+    helpers::{take_buffer_iter, take_byte_iter, take_num_iter, ByteCounter},
+    unpack::unpack_int_iter,
+    Error, Format, Limit, Packable, UnpackLimits, Unpackable,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::iter;
+use serde_json::{Map, Number, Value};
+
+/// Key of the extension type in the tagged object of an extension.
+const EXT_TYPE_KEY: &str = "$ext";
+
+/// Key of the payload in the tagged object of an extension.
+const EXT_DATA_KEY: &str = "data";
+
+/// JSON representation of bins and extension payloads.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinFormat {
+    /// A string with the standard, padded base64 encoding.
+    #[default]
+    Base64,
+    /// An array of byte numbers.
+    Array,
+}
+
+/// Options of the conversions between packed values and JSON.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JsonOptions {
+    /// JSON representation of bins and extension payloads.
+    pub bin: BinFormat,
+}
+
+impl JsonOptions {
+    /// Creates the default options.
+    pub const fn new() -> Self {
+        Self {
+            bin: BinFormat::Base64,
+        }
+    }
+
+    /// Sets the JSON representation of bins and extension payloads.
+    pub const fn with_bin(mut self, bin: BinFormat) -> Self {
+        self.bin = bin;
+        self
+    }
+
+    /// Converts a packed value to JSON, returning the amount of read bytes.
+    pub fn to_json(&self, buf: &[u8]) -> Result<(usize, Value), Error> {
+        self.to_json_iter(buf.iter().copied())
+    }
+
+    /// Converts a packed value from the iterator to JSON, returning the amount of read bytes.
+    pub fn to_json_iter<I>(&self, bytes: I) -> Result<(usize, Value), Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        self.value(&mut bytes.into_iter(), UnpackLimits::default().max_depth)
+    }
+
+    /// Packs a JSON value into the extendable buffer, returning the amount of written bytes.
+    pub fn from_json<T>(&self, value: &Value, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match value {
+            Value::Null => write_nil(buf),
+            Value::Bool(b) => write_bool(buf, *b),
+            Value::Number(n) => match (n.as_u64(), n.as_i64()) {
+                (Some(v), _) => write_uint(buf, v),
+                (_, Some(v)) => write_int(buf, v),
+                _ => n.as_f64().unwrap_or(f64::NAN).pack(buf),
+            },
+            Value::String(s) => s.pack(buf),
+            Value::Array(a) => {
+                write_array_len(buf, a.len() as u32)
+                    + a.iter().map(|v| self.from_json(v, buf)).sum::<usize>()
+            }
+            Value::Object(m) => match ext(m) {
+                Some((t, data)) => {
+                    let n = write_ext_header(buf, t, data.len() as u32);
+                    buf.extend(data.iter().copied());
+                    n + data.len()
+                }
+                None => {
+                    write_map_len(buf, m.len() as u32)
+                        + m.iter()
+                            .map(|(k, v)| k.pack(buf) + self.from_json(v, buf))
+                            .sum::<usize>()
+                }
+            },
+        }
+    }
+
+    fn bin(&self, bytes: &[u8]) -> Value {
+        match self.bin {
+            BinFormat::Base64 => Value::String(base64_encode(bytes)),
+            BinFormat::Array => Value::from(bytes),
+        }
+    }
+
+    fn value<I>(&self, bytes: &mut I, depth: usize) -> Result<(usize, Value), Error>
+    where
+        I: Iterator<Item = u8>,
+    {
+        let format = take_byte_iter(bytes.by_ref())?;
+        let (n, len) = match format {
+            0x00..=Format::POSITIVE_FIXINT
+            | Format::NEGATIVE_FIXINT..
+            | Format::UINT8..=Format::UINT64
+            | Format::INT8..=Format::INT64 => {
+                let (n, v) = unpack_int_iter(format, bytes.by_ref())?;
+                let v = match u64::try_from(v) {
+                    Ok(v) => Value::from(v),
+                    Err(_) => Value::from(v as i64),
+                };
+                return Ok((n, v));
+            }
+            Format::NIL => return Ok((1, Value::Null)),
+            Format::TRUE => return Ok((1, Value::Bool(true))),
+            Format::FALSE => return Ok((1, Value::Bool(false))),
+            Format::FLOAT32 => {
+                let v = take_num_iter(bytes.by_ref(), f32::from_be_bytes)?;
+                return Ok((5, float(v as f64)));
+            }
+            Format::FLOAT64 => {
+                let v = take_num_iter(bytes.by_ref(), f64::from_be_bytes)?;
+                return Ok((9, float(v)));
+            }
+            Format::FIXEXT1..=Format::FIXEXT16 | Format::EXT8..=Format::EXT32 => {
+                let mut bytes = iter::once(format).chain(bytes.by_ref());
+                let (n, t, len) = unpack_ext_header_iter(&mut bytes)?;
+                let data = take_buffer_iter(bytes, len)?;
+                let mut map = Map::new();
+                map.insert(EXT_TYPE_KEY.into(), Value::from(t));
+                map.insert(EXT_DATA_KEY.into(), self.bin(&data));
+                return Ok((n + len, Value::Object(map)));
+            }
+            Format::FIXSTR..=0xbf => (1, format as usize & 0x1f),
+            Format::FIXARRAY..=0x9f | Format::FIXMAP..=0x8f => (1, format as usize & 0x0f),
+            Format::BIN8 | Format::STR8 => (2, take_byte_iter(bytes.by_ref())? as usize),
+            Format::BIN16 | Format::STR16 | Format::ARRAY16 | Format::MAP16 => (
+                3,
+                take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+            ),
+            Format::BIN32 | Format::STR32 | Format::ARRAY32 | Format::MAP32 => (
+                5,
+                take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+            ),
+            _ => return Err(Error::UnexpectedFormatTag),
+        };
+
+        match format {
+            Format::BIN8..=Format::BIN32 => {
+                let data = take_buffer_iter(bytes.by_ref(), len)?;
+                Ok((n + len, self.bin(&data)))
+            }
+            Format::FIXSTR..=0xbf | Format::STR8..=Format::STR32 => {
+                let data = take_buffer_iter(bytes.by_ref(), len)?;
+                let s = String::from_utf8(data).map_err(|_| Error::InvalidUtf8)?;
+                Ok((n + len, Value::String(s)))
+            }
+            _ if depth == 0 => Err(Error::LimitExceeded(Limit::Depth)),
+            Format::FIXARRAY..=0x9f | Format::ARRAY16 | Format::ARRAY32 => {
+                let mut n = n;
+                let array = (0..len)
+                    .map(|_| {
+                        let (count, v) = self.value(bytes, depth - 1)?;
+                        n += count;
+                        Ok(v)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok((n, Value::Array(array)))
+            }
+            _ => {
+                let mut n = n;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let (count, k) = self.value(bytes, depth - 1)?;
+                    let k = match k {
+                        Value::String(s) => s,
+                        k => k.to_string(),
+                    };
+                    let (count_v, v) = self.value(bytes, depth - 1)?;
+                    n += count + count_v;
+                    map.insert(k, v);
+                }
+                Ok((n, Value::Object(map)))
+            }
+        }
+    }
+}
+
+/// Converts a float to JSON, mapping NaN and infinities to `null`.
+fn float(v: f64) -> Value {
+    Number::from_f64(v).map_or(Value::Null, Value::Number)
+}
+
+/// Returns the type and payload of a tagged extension object.
+fn ext(map: &Map<String, Value>) -> Option<(i8, Vec<u8>)> {
+    if map.len() != 2 {
+        return None;
+    }
+    let t = map.get(EXT_TYPE_KEY)?.as_i64()?;
+    let t = i8::try_from(t).ok()?;
+    let data = match map.get(EXT_DATA_KEY)? {
+        Value::String(s) => base64_decode(s)?,
+        Value::Array(a) => a
+            .iter()
+            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some((t, data))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64[(n >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    let chunks = s.chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+    for (i, chunk) in chunks.enumerate() {
+        let last = i == s.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut n = 0u32;
+        for c in &chunk[..4 - padding] {
+            let v = BASE64.iter().position(|b| b == c)? as u32;
+            n = (n << 6) | v;
+        }
+        n <<= 6 * padding as u32;
+        bytes.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(bytes)
+}
+
+impl Packable for Value {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        JsonOptions::new().from_json(self, buf)
+    }
+
+    fn packed_len(&self) -> usize {
+        let mut counter = ByteCounter::default();
+        self.pack(&mut counter)
+    }
+}
+
+impl Unpackable for Value {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        JsonOptions::new().to_json(buf)
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        JsonOptions::new().to_json_iter(bytes)
+    }
+}
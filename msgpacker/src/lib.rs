@@ -1,9 +1,5 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-#![feature(
-    iter_array_chunks,
-    maybe_uninit_array_assume_init,
-    maybe_uninit_uninit_array
-)]
+#![feature(iter_array_chunks, maybe_uninit_array_assume_init)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
@@ -11,22 +7,63 @@
 extern crate alloc;
 
 #[cfg(feature = "alloc")]
-mod extension;
+mod canonical;
+
+pub mod debug;
+pub mod encode;
+#[cfg(feature = "std")]
+pub mod framing;
+#[cfg(feature = "json")]
+pub mod json;
 
 mod binary;
 mod error;
+mod extension;
 mod format;
 mod helpers;
+mod limits;
+mod lossy;
 mod pack;
+mod scan;
+mod timestamp;
+mod tokenizer;
 mod unpack;
+mod wide;
+mod writer;
 
-pub use error::Error;
-use format::Format;
-pub use pack::{pack_array, pack_map};
+pub use binary::MsgPackerBinSlice;
+pub use error::{Context, Error, ErrorContext};
+pub use extension::{
+    pack_ext, pack_ext_canonical, packed_len_ext, unpack_ext, unpack_ext_iter, ExtensionRef,
+    ExtensionType,
+};
+pub use format::Format;
+use helpers::ByteCounter;
+pub use limits::{Limit, UnpackLimits};
+pub use lossy::Lossy;
+pub use pack::{
+    pack_array, pack_array_canonical, pack_bin, pack_map, pack_map_canonical, pack_set_canonical,
+    packed_len_array, packed_len_bin, packed_len_map,
+};
+pub use scan::{ElementHeader, ElementKind, ElementLen, ValueScanner};
+pub use timestamp::{
+    pack_timestamp, packed_len_timestamp, unpack_timestamp, unpack_timestamp_iter, Timestamp,
+};
+pub use tokenizer::{Event, Token, Tokenizer};
 pub use unpack::{unpack_array, unpack_array_iter, unpack_map, unpack_map_iter};
+pub use wide::{DecimalStr, ExtInt, WideInt};
+pub use writer::{HasherSink, SliceWriter};
 
 #[cfg(feature = "alloc")]
-pub use extension::Extension;
+pub use canonical::canonicalize;
+#[cfg(feature = "alloc")]
+pub use error::alloc::{DetailedError, PathSegment};
+#[cfg(feature = "alloc")]
+pub use extension::alloc::Extension;
+#[cfg(feature = "alloc")]
+pub use unpack::{unpack_bin, unpack_bin_iter};
+#[cfg(feature = "digest")]
+pub use writer::HashSink;
 
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
@@ -43,6 +80,39 @@ where
     value.pack_to_vec()
 }
 
+/// Packs the provided packable value into a slice of bytes, returning the amount of written bytes.
+///
+/// Returns [Error::BufferOverflow] if the value doesn't fit in the slice; the slice is left
+/// untouched past the point where it ran out of space.
+pub fn pack_to_slice<T>(value: &T, buf: &mut [u8]) -> Result<usize, Error>
+where
+    T: Packable,
+{
+    let mut writer = SliceWriter::new(buf);
+
+    value.pack(&mut writer);
+
+    writer.finish()
+}
+
+/// Unpacks a value that must span the whole buffer.
+///
+/// Returns [Error::TrailingBytes] if the value doesn't consume every byte of the buffer.
+pub fn unpack_exact<T>(buf: &[u8]) -> Result<T, <T as Unpackable>::Error>
+where
+    T: Unpackable,
+{
+    T::unpack_exact(buf)
+}
+
+/// Alias of [unpack_exact].
+pub fn from_slice_exact<T>(buf: &[u8]) -> Result<T, <T as Unpackable>::Error>
+where
+    T: Unpackable,
+{
+    T::unpack_exact(buf)
+}
+
 /// A packable type.
 pub trait Packable {
     /// Pack a value into the extendable buffer, returning the amount of written bytes.
@@ -50,10 +120,36 @@ pub trait Packable {
     where
         T: Extend<u8>;
 
+    /// Returns the exact amount of bytes [Packable::pack] would write, without allocating.
+    ///
+    /// The default implementation packs the value into a sink that only counts the bytes.
+    fn packed_len(&self) -> usize {
+        let mut counter = ByteCounter::default();
+
+        self.pack(&mut counter);
+
+        counter.count
+    }
+
+    /// Packs the value in the canonical form, returning the amount of written bytes.
+    ///
+    /// The canonical form is deterministic: positive integers use the shortest unsigned form, NaN
+    /// floats are normalized, and the entries of maps and sets are sorted by their packed bytes.
+    /// Without `alloc`, maps and sets keep their iteration order.
+    ///
+    /// The default implementation calls [Packable::pack], that is already canonical for most
+    /// types. Implementations that pack signed integers, floats, maps or sets should override it.
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        self.pack(buf)
+    }
+
     /// Packs the value into a vector of bytes.
     #[cfg(feature = "alloc")]
     fn pack_to_vec(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut bytes = Vec::with_capacity(self.packed_len());
 
         self.pack(&mut bytes);
 
@@ -63,7 +159,7 @@ pub trait Packable {
 
 impl<X> Packable for &X
 where
-    X: Packable,
+    X: Packable + ?Sized,
 {
     fn pack<T>(&self, buf: &mut T) -> usize
     where
@@ -71,11 +167,22 @@ where
     {
         X::pack(self, buf)
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        X::pack_canonical(self, buf)
+    }
+
+    fn packed_len(&self) -> usize {
+        X::packed_len(self)
+    }
 }
 
 impl<X> Packable for &mut X
 where
-    X: Packable,
+    X: Packable + ?Sized,
 {
     fn pack<T>(&self, buf: &mut T) -> usize
     where
@@ -83,6 +190,17 @@ where
     {
         X::pack(self, buf)
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        X::pack_canonical(self, buf)
+    }
+
+    fn packed_len(&self) -> usize {
+        X::packed_len(self)
+    }
 }
 
 /// An unpackable type.
@@ -101,6 +219,32 @@ pub trait Unpackable: Sized {
     /// bytes.
     fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error>;
 
+    /// Attaches a piece of the location of a failure to an error of this type, as it propagates
+    /// out of the collections and tuples holding the value.
+    ///
+    /// Defaults to returning the error unchanged. Types whose error tracks its location forward
+    /// the context to [ErrorContext::with_context], and containers forward it to the type of
+    /// their elements.
+    fn error_context(error: Self::Error, context: Context) -> Self::Error {
+        let _ = context;
+        error
+    }
+
+    /// Unpacks a value that must span the whole buffer.
+    ///
+    /// Returns [Error::TrailingBytes] if the value doesn't consume every byte of the buffer.
+    fn unpack_exact(buf: &[u8]) -> Result<Self, Self::Error> {
+        let (consumed, value) = Self::unpack(buf)?;
+        if consumed != buf.len() {
+            return Err(Error::TrailingBytes {
+                consumed,
+                total: buf.len(),
+            }
+            .into());
+        }
+        Ok(value)
+    }
+
     /// Unpacks a value from an iterator of bytes, returning the deserialized value and the amount
     /// of read bytes.
     ///
@@ -111,9 +255,39 @@ pub trait Unpackable: Sized {
         I: IntoIterator<Item = u8>;
 }
 
+/// An unpackable type that may borrow from the buffer.
+///
+/// Implemented for every [Unpackable] type, and for types that hold slices of the input, such as
+/// `&'a str`, `&'a [u8]` and [MsgPackerBinSlice]. Borrowed values can't be unpacked from
+/// iterators.
+pub trait UnpackableBorrowed<'a>: Sized {
+    /// Concrete error implementation for the serialization.
+    ///
+    /// Must interop with [Error].
+    type Error: From<Error>;
+
+    /// Unpacks a value from the buffer, returning the deserialized value and the amount of read
+    /// bytes.
+    fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error>;
+}
+
+impl<'a, X> UnpackableBorrowed<'a> for X
+where
+    X: Unpackable,
+{
+    type Error = <X as Unpackable>::Error;
+
+    fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+        X::unpack(buf)
+    }
+}
+
 /// Required types for the library.
 pub mod prelude {
-    pub use super::{Error, Packable, Unpackable};
+    pub use super::{
+        from_slice_exact, unpack_exact, Error, ExtensionRef, ExtensionType, Packable, Timestamp,
+        Unpackable, UnpackableBorrowed,
+    };
 
     #[cfg(feature = "alloc")]
     pub use super::binary::alloc::MsgPackerBin;
@@ -0,0 +1,301 @@
+use super::{
+    helpers::{take_buffer, take_byte},
+    ElementHeader, ElementKind, ElementLen, Error, Unpackable,
+};
+use core::fmt;
+
+/// A limit of [UnpackLimits].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// [UnpackLimits::max_depth].
+    Depth,
+    /// [UnpackLimits::max_container_len].
+    ContainerLen,
+    /// [UnpackLimits::max_bytes_len].
+    BytesLen,
+    /// [UnpackLimits::max_alloc].
+    Alloc,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Depth => f.write_str("maximum nesting depth"),
+            Limit::ContainerLen => f.write_str("maximum container length"),
+            Limit::BytesLen => f.write_str("maximum str, bin or ext length"),
+            Limit::Alloc => f.write_str("maximum decoded size"),
+        }
+    }
+}
+
+/// Limits enforced on untrusted input while it is decoded.
+///
+/// [UnpackLimits::unpack] and [UnpackLimits::unpack_iter] apply the limits to the arrays, maps,
+/// strs, bins and extensions read by the decoded type, through [crate::unpack_array],
+/// [crate::unpack_map] and the other unpack helpers, and fail with [Error::LimitExceeded] as soon
+/// as one is violated. Depth and container lengths are counted as the type reads them, so the
+/// fields of a derived struct, that are packed without a container header, count at the depth of
+/// the struct itself.
+///
+/// [UnpackLimits::validate] instead walks raw MessagePack, without a target type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnpackLimits {
+    /// Maximum nesting depth of arrays and maps.
+    pub max_depth: usize,
+    /// Maximum amount of entries of a single array or map.
+    pub max_container_len: usize,
+    /// Maximum length of a single str, bin or ext payload.
+    pub max_bytes_len: usize,
+    /// Maximum sum of all str, bin and ext payload lengths and container entries.
+    pub max_alloc: usize,
+}
+
+impl Default for UnpackLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_container_len: 1 << 20,
+            max_bytes_len: 1 << 24,
+            max_alloc: 1 << 26,
+        }
+    }
+}
+
+impl UnpackLimits {
+    /// Limits that accept any well-formed input.
+    pub const UNLIMITED: Self = Self {
+        max_depth: usize::MAX,
+        max_container_len: usize::MAX,
+        max_bytes_len: usize::MAX,
+        max_alloc: usize::MAX,
+    };
+
+    /// Validates the whole buffer against the limits, as a sequence of raw MessagePack values.
+    ///
+    /// Values are walked once, without allocating, and every length prefix is checked against
+    /// the amount of remaining bytes, so a short payload can't announce a huge container.
+    ///
+    /// The walk follows the MessagePack data model, not the layout of a Rust type: structs,
+    /// tuples and options are packed as concatenated values without a container header, so the
+    /// depth and lengths seen here may differ from the ones read by [Unpackable::unpack], and
+    /// collections of zero-sized types, that are packed as an empty sequence of bytes, will not
+    /// pass the remaining bytes check. Use [UnpackLimits::unpack] to check the limits against
+    /// what a type actually reads.
+    pub fn validate(&self, mut buf: &[u8]) -> Result<(), Error> {
+        let mut budget = self.max_alloc;
+        while !buf.is_empty() {
+            self.walk(&mut buf, 0, &mut budget)?;
+        }
+        Ok(())
+    }
+
+    /// Unpacks a value from the buffer, applying the limits to every collection it reads.
+    ///
+    /// Without the `std` feature the limits can't be tracked while decoding, and the buffer is
+    /// checked with [UnpackLimits::validate] before it is unpacked instead.
+    pub fn unpack<T>(&self, buf: &[u8]) -> Result<(usize, T), <T as Unpackable>::Error>
+    where
+        T: Unpackable,
+    {
+        #[cfg(not(feature = "std"))]
+        self.validate(buf)?;
+        scope::with(self, || T::unpack(buf))
+    }
+
+    /// Unpacks a value from the iterator, applying the limits to every collection it reads.
+    #[cfg(feature = "std")]
+    pub fn unpack_iter<T, I>(&self, bytes: I) -> Result<(usize, T), <T as Unpackable>::Error>
+    where
+        T: Unpackable,
+        I: IntoIterator<Item = u8>,
+    {
+        scope::with(self, || T::unpack_iter(bytes))
+    }
+
+    fn walk(&self, buf: &mut &[u8], depth: usize, budget: &mut usize) -> Result<(), Error> {
+        let header = ElementHeader::from_tag(take_byte(buf)?)?;
+        let len = match header.len {
+            ElementLen::Fixed(len) => len,
+            ElementLen::Prefixed(n) => take_buffer(buf, n)?
+                .iter()
+                .fold(0, |len, b| (len << 8) | *b as usize),
+        };
+        match header.kind {
+            ElementKind::Scalar => take_buffer(buf, len).map(|_| ()),
+            ElementKind::Bytes => self.bytes(buf, len, budget),
+            ElementKind::Ext => {
+                take_byte(buf)?;
+                self.bytes(buf, len, budget)
+            }
+            ElementKind::Array => self.array(buf, len, depth, budget),
+            ElementKind::Map => self.map(buf, len, depth, budget),
+        }
+    }
+
+    fn bytes(&self, buf: &mut &[u8], len: usize, budget: &mut usize) -> Result<(), Error> {
+        if len > self.max_bytes_len {
+            return Err(Error::LimitExceeded(Limit::BytesLen));
+        }
+        Self::charge(budget, len)?;
+        take_buffer(buf, len).map(|_| ())
+    }
+
+    fn array(
+        &self,
+        buf: &mut &[u8],
+        len: usize,
+        depth: usize,
+        budget: &mut usize,
+    ) -> Result<(), Error> {
+        self.container(buf, len, len, depth, budget)?;
+        (0..len).try_for_each(|_| self.walk(buf, depth + 1, budget))
+    }
+
+    fn map(
+        &self,
+        buf: &mut &[u8],
+        len: usize,
+        depth: usize,
+        budget: &mut usize,
+    ) -> Result<(), Error> {
+        let values = len.saturating_mul(2);
+        self.container(buf, len, values, depth, budget)?;
+        (0..values).try_for_each(|_| self.walk(buf, depth + 1, budget))
+    }
+
+    fn container(
+        &self,
+        buf: &[u8],
+        len: usize,
+        values: usize,
+        depth: usize,
+        budget: &mut usize,
+    ) -> Result<(), Error> {
+        if depth >= self.max_depth {
+            return Err(Error::LimitExceeded(Limit::Depth));
+        }
+        if len > self.max_container_len {
+            return Err(Error::LimitExceeded(Limit::ContainerLen));
+        }
+        // every value takes at least one byte
+        if values > buf.len() {
+            return Err(Error::BufferTooShort);
+        }
+        Self::charge(budget, len)
+    }
+
+    fn charge(budget: &mut usize, len: usize) -> Result<(), Error> {
+        *budget = budget
+            .checked_sub(len)
+            .ok_or(Error::LimitExceeded(Limit::Alloc))?;
+        Ok(())
+    }
+}
+
+/// Limits of the decode running on the current thread, applied by the unpack helpers.
+#[cfg(feature = "std")]
+pub(crate) mod scope {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Clone, Copy)]
+    struct State {
+        limits: UnpackLimits,
+        depth: usize,
+        budget: usize,
+    }
+
+    std::thread_local! {
+        static STATE: Cell<Option<State>> = const { Cell::new(None) };
+    }
+
+    /// Restores the limits of the enclosing decode, if any.
+    struct Restore(Option<State>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            STATE.with(|s| s.set(self.0));
+        }
+    }
+
+    /// Runs the decode with the limits applied.
+    pub fn with<R>(limits: &UnpackLimits, f: impl FnOnce() -> R) -> R {
+        let state = State {
+            limits: *limits,
+            depth: 0,
+            budget: limits.max_alloc,
+        };
+        let _restore = Restore(STATE.with(|s| s.replace(Some(state))));
+        f()
+    }
+
+    /// Leaves a container when dropped.
+    pub struct Nested(bool);
+
+    impl Drop for Nested {
+        fn drop(&mut self) {
+            if self.0 {
+                update(|state| state.depth -= 1);
+            }
+        }
+    }
+
+    fn update(f: impl FnOnce(&mut State)) {
+        STATE.with(|s| {
+            if let Some(mut state) = s.get() {
+                f(&mut state);
+                s.set(Some(state));
+            }
+        })
+    }
+
+    /// Enters an array or map of `len` entries, until the returned guard is dropped.
+    pub fn container(len: usize) -> Result<Nested, Error> {
+        let Some(mut state) = STATE.with(|s| s.get()) else {
+            return Ok(Nested(false));
+        };
+        if state.depth >= state.limits.max_depth {
+            return Err(Error::LimitExceeded(Limit::Depth));
+        }
+        if len > state.limits.max_container_len {
+            return Err(Error::LimitExceeded(Limit::ContainerLen));
+        }
+        UnpackLimits::charge(&mut state.budget, len)?;
+        state.depth += 1;
+        STATE.with(|s| s.set(Some(state)));
+        Ok(Nested(true))
+    }
+
+    /// Reads a str, bin or ext payload of `len` bytes.
+    pub fn bytes(len: usize) -> Result<(), Error> {
+        let Some(mut state) = STATE.with(|s| s.get()) else {
+            return Ok(());
+        };
+        if len > state.limits.max_bytes_len {
+            return Err(Error::LimitExceeded(Limit::BytesLen));
+        }
+        UnpackLimits::charge(&mut state.budget, len)?;
+        STATE.with(|s| s.set(Some(state)));
+        Ok(())
+    }
+}
+
+/// Without `std` the limits are only checked by [UnpackLimits::validate].
+#[cfg(not(feature = "std"))]
+pub(crate) mod scope {
+    use super::*;
+
+    pub fn with<R>(_limits: &UnpackLimits, f: impl FnOnce() -> R) -> R {
+        f()
+    }
+
+    pub struct Nested;
+
+    pub fn container(_len: usize) -> Result<Nested, Error> {
+        Ok(Nested)
+    }
+
+    pub fn bytes(_len: usize) -> Result<(), Error> {
+        Ok(())
+    }
+}
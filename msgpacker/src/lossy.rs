@@ -0,0 +1,22 @@
+use core::ops::{Deref, DerefMut};
+
+/// Wrapper struct to mark floats that may be rounded when unpacked
+///
+/// Plain `f32` and `f64` accept integers and narrower floats only when the value is represented
+/// exactly; this wrapper rounds to the nearest representable value instead. Packing is unchanged.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Lossy<T>(pub T);
+
+impl<T> Deref for Lossy<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Lossy<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
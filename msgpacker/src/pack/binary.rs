@@ -1,29 +1,69 @@
-use super::{Format, Packable};
+use super::Packable;
 use crate::binary::MsgPackerBinSlice;
-use core::iter;
+use crate::encode::{write_bin_header, write_str_header};
+
+/// Packs a byte slice as bin into the extendable buffer, returning the amount of written bytes.
+#[allow(unreachable_code)]
+pub fn pack_bin<T, B>(buf: &mut T, bytes: B) -> usize
+where
+    T: Extend<u8>,
+    B: AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    let Ok(len) = u32::try_from(bytes.len()) else {
+        #[cfg(feature = "strict")]
+        panic!("strict serialization enabled; the buffer is too large");
+        return 0;
+    };
+    let n = write_bin_header(buf, len);
+    buf.extend(bytes.iter().copied());
+    n + bytes.len()
+}
+
+/// Returns the amount of bytes [pack_bin] would write for the byte slice.
+pub fn packed_len_bin<B>(bytes: B) -> usize
+where
+    B: AsRef<[u8]>,
+{
+    let len = bytes.as_ref().len();
+    if len <= u8::MAX as usize {
+        2 + len
+    } else if len <= u16::MAX as usize {
+        3 + len
+    } else if len <= u32::MAX as usize {
+        5 + len
+    } else {
+        0
+    }
+}
 
 impl<'a> Packable for MsgPackerBinSlice<'a> {
-    #[allow(unreachable_code)]
     fn pack<T>(&self, buf: &mut T) -> usize
     where
         T: Extend<u8>,
     {
-        let n = if self.len() <= u8::MAX as usize {
-            buf.extend(iter::once(Format::BIN8).chain(iter::once(self.len() as u8)));
-            2
-        } else if self.len() <= u16::MAX as usize {
-            buf.extend(iter::once(Format::BIN16).chain((self.len() as u16).to_be_bytes()));
-            3
-        } else if self.len() <= u32::MAX as usize {
-            buf.extend(iter::once(Format::BIN32).chain((self.len() as u32).to_be_bytes()));
-            5
-        } else {
-            #[cfg(feature = "strict")]
-            panic!("strict serialization enabled; the buffer is too large");
-            return 0;
-        };
-        buf.extend(self.iter().copied());
-        n + self.len()
+        pack_bin(buf, self.0)
+    }
+
+    fn packed_len(&self) -> usize {
+        packed_len_bin(self.0)
+    }
+}
+
+/// Byte slices are packed as bin, so they can be unpacked borrowing from the buffer as `&[u8]`.
+///
+/// `Vec<u8>` is packed as an array of integers instead, like any other `Vec`; [pack_bin] packs it
+/// as bin.
+impl Packable for [u8] {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        pack_bin(buf, self)
+    }
+
+    fn packed_len(&self) -> usize {
+        packed_len_bin(self)
     }
 }
 
@@ -33,33 +73,36 @@ impl Packable for str {
     where
         T: Extend<u8>,
     {
-        let n = if self.len() <= 31 {
-            buf.extend(iter::once((self.len() as u8 & 0x1f) | 0xa0));
-            1
-        } else if self.len() <= u8::MAX as usize {
-            buf.extend(iter::once(Format::STR8).chain(iter::once(self.len() as u8)));
-            2
-        } else if self.len() <= u16::MAX as usize {
-            buf.extend(iter::once(Format::STR16).chain((self.len() as u16).to_be_bytes()));
-            3
-        } else if self.len() <= u32::MAX as usize {
-            buf.extend(iter::once(Format::STR32).chain((self.len() as u32).to_be_bytes()));
-            5
-        } else {
+        let Ok(len) = u32::try_from(self.len()) else {
             #[cfg(feature = "strict")]
             panic!("strict serialization enabled; the buffer is too large");
             return 0;
         };
+        let n = write_str_header(buf, len);
         buf.extend(self.as_bytes().iter().copied());
         n + self.len()
     }
+
+    fn packed_len(&self) -> usize {
+        if self.len() <= 31 {
+            1 + self.len()
+        } else if self.len() <= u8::MAX as usize {
+            2 + self.len()
+        } else if self.len() <= u16::MAX as usize {
+            3 + self.len()
+        } else if self.len() <= u32::MAX as usize {
+            5 + self.len()
+        } else {
+            0
+        }
+    }
 }
 
 #[cfg(feature = "alloc")]
 mod alloc {
     use super::*;
     use crate::binary::alloc::MsgPackerBin;
-    use ::alloc::string::String;
+    use ::alloc::{borrow::Cow, boxed::Box, string::String};
 
     impl Packable for MsgPackerBin {
         fn pack<T>(&self, buf: &mut T) -> usize
@@ -68,6 +111,49 @@ mod alloc {
         {
             self.as_slice().pack(buf)
         }
+
+        fn packed_len(&self) -> usize {
+            self.as_slice().packed_len()
+        }
+    }
+
+    impl Packable for Box<[u8]> {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_bin(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_bin(self)
+        }
+    }
+
+    impl<'a> Packable for Cow<'a, [u8]> {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_bin(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_bin(self)
+        }
+    }
+
+    impl<'a> Packable for Cow<'a, str> {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            self.as_ref().pack(buf)
+        }
+
+        fn packed_len(&self) -> usize {
+            self.as_ref().packed_len()
+        }
     }
 
     impl Packable for String {
@@ -77,5 +163,28 @@ mod alloc {
         {
             self.as_str().pack(buf)
         }
+
+        fn packed_len(&self) -> usize {
+            self.as_str().packed_len()
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+mod heapless {
+    use super::*;
+    use ::heapless::String;
+
+    impl<const N: usize> Packable for String<N> {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            self.as_str().pack(buf)
+        }
+
+        fn packed_len(&self) -> usize {
+            self.as_str().packed_len()
+        }
     }
 }
@@ -1,8 +1,42 @@
-use super::{Format, Packable};
-use core::{borrow::Borrow, iter};
+use super::Packable;
+use crate::encode::{write_array_len, write_map_len};
+use core::borrow::Borrow;
 
-/// Packs an array into the extendable buffer, returning the amount of written bytes.
+/// Packs an array header, returning the amount of written bytes, or `None` if the length doesn't
+/// fit the protocol.
 #[allow(unreachable_code)]
+pub(crate) fn pack_array_header<T>(buf: &mut T, len: usize) -> Option<usize>
+where
+    T: Extend<u8>,
+{
+    match u32::try_from(len) {
+        Ok(len) => Some(write_array_len(buf, len)),
+        Err(_) => {
+            #[cfg(feature = "strict")]
+            panic!("strict serialization enabled; the buffer is too large");
+            None
+        }
+    }
+}
+
+/// Packs a map header, returning the amount of written bytes, or `None` if the length doesn't
+/// fit the protocol.
+#[allow(unreachable_code)]
+pub(crate) fn pack_map_header<T>(buf: &mut T, len: usize) -> Option<usize>
+where
+    T: Extend<u8>,
+{
+    match u32::try_from(len) {
+        Ok(len) => Some(write_map_len(buf, len)),
+        Err(_) => {
+            #[cfg(feature = "strict")]
+            panic!("strict serialization enabled; the buffer is too large");
+            None
+        }
+    }
+}
+
+/// Packs an array into the extendable buffer, returning the amount of written bytes.
 pub fn pack_array<T, A, I, V>(buf: &mut T, iter: A) -> usize
 where
     T: Extend<u8>,
@@ -11,26 +45,13 @@ where
     V: Packable,
 {
     let values = iter.into_iter();
-    let len = values.len();
-    let n = if len <= 15 {
-        buf.extend(iter::once(((len & 0x0f) as u8) | 0x90));
-        1
-    } else if len <= u16::MAX as usize {
-        buf.extend(iter::once(Format::ARRAY16).chain((len as u16).to_be_bytes()));
-        3
-    } else if len <= u32::MAX as usize {
-        buf.extend(iter::once(Format::ARRAY32).chain((len as u32).to_be_bytes()));
-        5
-    } else {
-        #[cfg(feature = "strict")]
-        panic!("strict serialization enabled; the buffer is too large");
+    let Some(n) = pack_array_header(buf, values.len()) else {
         return 0;
     };
     n + values.map(|v| v.pack(buf)).sum::<usize>()
 }
 
 /// Packs a map into the extendable buffer, returning the amount of written bytes.
-#[allow(unreachable_code)]
 pub fn pack_map<T, A, I, B, K, V>(buf: &mut T, iter: A) -> usize
 where
     T: Extend<u8>,
@@ -41,19 +62,7 @@ where
     V: Packable,
 {
     let map = iter.into_iter();
-    let len = map.len();
-    let n = if len <= 15 {
-        buf.extend(iter::once(((len & 0x0f) as u8) | 0x80));
-        1
-    } else if len <= u16::MAX as usize {
-        buf.extend(iter::once(Format::MAP16).chain((len as u16).to_be_bytes()));
-        3
-    } else if len <= u32::MAX as usize {
-        buf.extend(iter::once(Format::MAP32).chain((len as u32).to_be_bytes()));
-        5
-    } else {
-        #[cfg(feature = "strict")]
-        panic!("strict serialization enabled; the buffer is too large");
+    let Some(n) = pack_map_header(buf, map.len()) else {
         return 0;
     };
     n + map
@@ -64,6 +73,158 @@ where
         .sum::<usize>()
 }
 
+/// Packs an array in the canonical form, preserving the order of the elements.
+///
+/// See [Packable::pack_canonical].
+pub fn pack_array_canonical<T, A, I, V>(buf: &mut T, iter: A) -> usize
+where
+    T: Extend<u8>,
+    A: IntoIterator<IntoIter = I>,
+    I: Iterator<Item = V> + ExactSizeIterator,
+    V: Packable,
+{
+    let values = iter.into_iter();
+    let Some(n) = pack_array_header(buf, values.len()) else {
+        return 0;
+    };
+    n + values.map(|v| v.pack_canonical(buf)).sum::<usize>()
+}
+
+/// Packs a set as an array in the canonical form, with the elements sorted by their packed
+/// bytes.
+///
+/// See [Packable::pack_canonical]. Without `alloc`, the elements keep the order of the iterator.
+pub fn pack_set_canonical<T, A, I, V>(buf: &mut T, iter: A) -> usize
+where
+    T: Extend<u8>,
+    A: IntoIterator<IntoIter = I>,
+    I: Iterator<Item = V> + ExactSizeIterator,
+    V: Packable,
+{
+    #[cfg(feature = "alloc")]
+    {
+        let mut values: ::alloc::vec::Vec<_> = iter
+            .into_iter()
+            .map(|v| {
+                let mut bytes = ::alloc::vec::Vec::new();
+                v.pack_canonical(&mut bytes);
+                bytes
+            })
+            .collect();
+        values.sort_unstable();
+        let Some(n) = pack_array_header(buf, values.len()) else {
+            return 0;
+        };
+        n + values
+            .into_iter()
+            .map(|v| {
+                let len = v.len();
+                buf.extend(v);
+                len
+            })
+            .sum::<usize>()
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    pack_array_canonical(buf, iter)
+}
+
+/// Packs a map in the canonical form, with the entries sorted by the packed bytes of their keys.
+///
+/// See [Packable::pack_canonical]. Without `alloc`, the entries keep the order of the iterator.
+pub fn pack_map_canonical<T, A, I, B, K, V>(buf: &mut T, iter: A) -> usize
+where
+    T: Extend<u8>,
+    A: IntoIterator<IntoIter = I>,
+    B: Borrow<(K, V)>,
+    I: Iterator<Item = B> + ExactSizeIterator,
+    K: Packable,
+    V: Packable,
+{
+    #[cfg(feature = "alloc")]
+    {
+        let mut entries: ::alloc::vec::Vec<_> = iter
+            .into_iter()
+            .map(|b| {
+                let (k, v) = b.borrow();
+                let (mut key, mut value) = (::alloc::vec::Vec::new(), ::alloc::vec::Vec::new());
+                k.pack_canonical(&mut key);
+                v.pack_canonical(&mut value);
+                (key, value)
+            })
+            .collect();
+        entries.sort_unstable();
+        let Some(n) = pack_map_header(buf, entries.len()) else {
+            return 0;
+        };
+        n + entries
+            .into_iter()
+            .map(|(k, v)| {
+                let len = k.len() + v.len();
+                buf.extend(k.into_iter().chain(v));
+                len
+            })
+            .sum::<usize>()
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    {
+        let map = iter.into_iter();
+        let Some(n) = pack_map_header(buf, map.len()) else {
+            return 0;
+        };
+        n + map
+            .map(|b| {
+                let (k, v) = b.borrow();
+                k.pack_canonical(buf) + v.pack_canonical(buf)
+            })
+            .sum::<usize>()
+    }
+}
+
+/// Returns the amount of bytes [pack_array] would write for the provided iterator.
+pub fn packed_len_array<A, I, V>(iter: A) -> usize
+where
+    A: IntoIterator<IntoIter = I>,
+    I: Iterator<Item = V> + ExactSizeIterator,
+    V: Packable,
+{
+    let values = iter.into_iter();
+    collection_header_len(values.len()) + values.map(|v| v.packed_len()).sum::<usize>()
+}
+
+/// Returns the amount of bytes [pack_map] would write for the provided iterator.
+pub fn packed_len_map<A, I, B, K, V>(iter: A) -> usize
+where
+    A: IntoIterator<IntoIter = I>,
+    B: Borrow<(K, V)>,
+    I: Iterator<Item = B> + ExactSizeIterator,
+    K: Packable,
+    V: Packable,
+{
+    let map = iter.into_iter();
+    collection_header_len(map.len())
+        + map
+            .map(|b| {
+                let (k, v) = b.borrow();
+                k.packed_len() + v.packed_len()
+            })
+            .sum::<usize>()
+}
+
+/// The length of an array or map header, for the given amount of entries.
+fn collection_header_len(len: usize) -> usize {
+    if len <= 15 {
+        1
+    } else if len <= u16::MAX as usize {
+        3
+    } else if len <= u32::MAX as usize {
+        5
+    } else {
+        0
+    }
+}
+
 #[cfg(feature = "alloc")]
 mod alloc {
     use super::*;
@@ -80,6 +241,17 @@ mod alloc {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_array_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<X> Packable for BTreeSet<X>
@@ -92,6 +264,17 @@ mod alloc {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_set_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<X> Packable for BinaryHeap<X>
@@ -104,6 +287,17 @@ mod alloc {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_set_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<X> Packable for LinkedList<X>
@@ -116,6 +310,17 @@ mod alloc {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_array_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<X> Packable for VecDeque<X>
@@ -128,6 +333,17 @@ mod alloc {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_array_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<K, V> Packable for BTreeMap<K, V>
@@ -141,6 +357,17 @@ mod alloc {
         {
             pack_map(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_map_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_map(self)
+        }
     }
 }
 
@@ -159,6 +386,17 @@ mod std {
         {
             pack_array(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_set_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self)
+        }
     }
 
     impl<K, V> Packable for HashMap<K, V>
@@ -172,5 +410,110 @@ mod std {
         {
             pack_map(buf, self)
         }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_map_canonical(buf, self)
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_map(self)
+        }
+    }
+}
+
+#[cfg(feature = "heapless")]
+mod heapless {
+    use super::*;
+    use ::heapless::{IndexMap, Vec};
+
+    /// Adapter that provides the exact length of iterators that don't implement
+    /// [ExactSizeIterator].
+    struct ExactLen<I> {
+        iter: I,
+        len: usize,
+    }
+
+    impl<I> Iterator for ExactLen<I>
+    where
+        I: Iterator,
+    {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let item = self.iter.next()?;
+            self.len -= 1;
+            Some(item)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.len, Some(self.len))
+        }
+    }
+
+    impl<I> ExactSizeIterator for ExactLen<I> where I: Iterator {}
+
+    impl<X, const N: usize> Packable for Vec<X, N>
+    where
+        X: Packable,
+    {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_array(buf, self.iter())
+        }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_array_canonical(buf, self.iter())
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_array(self.iter())
+        }
+    }
+
+    impl<K, V, S, const N: usize> Packable for IndexMap<K, V, S, N>
+    where
+        K: Packable,
+        V: Packable,
+    {
+        fn pack<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_map(
+                buf,
+                ExactLen {
+                    iter: self.iter(),
+                    len: self.len(),
+                },
+            )
+        }
+
+        fn pack_canonical<T>(&self, buf: &mut T) -> usize
+        where
+            T: Extend<u8>,
+        {
+            pack_map_canonical(
+                buf,
+                ExactLen {
+                    iter: self.iter(),
+                    len: self.len(),
+                },
+            )
+        }
+
+        fn packed_len(&self) -> usize {
+            packed_len_map(ExactLen {
+                iter: self.iter(),
+                len: self.len(),
+            })
+        }
     }
 }
@@ -1,5 +1,6 @@
-use super::{Format, Packable};
-use core::{iter, marker::PhantomData};
+use super::Packable;
+use crate::encode::write_bool;
+use core::marker::PhantomData;
 
 impl Packable for () {
     fn pack<T>(&self, _buf: &mut T) -> usize
@@ -8,6 +9,10 @@ impl Packable for () {
     {
         0
     }
+
+    fn packed_len(&self) -> usize {
+        0
+    }
 }
 
 impl<X> Packable for PhantomData<X> {
@@ -17,6 +22,10 @@ impl<X> Packable for PhantomData<X> {
     {
         0
     }
+
+    fn packed_len(&self) -> usize {
+        0
+    }
 }
 
 impl Packable for bool {
@@ -24,11 +33,10 @@ impl Packable for bool {
     where
         T: Extend<u8>,
     {
-        if *self {
-            buf.extend(iter::once(Format::TRUE));
-        } else {
-            buf.extend(iter::once(Format::FALSE));
-        }
+        write_bool(buf, *self)
+    }
+
+    fn packed_len(&self) -> usize {
         1
     }
 }
@@ -46,6 +54,23 @@ where
             None => 0u8.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => 1u8.pack(buf) + t.pack_canonical(buf),
+            None => 0u8.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => 1 + t.packed_len(),
+            None => 1,
+        }
+    }
 }
 
 macro_rules! array {
@@ -60,6 +85,17 @@ macro_rules! array {
             {
                 self.iter().map(|t| t.pack(buf)).sum()
             }
+
+            fn pack_canonical<T>(&self, buf: &mut T) -> usize
+            where
+                T: Extend<u8>,
+            {
+                self.iter().map(|t| t.pack_canonical(buf)).sum()
+            }
+
+            fn packed_len(&self) -> usize {
+                self.iter().map(|t| t.packed_len()).sum()
+            }
         }
     };
 }
@@ -144,6 +180,23 @@ macro_rules! tuple {
 
                 0 $( + $name.pack(buf))+
             }
+
+            #[allow(non_snake_case)]
+            fn pack_canonical<TT>(&self, buf: &mut TT) -> usize
+            where
+                TT: Extend<u8>,
+            {
+                let ($(ref $name,)+) = *self;
+
+                0 $( + $name.pack_canonical(buf))+
+            }
+
+            #[allow(non_snake_case)]
+            fn packed_len(&self) -> usize {
+                let ($(ref $name,)+) = *self;
+
+                0 $( + $name.packed_len())+
+            }
         }
     );
 }
@@ -1,4 +1,5 @@
 use super::{Format, Packable};
+use crate::lossy::Lossy;
 use core::iter;
 
 impl Packable for f32 {
@@ -9,6 +10,20 @@ impl Packable for f32 {
         buf.extend(iter::once(Format::FLOAT32).chain(self.to_be_bytes()));
         5
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self.is_nan() {
+            true => f32::NAN.pack(buf),
+            false => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        5
+    }
 }
 
 impl Packable for f64 {
@@ -19,4 +34,41 @@ impl Packable for f64 {
         buf.extend(iter::once(Format::FLOAT64).chain(self.to_be_bytes()));
         9
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self.is_nan() {
+            true => f64::NAN.pack(buf),
+            false => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        9
+    }
+}
+
+impl<X> Packable for Lossy<X>
+where
+    X: Packable,
+{
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        self.0.pack(buf)
+    }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        self.0.pack_canonical(buf)
+    }
+
+    fn packed_len(&self) -> usize {
+        self.0.packed_len()
+    }
 }
@@ -1,4 +1,5 @@
 use super::{Format, Packable};
+use crate::encode::{write_int, write_uint};
 use core::iter;
 use core::num::{
     NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
@@ -10,11 +11,13 @@ impl Packable for u8 {
     where
         T: Extend<u8>,
     {
+        write_uint(buf, *self as u64)
+    }
+
+    fn packed_len(&self) -> usize {
         if *self <= 127 {
-            buf.extend(iter::once(self & Format::POSITIVE_FIXINT));
             1
         } else {
-            buf.extend(iter::once(Format::UINT8).chain(iter::once(*self)));
             2
         }
     }
@@ -30,6 +33,13 @@ impl Packable for Option<NonZeroU8> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => u8::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for u16 {
@@ -37,14 +47,15 @@ impl Packable for u16 {
     where
         T: Extend<u8>,
     {
+        write_uint(buf, *self as u64)
+    }
+
+    fn packed_len(&self) -> usize {
         if *self <= 127 {
-            buf.extend(iter::once(*self as u8 & Format::POSITIVE_FIXINT));
             1
         } else if *self <= u8::MAX as u16 {
-            buf.extend(iter::once(Format::UINT8).chain(iter::once(*self as u8)));
             2
         } else {
-            buf.extend(iter::once(Format::UINT16).chain(self.to_be_bytes()));
             3
         }
     }
@@ -60,6 +71,13 @@ impl Packable for Option<NonZeroU16> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => u16::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for u32 {
@@ -67,17 +85,17 @@ impl Packable for u32 {
     where
         T: Extend<u8>,
     {
+        write_uint(buf, *self as u64)
+    }
+
+    fn packed_len(&self) -> usize {
         if *self <= 127 {
-            buf.extend(iter::once(*self as u8 & Format::POSITIVE_FIXINT));
             1
         } else if *self <= u8::MAX as u32 {
-            buf.extend(iter::once(Format::UINT8).chain(iter::once(*self as u8)));
             2
         } else if *self <= u16::MAX as u32 {
-            buf.extend(iter::once(Format::UINT16).chain((*self as u16).to_be_bytes()));
             3
         } else {
-            buf.extend(iter::once(Format::UINT32).chain(self.to_be_bytes()));
             5
         }
     }
@@ -93,6 +111,13 @@ impl Packable for Option<NonZeroU32> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => u32::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for u64 {
@@ -100,20 +125,19 @@ impl Packable for u64 {
     where
         T: Extend<u8>,
     {
+        write_uint(buf, *self)
+    }
+
+    fn packed_len(&self) -> usize {
         if *self <= 127 {
-            buf.extend(iter::once(*self as u8 & Format::POSITIVE_FIXINT));
             1
         } else if *self <= u8::MAX as u64 {
-            buf.extend(iter::once(Format::UINT8).chain(iter::once(*self as u8)));
             2
         } else if *self <= u16::MAX as u64 {
-            buf.extend(iter::once(Format::UINT16).chain((*self as u16).to_be_bytes()));
             3
         } else if *self <= u32::MAX as u64 {
-            buf.extend(iter::once(Format::UINT32).chain((*self as u32).to_be_bytes()));
             5
         } else {
-            buf.extend(iter::once(Format::UINT64).chain(self.to_be_bytes()));
             9
         }
     }
@@ -129,6 +153,13 @@ impl Packable for Option<NonZeroU64> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => u64::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for u128 {
@@ -160,6 +191,22 @@ impl Packable for u128 {
             18
         }
     }
+
+    fn packed_len(&self) -> usize {
+        if *self <= 127 {
+            1
+        } else if *self <= u8::MAX as u128 {
+            2
+        } else if *self <= u16::MAX as u128 {
+            3
+        } else if *self <= u32::MAX as u128 {
+            5
+        } else if *self <= u64::MAX as u128 {
+            9
+        } else {
+            18
+        }
+    }
 }
 
 impl Packable for Option<NonZeroU128> {
@@ -172,6 +219,13 @@ impl Packable for Option<NonZeroU128> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => u128::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for usize {
@@ -179,20 +233,19 @@ impl Packable for usize {
     where
         T: Extend<u8>,
     {
+        write_uint(buf, *self as u64)
+    }
+
+    fn packed_len(&self) -> usize {
         if *self <= 127 {
-            buf.extend(iter::once(*self as u8 & Format::POSITIVE_FIXINT));
             1
         } else if *self <= u8::MAX as usize {
-            buf.extend(iter::once(Format::UINT8).chain(iter::once(*self as u8)));
             2
         } else if *self <= u16::MAX as usize {
-            buf.extend(iter::once(Format::UINT16).chain((*self as u16).to_be_bytes()));
             3
         } else if *self <= u32::MAX as usize {
-            buf.extend(iter::once(Format::UINT32).chain((*self as u32).to_be_bytes()));
             5
         } else {
-            buf.extend(iter::once(Format::UINT64).chain(self.to_be_bytes()));
             9
         }
     }
@@ -208,6 +261,13 @@ impl Packable for Option<NonZeroUsize> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => usize::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for i8 {
@@ -226,6 +286,21 @@ impl Packable for i8 {
             1
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        write_int(buf, *self as i64)
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self <= -33 {
+            2
+        } else {
+            1
+        }
+    }
 }
 
 impl Packable for Option<NonZeroI8> {
@@ -238,6 +313,23 @@ impl Packable for Option<NonZeroI8> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => i8::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => i8::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for i16 {
@@ -262,6 +354,25 @@ impl Packable for i16 {
             3
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        write_int(buf, *self as i64)
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self < i8::MIN as i16 {
+            3
+        } else if *self <= -33 {
+            2
+        } else if *self <= i8::MAX as i16 {
+            1
+        } else {
+            3
+        }
+    }
 }
 
 impl Packable for Option<NonZeroI16> {
@@ -274,6 +385,23 @@ impl Packable for Option<NonZeroI16> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => i16::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => i16::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for i32 {
@@ -304,6 +432,29 @@ impl Packable for i32 {
             5
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        write_int(buf, *self as i64)
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self < i16::MIN as i32 {
+            5
+        } else if *self < i8::MIN as i32 {
+            3
+        } else if *self <= -33 {
+            2
+        } else if *self <= i8::MAX as i32 {
+            1
+        } else if *self <= i16::MAX as i32 {
+            3
+        } else {
+            5
+        }
+    }
 }
 
 impl Packable for Option<NonZeroI32> {
@@ -316,6 +467,23 @@ impl Packable for Option<NonZeroI32> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => i32::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => i32::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for i64 {
@@ -352,6 +520,33 @@ impl Packable for i64 {
             9
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        write_int(buf, *self)
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self < i32::MIN as i64 {
+            9
+        } else if *self < i16::MIN as i64 {
+            5
+        } else if *self < i8::MIN as i64 {
+            3
+        } else if *self <= -33 {
+            2
+        } else if *self <= i8::MAX as i64 {
+            1
+        } else if *self <= i16::MAX as i64 {
+            3
+        } else if *self <= i32::MAX as i64 {
+            5
+        } else {
+            9
+        }
+    }
 }
 
 impl Packable for Option<NonZeroI64> {
@@ -364,6 +559,23 @@ impl Packable for Option<NonZeroI64> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => i64::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => i64::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for i128 {
@@ -414,6 +626,40 @@ impl Packable for i128 {
             18
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match u128::try_from(*self) {
+            Ok(v) => v.pack(buf),
+            Err(_) => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self < i64::MIN as i128 {
+            18
+        } else if *self < i32::MIN as i128 {
+            9
+        } else if *self < i16::MIN as i128 {
+            5
+        } else if *self < i8::MIN as i128 {
+            3
+        } else if *self <= -33 {
+            2
+        } else if *self <= i8::MAX as i128 {
+            1
+        } else if *self <= i16::MAX as i128 {
+            3
+        } else if *self <= i32::MAX as i128 {
+            5
+        } else if *self <= i64::MAX as i128 {
+            9
+        } else {
+            18
+        }
+    }
 }
 
 impl Packable for Option<NonZeroI128> {
@@ -426,6 +672,23 @@ impl Packable for Option<NonZeroI128> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => i128::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => i128::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
 
 impl Packable for isize {
@@ -462,6 +725,33 @@ impl Packable for isize {
             9
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        write_int(buf, *self as i64)
+    }
+
+    fn packed_len(&self) -> usize {
+        if *self < i32::MIN as isize {
+            9
+        } else if *self < i16::MIN as isize {
+            5
+        } else if *self < i8::MIN as isize {
+            3
+        } else if *self <= -33 {
+            2
+        } else if *self <= i8::MAX as isize {
+            1
+        } else if *self <= i16::MAX as isize {
+            3
+        } else if *self <= i32::MAX as isize {
+            5
+        } else {
+            9
+        }
+    }
 }
 
 impl Packable for Option<NonZeroIsize> {
@@ -474,4 +764,21 @@ impl Packable for Option<NonZeroIsize> {
             None => 0u32.pack(buf),
         }
     }
+
+    fn pack_canonical<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self {
+            Some(t) => isize::from(*t).pack_canonical(buf),
+            None => self.pack(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        match self {
+            Some(t) => isize::from(*t).packed_len(),
+            None => 1,
+        }
+    }
 }
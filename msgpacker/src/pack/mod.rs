@@ -6,4 +6,11 @@ mod common;
 mod float;
 mod int;
 
-pub use collections::{pack_array, pack_map};
+pub use binary::{pack_bin, packed_len_bin};
+#[cfg(feature = "alloc")]
+pub(crate) use collections::{pack_array_header, pack_map_header};
+
+pub use collections::{
+    pack_array, pack_array_canonical, pack_map, pack_map_canonical, pack_set_canonical,
+    packed_len_array, packed_len_map,
+};
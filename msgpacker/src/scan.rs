@@ -0,0 +1,230 @@
+use super::{Error, Format};
+
+/// Kind of the content of an element, read from its format tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementKind {
+    /// Nil, boolean, integer or float; the length is the amount of bytes after the tag.
+    Scalar,
+    /// Str or bin; the length is the amount of bytes of the payload.
+    Bytes,
+    /// Extension; the length is the amount of bytes of the payload, after the extension type.
+    Ext,
+    /// Array; the length is the amount of elements.
+    Array,
+    /// Map; the length is the amount of pairs of key and value.
+    Map,
+}
+
+impl ElementKind {
+    /// Returns the amount of bytes that follow the header of an element of this kind with the
+    /// given length, and the amount of values nested in it.
+    pub const fn layout(&self, len: usize) -> (usize, usize) {
+        match self {
+            ElementKind::Scalar | ElementKind::Bytes => (len, 0),
+            ElementKind::Ext => (len.saturating_add(1), 0),
+            ElementKind::Array => (0, len),
+            ElementKind::Map => (0, len.saturating_mul(2)),
+        }
+    }
+}
+
+/// Length of an element, either implied by its format tag or read after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementLen {
+    /// The length is fixed by the tag.
+    Fixed(usize),
+    /// The length is a big-endian number of the given amount of bytes, right after the tag.
+    Prefixed(usize),
+}
+
+/// Layout of an element announced by its format tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElementHeader {
+    /// Kind of the content.
+    pub kind: ElementKind,
+    /// Length of the content, with a meaning that depends on the kind.
+    pub len: ElementLen,
+}
+
+impl ElementHeader {
+    const fn new(kind: ElementKind, len: ElementLen) -> Self {
+        Self { kind, len }
+    }
+
+    /// Reads the layout of an element from its format tag.
+    ///
+    /// Returns [Error::UnexpectedFormatTag] for the reserved tag.
+    pub const fn from_tag(tag: u8) -> Result<Self, Error> {
+        use ElementKind::*;
+        use ElementLen::*;
+
+        let header = match tag {
+            0x00..=Format::POSITIVE_FIXINT | Format::NEGATIVE_FIXINT.. => {
+                Self::new(Scalar, Fixed(0))
+            }
+            Format::NIL | Format::TRUE | Format::FALSE => Self::new(Scalar, Fixed(0)),
+            Format::FIXMAP..=0x8f => Self::new(Map, Fixed(tag as usize & 0x0f)),
+            Format::FIXARRAY..=0x9f => Self::new(Array, Fixed(tag as usize & 0x0f)),
+            Format::FIXSTR..=0xbf => Self::new(Bytes, Fixed(tag as usize & 0x1f)),
+            Format::UINT8 | Format::INT8 => Self::new(Scalar, Fixed(1)),
+            Format::UINT16 | Format::INT16 => Self::new(Scalar, Fixed(2)),
+            Format::UINT32 | Format::INT32 | Format::FLOAT32 => Self::new(Scalar, Fixed(4)),
+            Format::UINT64 | Format::INT64 | Format::FLOAT64 => Self::new(Scalar, Fixed(8)),
+            Format::BIN8 | Format::STR8 => Self::new(Bytes, Prefixed(1)),
+            Format::BIN16 | Format::STR16 => Self::new(Bytes, Prefixed(2)),
+            Format::BIN32 | Format::STR32 => Self::new(Bytes, Prefixed(4)),
+            Format::ARRAY16 => Self::new(Array, Prefixed(2)),
+            Format::ARRAY32 => Self::new(Array, Prefixed(4)),
+            Format::MAP16 => Self::new(Map, Prefixed(2)),
+            Format::MAP32 => Self::new(Map, Prefixed(4)),
+            Format::FIXEXT1 => Self::new(Ext, Fixed(1)),
+            Format::FIXEXT2 => Self::new(Ext, Fixed(2)),
+            Format::FIXEXT4 => Self::new(Ext, Fixed(4)),
+            Format::FIXEXT8 => Self::new(Ext, Fixed(8)),
+            Format::FIXEXT16 => Self::new(Ext, Fixed(16)),
+            Format::EXT8 => Self::new(Ext, Prefixed(1)),
+            Format::EXT16 => Self::new(Ext, Prefixed(2)),
+            Format::EXT32 => Self::new(Ext, Prefixed(4)),
+            _ => return Err(Error::UnexpectedFormatTag),
+        };
+        Ok(header)
+    }
+}
+
+/// Position of the scanner inside the current element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum State {
+    /// Expects the format tag of the next element.
+    Tag,
+    /// Reads the length prefix of an element, with `remaining` bytes left.
+    Len {
+        kind: ElementKind,
+        remaining: usize,
+        len: usize,
+    },
+    /// Skips the payload of an element, with `remaining` bytes left.
+    Payload { remaining: usize },
+}
+
+/// Finds the boundaries of complete values in a stream of packed bytes, fed in chunks.
+///
+/// The scanner reads format tags and lengths only, without decoding or checking the contents,
+/// and keeps its position across calls to [ValueScanner::feed], so every byte of the stream is
+/// read once regardless of how it is split.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValueScanner {
+    state: State,
+    pending: usize,
+    len: usize,
+}
+
+impl Default for ValueScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValueScanner {
+    /// Creates a scanner at the start of a value.
+    pub const fn new() -> Self {
+        Self {
+            state: State::Tag,
+            pending: 1,
+            len: 0,
+        }
+    }
+
+    /// Amount of bytes of the current value fed so far.
+    ///
+    /// After an error, this is the offset of the failing format tag in the value.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if no bytes of the current value were fed yet.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Discards the current value, starting over at the next fed byte.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Feeds the next bytes of the stream.
+    ///
+    /// Returns the amount of leading bytes that complete the current value, after which the
+    /// scanner starts over at the following value, or `None` if all the bytes belong to the
+    /// current value and more are needed.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Option<usize>, Error> {
+        let mut i = 0;
+        loop {
+            if self.state == State::Tag && self.pending == 0 {
+                self.reset();
+                return Ok(Some(i));
+            }
+            let Some(&byte) = bytes.get(i) else {
+                self.len += i;
+                return Ok(None);
+            };
+            match self.state {
+                State::Tag => {
+                    let header = match ElementHeader::from_tag(byte) {
+                        Ok(header) => header,
+                        Err(e) => {
+                            self.len += i;
+                            return Err(e);
+                        }
+                    };
+                    i += 1;
+                    match header.len {
+                        ElementLen::Fixed(len) => self.enter(header.kind, len),
+                        ElementLen::Prefixed(remaining) => {
+                            self.state = State::Len {
+                                kind: header.kind,
+                                remaining,
+                                len: 0,
+                            }
+                        }
+                    }
+                }
+                State::Len {
+                    kind,
+                    remaining,
+                    len,
+                } => {
+                    i += 1;
+                    let len = (len << 8) | byte as usize;
+                    match remaining - 1 {
+                        0 => self.enter(kind, len),
+                        remaining => {
+                            self.state = State::Len {
+                                kind,
+                                remaining,
+                                len,
+                            }
+                        }
+                    }
+                }
+                State::Payload { remaining } => {
+                    let n = remaining.min(bytes.len() - i);
+                    i += n;
+                    self.state = match remaining - n {
+                        0 => State::Tag,
+                        remaining => State::Payload { remaining },
+                    };
+                }
+            }
+        }
+    }
+
+    /// Moves past the header of an element.
+    fn enter(&mut self, kind: ElementKind, len: usize) {
+        let (payload, children) = kind.layout(len);
+        self.pending = (self.pending - 1).saturating_add(children);
+        self.state = match payload {
+            0 => State::Tag,
+            remaining => State::Payload { remaining },
+        };
+    }
+}
@@ -0,0 +1,222 @@
+use super::{
+    error::Error,
+    extension::{pack_ext, packed_len_ext, unpack_ext, unpack_ext_iter, ExtensionType},
+    helpers::take_num,
+    Packable, Unpackable,
+};
+
+/// Instant in time as seconds and nanoseconds since the Unix epoch, packed as the timestamp
+/// extension of the protocol.
+///
+/// Instants before 1970 have negative seconds, while the nanoseconds are always added forward in
+/// time and are lower than `1_000_000_000`, which [Timestamp::new] enforces.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp {
+    secs: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// Upper bound, exclusive, of the nanoseconds field.
+    pub const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+    /// Creates a new timestamp, returning [Error::InvalidExtension] if `nanos` isn't lower than
+    /// [Timestamp::NANOS_PER_SEC].
+    pub const fn new(secs: i64, nanos: u32) -> Result<Self, Error> {
+        if nanos >= Self::NANOS_PER_SEC {
+            return Err(Error::InvalidExtension);
+        }
+        Ok(Self { secs, nanos })
+    }
+
+    /// Creates a timestamp of a whole amount of seconds.
+    pub const fn from_secs(secs: i64) -> Self {
+        Self { secs, nanos: 0 }
+    }
+
+    /// Seconds since the Unix epoch.
+    pub const fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Nanoseconds added to the seconds, lower than [Timestamp::NANOS_PER_SEC].
+    pub const fn nanos(&self) -> u32 {
+        self.nanos
+    }
+}
+
+impl ExtensionType for Timestamp {
+    const EXT_TYPE: i8 = -1;
+
+    type Error = Error;
+
+    fn pack_payload<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        match self.payload_len() {
+            4 => buf.extend((self.secs as u32).to_be_bytes()),
+            8 => buf.extend(((self.nanos as u64) << 34 | self.secs as u64).to_be_bytes()),
+            _ => buf.extend(
+                self.nanos
+                    .to_be_bytes()
+                    .into_iter()
+                    .chain(self.secs.to_be_bytes()),
+            ),
+        }
+        self.payload_len()
+    }
+
+    fn payload_len(&self) -> usize {
+        if self.secs >> 32 == 0 && self.nanos == 0 {
+            4
+        } else if self.secs >> 34 == 0 && self.nanos < 1u32 << 30 {
+            8
+        } else {
+            12
+        }
+    }
+
+    fn unpack_payload(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let n = buf.len();
+        let (secs, nanos) = match n {
+            4 => (take_num(&mut buf, u32::from_be_bytes)? as i64, 0),
+            8 => {
+                let data = take_num(&mut buf, u64::from_be_bytes)?;
+                ((data & ((1u64 << 34) - 1)) as i64, (data >> 34) as u32)
+            }
+            12 => {
+                let nanos = take_num(&mut buf, u32::from_be_bytes)?;
+                (take_num(&mut buf, i64::from_be_bytes)?, nanos)
+            }
+            _ => return Err(Error::InvalidExtension),
+        };
+        Ok((n, Self::new(secs, nanos)?))
+    }
+
+    fn unpack_payload_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut payload = [0u8; 13];
+        let mut n = 0;
+        for (b, x) in payload.iter_mut().zip(bytes) {
+            *b = x;
+            n += 1;
+        }
+        Self::unpack_payload(&payload[..n])
+    }
+}
+
+impl Packable for Timestamp {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        pack_ext(buf, self)
+    }
+
+    fn packed_len(&self) -> usize {
+        packed_len_ext(self)
+    }
+}
+
+impl Unpackable for Timestamp {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        unpack_ext(buf)
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        unpack_ext_iter(bytes)
+    }
+}
+
+/// Packs a value convertible into a [Timestamp] as the timestamp extension, returning the amount
+/// of written bytes.
+pub fn pack_timestamp<T, V>(buf: &mut T, value: &V) -> usize
+where
+    T: Extend<u8>,
+    V: Clone + Into<Timestamp>,
+{
+    value.clone().into().pack(buf)
+}
+
+/// Returns the amount of bytes [pack_timestamp] would write for the value.
+pub fn packed_len_timestamp<V>(value: &V) -> usize
+where
+    V: Clone + Into<Timestamp>,
+{
+    value.clone().into().packed_len()
+}
+
+/// Unpacks a timestamp extension into a value convertible from a [Timestamp], returning the
+/// amount of read bytes.
+///
+/// Returns [Error::IntegerOverflow] if the instant can't be represented by the value.
+pub fn unpack_timestamp<V>(buf: &[u8]) -> Result<(usize, V), Error>
+where
+    V: TryFrom<Timestamp>,
+{
+    let (n, t) = Timestamp::unpack(buf)?;
+    let v = V::try_from(t).map_err(|_| Error::IntegerOverflow)?;
+    Ok((n, v))
+}
+
+/// Unpacks a timestamp extension into a value convertible from a [Timestamp], returning the
+/// amount of read bytes.
+///
+/// Returns [Error::IntegerOverflow] if the instant can't be represented by the value.
+pub fn unpack_timestamp_iter<I, V>(bytes: I) -> Result<(usize, V), Error>
+where
+    I: IntoIterator<Item = u8>,
+    V: TryFrom<Timestamp>,
+{
+    let (n, t) = Timestamp::unpack_iter(bytes)?;
+    let v = V::try_from(t).map_err(|_| Error::IntegerOverflow)?;
+    Ok((n, v))
+}
+
+#[cfg(feature = "std")]
+mod system_time {
+    use super::*;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    impl From<SystemTime> for Timestamp {
+        fn from(t: SystemTime) -> Self {
+            match t.duration_since(UNIX_EPOCH) {
+                Ok(d) => Timestamp {
+                    secs: d.as_secs() as i64,
+                    nanos: d.subsec_nanos(),
+                },
+                Err(e) if e.duration().subsec_nanos() == 0 => Timestamp {
+                    secs: -(e.duration().as_secs() as i64),
+                    nanos: 0,
+                },
+                Err(e) => Timestamp {
+                    secs: -(e.duration().as_secs() as i64) - 1,
+                    nanos: Timestamp::NANOS_PER_SEC - e.duration().subsec_nanos(),
+                },
+            }
+        }
+    }
+
+    impl TryFrom<Timestamp> for SystemTime {
+        type Error = Error;
+
+        fn try_from(t: Timestamp) -> Result<Self, Self::Error> {
+            let nanos = Duration::from_nanos(t.nanos as u64);
+            let secs = Duration::from_secs(t.secs.unsigned_abs());
+            match t.secs {
+                0.. => UNIX_EPOCH.checked_add(secs),
+                _ => UNIX_EPOCH.checked_sub(secs),
+            }
+            .and_then(|t| t.checked_add(nanos))
+            .ok_or(Error::IntegerOverflow)
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use super::{
+    extension::unpack_ext_header,
+    helpers::{take_buffer, take_byte, take_num},
+    unpack::{unpack_bytes, unpack_int, unpack_str},
+    Error, Format,
+};
+
+/// A single protocol element read by the [Tokenizer].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token<'a> {
+    /// Nil.
+    Nil,
+    /// Boolean.
+    Bool(bool),
+    /// Positive fixint or one of the `UINT*` formats.
+    Uint(u64),
+    /// Negative fixint or one of the `INT*` formats.
+    Int(i64),
+    /// Single precision float.
+    F32(f32),
+    /// Double precision float.
+    F64(f64),
+    /// String, borrowed from the input.
+    Str(&'a str),
+    /// Byte array, borrowed from the input.
+    Bin(&'a [u8]),
+    /// Header of an array; its elements are the next `len` values.
+    ArrayStart(usize),
+    /// Header of a map; its entries are the next `len` pairs of key and value.
+    MapStart(usize),
+    /// Extension type and payload, borrowed from the input.
+    Ext(i8, &'a [u8]),
+}
+
+/// A token with the offset of its format tag in the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Event<'a> {
+    /// Offset of the token, in bytes, from the start of the input.
+    pub offset: usize,
+    /// The read token.
+    pub token: Token<'a>,
+}
+
+/// A pull-based reader that walks packed bytes token by token, without a target type.
+///
+/// Containers are not nested: an array or map yields its start token, followed by the tokens of
+/// its elements. The iterator ends at the end of the input, or after yielding the first error.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    failed: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a new tokenizer over the provided input.
+    pub const fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// Offset, in bytes, of the next token.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Bytes that weren't read yet.
+    pub const fn remaining(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    /// Reads the next token, returning its length in bytes.
+    fn token(&self) -> Result<(usize, Token<'a>), Error> {
+        let mut buf = self.buf;
+        let format = take_byte(&mut buf)?;
+        let token = match format {
+            0x00..=Format::POSITIVE_FIXINT | Format::UINT8..=Format::UINT64 => {
+                let (n, v) = unpack_int(self.buf)?;
+                return Ok((n, Token::Uint(v as u64)));
+            }
+            Format::NEGATIVE_FIXINT.. | Format::INT8..=Format::INT64 => {
+                let (n, v) = unpack_int(self.buf)?;
+                return Ok((n, Token::Int(v as i64)));
+            }
+            Format::NIL => (1, Token::Nil),
+            Format::TRUE => (1, Token::Bool(true)),
+            Format::FALSE => (1, Token::Bool(false)),
+            Format::FLOAT32 => (5, Token::F32(take_num(&mut buf, f32::from_be_bytes)?)),
+            Format::FLOAT64 => (9, Token::F64(take_num(&mut buf, f64::from_be_bytes)?)),
+            Format::BIN8..=Format::BIN32 => {
+                let (n, bytes) = unpack_bytes(self.buf)?;
+                (n, Token::Bin(bytes))
+            }
+            Format::FIXSTR..=0xbf | Format::STR8..=Format::STR32 => {
+                let (n, str) = unpack_str(self.buf)?;
+                (n, Token::Str(str))
+            }
+            Format::FIXARRAY..=0x9f => (1, Token::ArrayStart(format as usize & 0x0f)),
+            Format::FIXMAP..=0x8f => (1, Token::MapStart(format as usize & 0x0f)),
+            Format::ARRAY16 => (
+                3,
+                Token::ArrayStart(take_num(&mut buf, u16::from_be_bytes)? as usize),
+            ),
+            Format::ARRAY32 => (
+                5,
+                Token::ArrayStart(take_num(&mut buf, u32::from_be_bytes)? as usize),
+            ),
+            Format::MAP16 => (
+                3,
+                Token::MapStart(take_num(&mut buf, u16::from_be_bytes)? as usize),
+            ),
+            Format::MAP32 => (
+                5,
+                Token::MapStart(take_num(&mut buf, u32::from_be_bytes)? as usize),
+            ),
+            Format::FIXEXT1..=Format::FIXEXT16 | Format::EXT8..=Format::EXT32 => {
+                let mut buf = self.buf;
+                let (n, t, len) = unpack_ext_header(&mut buf)?;
+                (n + len, Token::Ext(t, take_buffer(&mut buf, len)?))
+            }
+            _ => return Err(Error::UnexpectedFormatTag),
+        };
+        Ok(token)
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.buf.is_empty() {
+            return None;
+        }
+        match self.token() {
+            Ok((n, token)) => {
+                let offset = self.offset;
+                self.buf = &self.buf[n..];
+                self.offset += n;
+                Some(Ok(Event { offset, token }))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> core::iter::FusedIterator for Tokenizer<'a> {}
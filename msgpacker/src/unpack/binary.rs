@@ -1,7 +1,11 @@
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+use super::helpers::{take_byte_iter, take_num_iter};
 use super::{
     helpers::{take_byte, take_num},
-    Error, Format, Unpackable,
+    Error, Format, UnpackableBorrowed,
 };
+use crate::binary::MsgPackerBinSlice;
+use crate::limits::scope;
 use core::str;
 
 pub fn unpack_bytes(mut buf: &[u8]) -> Result<(usize, &[u8]), Error> {
@@ -12,21 +16,54 @@ pub fn unpack_bytes(mut buf: &[u8]) -> Result<(usize, &[u8]), Error> {
         Format::BIN32 => (5, take_num(&mut buf, u32::from_be_bytes)? as usize),
         _ => return Err(Error::UnexpectedFormatTag),
     };
+    scope::bytes(len)?;
     if buf.len() < len {
         return Err(Error::BufferTooShort);
     }
     Ok((n + len, &buf[..len]))
 }
 
-pub fn unpack_str(mut buf: &[u8]) -> Result<(usize, &str), Error> {
-    let format = take_byte(&mut buf)?;
+/// Reads the header of a str, returning the amount of read bytes and the length of the str.
+fn str_header(buf: &mut &[u8]) -> Result<(usize, usize), Error> {
+    let format = take_byte(buf)?;
     let (n, len) = match format {
         0xa0..=0xbf => (1, format as usize & 0x1f),
-        Format::STR8 => (2, take_byte(&mut buf)? as usize),
-        Format::STR16 => (3, take_num(&mut buf, u16::from_be_bytes)? as usize),
-        Format::STR32 => (5, take_num(&mut buf, u32::from_be_bytes)? as usize),
+        Format::STR8 => (2, take_byte(buf)? as usize),
+        Format::STR16 => (3, take_num(buf, u16::from_be_bytes)? as usize),
+        Format::STR32 => (5, take_num(buf, u32::from_be_bytes)? as usize),
         _ => return Err(Error::UnexpectedFormatTag),
     };
+    scope::bytes(len)?;
+    Ok((n, len))
+}
+
+/// Reads the header of a str from the iterator, returning the amount of read bytes and the
+/// length of the str.
+#[cfg(any(feature = "alloc", feature = "heapless"))]
+fn str_header_iter<I>(bytes: &mut I) -> Result<(usize, usize), Error>
+where
+    I: Iterator<Item = u8>,
+{
+    let format = take_byte_iter(bytes.by_ref())?;
+    let (n, len) = match format {
+        0xa0..=0xbf => (1, format as usize & 0x1f),
+        Format::STR8 => (2, take_byte_iter(bytes.by_ref())? as usize),
+        Format::STR16 => (
+            3,
+            take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+        ),
+        Format::STR32 => (
+            5,
+            take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+        ),
+        _ => return Err(Error::UnexpectedFormatTag),
+    };
+    scope::bytes(len)?;
+    Ok((n, len))
+}
+
+pub fn unpack_str(mut buf: &[u8]) -> Result<(usize, &str), Error> {
+    let (n, len) = str_header(&mut buf)?;
     if buf.len() < len {
         return Err(Error::BufferTooShort);
     }
@@ -34,18 +71,154 @@ pub fn unpack_str(mut buf: &[u8]) -> Result<(usize, &str), Error> {
     Ok((n + len, str))
 }
 
+impl<'a> UnpackableBorrowed<'a> for &'a str {
+    type Error = Error;
+
+    fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+        unpack_str(buf)
+    }
+}
+
+impl<'a> UnpackableBorrowed<'a> for &'a [u8] {
+    type Error = Error;
+
+    fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+        unpack_bytes(buf)
+    }
+}
+
+impl<'a> UnpackableBorrowed<'a> for MsgPackerBinSlice<'a> {
+    type Error = Error;
+
+    fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+        unpack_bytes(buf).map(|(n, b)| (n, MsgPackerBinSlice(b)))
+    }
+}
+
 #[cfg(feature = "alloc")]
-mod alloc {
+pub mod alloc {
     use super::*;
     use crate::binary::alloc::MsgPackerBin;
-    use crate::helpers::{take_byte_iter, take_num_iter};
-    use ::alloc::{string::String, vec::Vec};
+    use crate::helpers::take_buffer_iter;
+    use crate::unpack::{unpack_array, unpack_array_iter};
+    use crate::Unpackable;
+    use ::alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+    use core::iter;
+
+    /// Unpacks a bin, or an array of `u8`, from the buffer, returning a byte container and the
+    /// amount of read bytes.
+    ///
+    /// Returns [Error::UnexpectedBinLength] if the container rejects the amount of bytes, as
+    /// fixed-size arrays do.
+    pub fn unpack_bin<C>(buf: &[u8]) -> Result<(usize, C), Error>
+    where
+        C: TryFrom<Vec<u8>>,
+    {
+        let (n, bytes) = match buf.first() {
+            Some(&(Format::BIN8 | Format::BIN16 | Format::BIN32)) => {
+                unpack_bytes(buf).map(|(n, b)| (n, b.to_vec()))?
+            }
+            _ => unpack_array::<u8, Vec<u8>>(buf)?,
+        };
+        C::try_from(bytes)
+            .map(|c| (n, c))
+            .map_err(|_| Error::UnexpectedBinLength)
+    }
+
+    /// Unpacks a bin, or an array of `u8`, from the iterator, returning a byte container and the
+    /// amount of read bytes.
+    ///
+    /// Returns [Error::UnexpectedBinLength] if the container rejects the amount of bytes, as
+    /// fixed-size arrays do.
+    pub fn unpack_bin_iter<I, C>(bytes: I) -> Result<(usize, C), Error>
+    where
+        I: IntoIterator<Item = u8>,
+        C: TryFrom<Vec<u8>>,
+    {
+        let mut bytes = bytes.into_iter();
+        let format = take_byte_iter(bytes.by_ref())?;
+        let (n, len) = match format {
+            Format::BIN8 => (2, take_byte_iter(bytes.by_ref())? as usize),
+            Format::BIN16 => (
+                3,
+                take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+            ),
+            Format::BIN32 => (
+                5,
+                take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+            ),
+            _ => {
+                let (n, v) = unpack_array_iter::<_, u8, Vec<u8>>(iter::once(format).chain(bytes))?;
+                return C::try_from(v)
+                    .map(|c| (n, c))
+                    .map_err(|_| Error::UnexpectedBinLength);
+            }
+        };
+        scope::bytes(len)?;
+        let v = take_buffer_iter(bytes, len)?;
+        C::try_from(v)
+            .map(|c| (n + len, c))
+            .map_err(|_| Error::UnexpectedBinLength)
+    }
 
     impl Unpackable for MsgPackerBin {
         type Error = Error;
 
         fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-            unpack_bytes(buf).map(|(n, b)| (n, MsgPackerBin(b.to_vec())))
+            unpack_bin(buf).map(|(n, b)| (n, MsgPackerBin(b)))
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            unpack_bin_iter(bytes).map(|(n, b)| (n, MsgPackerBin(b)))
+        }
+    }
+
+    impl Unpackable for Box<[u8]> {
+        type Error = Error;
+
+        fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_bin(buf)
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            unpack_bin_iter(bytes)
+        }
+    }
+
+    impl<'a> UnpackableBorrowed<'a> for Cow<'a, str> {
+        type Error = Error;
+
+        fn unpack_borrowed(buf: &'a [u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_str(buf).map(|(n, s)| (n, Cow::Borrowed(s)))
+        }
+    }
+
+    impl<'a> Unpackable for Cow<'a, [u8]> {
+        type Error = Error;
+
+        fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_bin(buf)
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            unpack_bin_iter(bytes)
+        }
+    }
+
+    impl Unpackable for String {
+        type Error = Error;
+
+        fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_str(buf).map(|(n, s)| (n, s.into()))
         }
 
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
@@ -53,32 +226,33 @@ mod alloc {
             I: IntoIterator<Item = u8>,
         {
             let mut bytes = bytes.into_iter();
-            let format = take_byte_iter(bytes.by_ref())?;
-            let (n, len) = match format {
-                Format::BIN8 => (2, take_byte_iter(bytes.by_ref())? as usize),
-                Format::BIN16 => (
-                    3,
-                    take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
-                ),
-                Format::BIN32 => (
-                    5,
-                    take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
-                ),
-                _ => return Err(Error::UnexpectedFormatTag),
-            };
+            let (n, len) = str_header_iter(&mut bytes)?;
             let v: Vec<_> = bytes.take(len).collect();
             if v.len() < len {
                 return Err(Error::BufferTooShort);
             }
-            Ok((n + len, MsgPackerBin(v)))
+            let s = String::from_utf8(v).map_err(|_| Error::InvalidUtf8)?;
+            Ok((n + len, s))
         }
     }
+}
 
-    impl Unpackable for String {
+#[cfg(feature = "heapless")]
+mod heapless {
+    use super::*;
+    use crate::Unpackable;
+    use ::heapless::{String, Vec};
+
+    impl<const N: usize> Unpackable for String<N> {
         type Error = Error;
 
         fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-            unpack_str(buf).map(|(n, s)| (n, s.into()))
+            let (n, s) = unpack_str(buf)?;
+            let mut string = String::new();
+            string
+                .push_str(s)
+                .map_err(|_| Error::CapacityExceeded)
+                .map(|_| (n, string))
         }
 
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
@@ -86,21 +260,11 @@ mod alloc {
             I: IntoIterator<Item = u8>,
         {
             let mut bytes = bytes.into_iter();
-            let format = take_byte_iter(bytes.by_ref())?;
-            let (n, len) = match format {
-                0xa0..=0xbf => (1, format as usize & 0x1f),
-                Format::STR8 => (2, take_byte_iter(bytes.by_ref())? as usize),
-                Format::STR16 => (
-                    3,
-                    take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
-                ),
-                Format::STR32 => (
-                    5,
-                    take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
-                ),
-                _ => return Err(Error::UnexpectedFormatTag),
-            };
-            let v: Vec<_> = bytes.take(len).collect();
+            let (n, len) = str_header_iter(&mut bytes)?;
+            if len > N {
+                return Err(Error::CapacityExceeded);
+            }
+            let v: Vec<u8, N> = bytes.take(len).collect();
             if v.len() < len {
                 return Err(Error::BufferTooShort);
             }
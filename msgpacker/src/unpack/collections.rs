@@ -1,30 +1,112 @@
 use super::{
     helpers::{take_byte, take_byte_iter, take_num, take_num_iter},
-    Error, Format, Unpackable,
+    located, Context, Error, Format, Unpackable,
 };
+use crate::limits::scope;
+use core::mem;
+
+/// Reads the header of an array, returning the amount of read bytes and of elements.
+fn array_header(buf: &mut &[u8]) -> Result<(usize, usize), Error> {
+    let format = take_byte(buf)?;
+    match format {
+        0x90..=0x9f => Ok((1, (format & 0x0f) as usize)),
+        Format::ARRAY16 => Ok((3, take_num(buf, u16::from_be_bytes)? as usize)),
+        Format::ARRAY32 => Ok((5, take_num(buf, u32::from_be_bytes)? as usize)),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+/// Reads the header of an array from the iterator, returning the amount of read bytes and of
+/// elements.
+fn array_header_iter<I>(bytes: &mut I) -> Result<(usize, usize), Error>
+where
+    I: Iterator<Item = u8>,
+{
+    let format = take_byte_iter(bytes.by_ref())?;
+    match format {
+        0x90..=0x9f => Ok((1, (format & 0x0f) as usize)),
+        Format::ARRAY16 => Ok((
+            3,
+            take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+        )),
+        Format::ARRAY32 => Ok((
+            5,
+            take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+        )),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+/// Reads the header of a map, returning the amount of read bytes and of entries.
+fn map_header(buf: &mut &[u8]) -> Result<(usize, usize), Error> {
+    let format = take_byte(buf)?;
+    match format {
+        0x80..=0x8f => Ok((1, (format & 0x0f) as usize)),
+        Format::MAP16 => Ok((3, take_num(buf, u16::from_be_bytes)? as usize)),
+        Format::MAP32 => Ok((5, take_num(buf, u32::from_be_bytes)? as usize)),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+/// Reads the header of a map from the iterator, returning the amount of read bytes and of
+/// entries.
+fn map_header_iter<I>(bytes: &mut I) -> Result<(usize, usize), Error>
+where
+    I: Iterator<Item = u8>,
+{
+    let format = take_byte_iter(bytes.by_ref())?;
+    match format {
+        0x80..=0x8f => Ok((1, (format & 0x0f) as usize)),
+        Format::MAP16 => Ok((
+            3,
+            take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+        )),
+        Format::MAP32 => Ok((
+            5,
+            take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+        )),
+        _ => Err(Error::UnexpectedFormatTag),
+    }
+}
+
+/// Rejects a header that announces more than `max_len` elements.
+fn check_capacity(len: usize, max_len: usize) -> Result<(), Error> {
+    match len > max_len {
+        true => Err(Error::CapacityExceeded),
+        false => Ok(()),
+    }
+}
 
 /// Unpacks an array from the buffer, returning a collectable type and the amount of read bytes.
-pub fn unpack_array<V, C>(mut buf: &[u8]) -> Result<(usize, C), <V as Unpackable>::Error>
+pub fn unpack_array<V, C>(buf: &[u8]) -> Result<(usize, C), <V as Unpackable>::Error>
 where
     V: Unpackable,
     C: FromIterator<V>,
 {
-    let format = take_byte(&mut buf)?;
-    let (mut n, len) = match format {
-        0x90..=0x9f => (1, (format & 0x0f) as usize),
-        Format::ARRAY16 => (
-            3,
-            take_num(&mut buf, u16::from_be_bytes).map(|v| v as usize)?,
-        ),
-        Format::ARRAY32 => (
-            5,
-            take_num(&mut buf, u32::from_be_bytes).map(|v| v as usize)?,
-        ),
-        _ => return Err(Error::UnexpectedFormatTag.into()),
-    };
+    unpack_array_bounded(buf, usize::MAX)
+}
+
+/// Unpacks an array of at most `max_len` elements, rejecting a longer one with
+/// [Error::CapacityExceeded] before decoding any element.
+fn unpack_array_bounded<V, C>(
+    mut buf: &[u8],
+    max_len: usize,
+) -> Result<(usize, C), <V as Unpackable>::Error>
+where
+    V: Unpackable,
+    C: FromIterator<V>,
+{
+    let (mut n, len) = array_header(&mut buf)?;
+    check_capacity(len, max_len)?;
+    let _nested = scope::container(len)?;
+    // every non zero-sized element takes at least one byte; reject before preallocating
+    if mem::size_of::<V>() != 0 && len > buf.len() {
+        return Err(Error::BufferTooShort.into());
+    }
     let array: C = (0..len)
-        .map(|_| {
-            let (count, v) = V::unpack(buf)?;
+        .map(|i| {
+            let (count, v) = V::unpack(buf)
+                .map_err(|e| V::error_context(located::<V>(e, buf, n), Context::Index(i)))?;
             buf = &buf[count..];
             n += count;
             Ok(v)
@@ -35,28 +117,34 @@ where
 
 /// Unpacks an array from the iterator, returning a collectable type and the amount of read bytes.
 pub fn unpack_array_iter<I, V, C>(iter: I) -> Result<(usize, C), <V as Unpackable>::Error>
+where
+    I: IntoIterator<Item = u8>,
+    V: Unpackable,
+    C: FromIterator<V>,
+{
+    unpack_array_iter_bounded(iter, usize::MAX)
+}
+
+/// Unpacks an array of at most `max_len` elements from the iterator, rejecting a longer one with
+/// [Error::CapacityExceeded] before decoding any element.
+fn unpack_array_iter_bounded<I, V, C>(
+    iter: I,
+    max_len: usize,
+) -> Result<(usize, C), <V as Unpackable>::Error>
 where
     I: IntoIterator<Item = u8>,
     V: Unpackable,
     C: FromIterator<V>,
 {
     let mut bytes = iter.into_iter();
-    let format = take_byte_iter(bytes.by_ref())?;
-    let (mut n, len) = match format {
-        0x90..=0x9f => (1, (format & 0x0f) as usize),
-        Format::ARRAY16 => (
-            3,
-            take_num_iter(bytes.by_ref(), u16::from_be_bytes).map(|v| v as usize)?,
-        ),
-        Format::ARRAY32 => (
-            5,
-            take_num_iter(bytes.by_ref(), u32::from_be_bytes).map(|v| v as usize)?,
-        ),
-        _ => return Err(Error::UnexpectedFormatTag.into()),
-    };
+    let (mut n, len) = array_header_iter(&mut bytes)?;
+    check_capacity(len, max_len)?;
+    let _nested = scope::container(len)?;
     let array: C = (0..len)
-        .map(|_| {
-            let (count, v) = V::unpack_iter(bytes.by_ref())?;
+        .map(|i| {
+            let (count, v) = V::unpack_iter(bytes.by_ref()).map_err(|e| {
+                V::error_context(V::error_context(e, Context::Offset(n)), Context::Index(i))
+            })?;
             n += count;
             Ok(v)
         })
@@ -65,32 +153,42 @@ where
 }
 
 /// Unpacks a map from the buffer, returning a collectable type and the amount of read bytes.
-pub fn unpack_map<K, V, C>(mut buf: &[u8]) -> Result<(usize, C), <V as Unpackable>::Error>
+pub fn unpack_map<K, V, C>(buf: &[u8]) -> Result<(usize, C), <V as Unpackable>::Error>
 where
     K: Unpackable,
     V: Unpackable,
     <V as Unpackable>::Error: From<<K as Unpackable>::Error>,
     C: FromIterator<(K, V)>,
 {
-    let format = take_byte(&mut buf)?;
-    let (mut n, len) = match format {
-        0x80..=0x8f => (1, (format & 0x0f) as usize),
-        Format::MAP16 => (
-            3,
-            take_num(&mut buf, u16::from_be_bytes).map(|v| v as usize)?,
-        ),
-        Format::MAP32 => (
-            5,
-            take_num(&mut buf, u32::from_be_bytes).map(|v| v as usize)?,
-        ),
-        _ => return Err(Error::UnexpectedFormatTag.into()),
-    };
+    unpack_map_bounded(buf, usize::MAX)
+}
+
+/// Unpacks a map of at most `max_len` entries, rejecting a longer one with
+/// [Error::CapacityExceeded] before decoding any entry.
+fn unpack_map_bounded<K, V, C>(
+    mut buf: &[u8],
+    max_len: usize,
+) -> Result<(usize, C), <V as Unpackable>::Error>
+where
+    K: Unpackable,
+    V: Unpackable,
+    <V as Unpackable>::Error: From<<K as Unpackable>::Error>,
+    C: FromIterator<(K, V)>,
+{
+    let (mut n, len) = map_header(&mut buf)?;
+    check_capacity(len, max_len)?;
+    let _nested = scope::container(len)?;
+    if mem::size_of::<(K, V)>() != 0 && len > buf.len() {
+        return Err(Error::BufferTooShort.into());
+    }
     let map: C = (0..len)
-        .map(|_| {
-            let (count, k) = K::unpack(buf)?;
+        .map(|i| {
+            let (count, k) = K::unpack(buf)
+                .map_err(|e| V::error_context(located::<V>(e.into(), buf, n), Context::Key(i)))?;
             buf = &buf[count..];
             n += count;
-            let (count, v) = V::unpack(buf)?;
+            let (count, v) = V::unpack(buf)
+                .map_err(|e| V::error_context(located::<V>(e, buf, n), Context::Value(i)))?;
             buf = &buf[count..];
             n += count;
             Ok((k, v))
@@ -101,6 +199,22 @@ where
 
 /// Unpacks a map from the iterator, returning a collectable type and the amount of read bytes.
 pub fn unpack_map_iter<I, K, V, C>(iter: I) -> Result<(usize, C), <V as Unpackable>::Error>
+where
+    I: IntoIterator<Item = u8>,
+    K: Unpackable,
+    V: Unpackable,
+    <V as Unpackable>::Error: From<<K as Unpackable>::Error>,
+    C: FromIterator<(K, V)>,
+{
+    unpack_map_iter_bounded(iter, usize::MAX)
+}
+
+/// Unpacks a map of at most `max_len` entries from the iterator, rejecting a longer one with
+/// [Error::CapacityExceeded] before decoding any entry.
+fn unpack_map_iter_bounded<I, K, V, C>(
+    iter: I,
+    max_len: usize,
+) -> Result<(usize, C), <V as Unpackable>::Error>
 where
     I: IntoIterator<Item = u8>,
     K: Unpackable,
@@ -109,24 +223,21 @@ where
     C: FromIterator<(K, V)>,
 {
     let mut bytes = iter.into_iter();
-    let format = take_byte_iter(bytes.by_ref())?;
-    let (mut n, len) = match format {
-        0x80..=0x8f => (1, (format & 0x0f) as usize),
-        Format::MAP16 => (
-            3,
-            take_num_iter(bytes.by_ref(), u16::from_be_bytes).map(|v| v as usize)?,
-        ),
-        Format::MAP32 => (
-            5,
-            take_num_iter(bytes.by_ref(), u32::from_be_bytes).map(|v| v as usize)?,
-        ),
-        _ => return Err(Error::UnexpectedFormatTag.into()),
-    };
+    let (mut n, len) = map_header_iter(&mut bytes)?;
+    check_capacity(len, max_len)?;
+    let _nested = scope::container(len)?;
     let map: C = (0..len)
-        .map(|_| {
-            let (count, k) = K::unpack_iter(bytes.by_ref())?;
+        .map(|i| {
+            let (count, k) = K::unpack_iter(bytes.by_ref()).map_err(|e| {
+                V::error_context(
+                    V::error_context(e.into(), Context::Offset(n)),
+                    Context::Key(i),
+                )
+            })?;
             n += count;
-            let (count, v) = V::unpack_iter(bytes.by_ref())?;
+            let (count, v) = V::unpack_iter(bytes.by_ref()).map_err(|e| {
+                V::error_context(V::error_context(e, Context::Offset(n)), Context::Value(i))
+            })?;
             n += count;
             Ok((k, v))
         })
@@ -150,6 +261,10 @@ mod alloc {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -168,6 +283,10 @@ mod alloc {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -186,6 +305,10 @@ mod alloc {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -204,6 +327,10 @@ mod alloc {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -222,6 +349,10 @@ mod alloc {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -242,6 +373,10 @@ mod alloc {
             unpack_map(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            V::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -269,6 +404,10 @@ mod std {
             unpack_array(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -289,6 +428,10 @@ mod std {
             unpack_map(buf)
         }
 
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            V::error_context(error, context)
+        }
+
         fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
         where
             I: IntoIterator<Item = u8>,
@@ -297,3 +440,57 @@ mod std {
         }
     }
 }
+
+#[cfg(feature = "heapless")]
+mod heapless {
+    use super::*;
+    use ::heapless::{IndexMap, Vec};
+    use core::hash::{BuildHasher, Hash};
+
+    impl<X, const N: usize> Unpackable for Vec<X, N>
+    where
+        X: Unpackable,
+    {
+        type Error = <X as Unpackable>::Error;
+
+        fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_array_bounded(buf, N)
+        }
+
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            X::error_context(error, context)
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            unpack_array_iter_bounded(bytes, N)
+        }
+    }
+
+    impl<K, V, S, const N: usize> Unpackable for IndexMap<K, V, S, N>
+    where
+        K: Unpackable + Eq + Hash,
+        V: Unpackable,
+        <V as Unpackable>::Error: From<<K as Unpackable>::Error>,
+        S: BuildHasher + Default,
+    {
+        type Error = <V as Unpackable>::Error;
+
+        fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+            unpack_map_bounded(buf, N)
+        }
+
+        fn error_context(error: Self::Error, context: Context) -> Self::Error {
+            V::error_context(error, context)
+        }
+
+        fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+        where
+            I: IntoIterator<Item = u8>,
+        {
+            unpack_map_iter_bounded(bytes, N)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     helpers::{take_byte, take_byte_iter},
-    Error, Format, Unpackable,
+    located, Context, Error, Format, Unpackable,
 };
 use core::{marker::PhantomData, mem::MaybeUninit};
 
@@ -67,7 +67,9 @@ where
     fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
         match u8::unpack(buf)? {
             (1, 0) => Ok((1, None)),
-            (1, 1) => X::unpack(&buf[1..]).map(|(n, x)| (n + 1, Some(x))),
+            (1, 1) => X::unpack(&buf[1..])
+                .map(|(n, x)| (n + 1, Some(x)))
+                .map_err(|e| located::<X>(e, &buf[1..], 1)),
             _ => Err(Error::InvalidEnumVariant.into()),
         }
     }
@@ -79,11 +81,17 @@ where
         let mut bytes = bytes.into_iter();
         match bytes.next() {
             Some(0) => Ok((1, None)),
-            Some(1) => X::unpack_iter(bytes).map(|(n, x)| (n + 1, Some(x))),
+            Some(1) => X::unpack_iter(bytes)
+                .map(|(n, x)| (n + 1, Some(x)))
+                .map_err(|e| X::error_context(e, Context::Offset(1))),
             Some(_) => Err(Error::InvalidEnumVariant.into()),
             None => Err(Error::BufferTooShort.into()),
         }
     }
+
+    fn error_context(error: Self::Error, context: Context) -> Self::Error {
+        X::error_context(error, context)
+    }
 }
 
 macro_rules! array {
@@ -95,16 +103,18 @@ macro_rules! array {
             type Error = <X as Unpackable>::Error;
 
             fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-                let mut array = MaybeUninit::uninit_array();
-                let n =
-                    array
-                        .iter_mut()
-                        .try_fold::<_, _, Result<_, Self::Error>>(0, |count, a| {
-                            let (n, x) = X::unpack(buf)?;
-                            buf = &buf[n..];
-                            a.write(x);
-                            Ok(count + n)
+                let mut array = [const { MaybeUninit::uninit() }; $n];
+                let n = array
+                    .iter_mut()
+                    .enumerate()
+                    .try_fold::<_, _, Result<_, Self::Error>>(0, |count, (i, a)| {
+                        let (n, x) = X::unpack(buf).map_err(|e| {
+                            X::error_context(located::<X>(e, buf, count), Context::Index(i))
                         })?;
+                        buf = &buf[n..];
+                        a.write(x);
+                        Ok(count + n)
+                    })?;
                 // Safety: array is initialized
                 let array = unsafe { MaybeUninit::array_assume_init(array) };
                 Ok((n, array))
@@ -115,19 +125,28 @@ macro_rules! array {
                 I: IntoIterator<Item = u8>,
             {
                 let mut bytes = bytes.into_iter();
-                let mut array = MaybeUninit::uninit_array();
-                let n =
-                    array
-                        .iter_mut()
-                        .try_fold::<_, _, Result<_, Self::Error>>(0, |count, a| {
-                            let (n, x) = X::unpack_iter(bytes.by_ref())?;
-                            a.write(x);
-                            Ok(count + n)
+                let mut array = [const { MaybeUninit::uninit() }; $n];
+                let n = array
+                    .iter_mut()
+                    .enumerate()
+                    .try_fold::<_, _, Result<_, Self::Error>>(0, |count, (i, a)| {
+                        let (n, x) = X::unpack_iter(bytes.by_ref()).map_err(|e| {
+                            X::error_context(
+                                X::error_context(e, Context::Offset(count)),
+                                Context::Index(i),
+                            )
                         })?;
+                        a.write(x);
+                        Ok(count + n)
+                    })?;
                 // Safety: array is initialized
                 let array = unsafe { MaybeUninit::array_assume_init(array) };
                 Ok((n, array))
             }
+
+            fn error_context(error: Self::Error, context: Context) -> Self::Error {
+                X::error_context(error, context)
+            }
         }
     };
 }
@@ -211,7 +230,12 @@ macro_rules! tuple {
             fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
                 let mut n = 0;
 
-                $(let (c, $name) = $name::unpack(buf)?; n += c; buf = &buf[c..];)+
+                $(
+                    let (c, $name) = $name::unpack(buf)
+                        .map_err(|e| located::<$err>(e.into(), buf, n))?;
+                    n += c;
+                    buf = &buf[c..];
+                )+
                 let _ = buf;
 
                 Ok((n, ($($name, )+)))
@@ -225,10 +249,18 @@ macro_rules! tuple {
                 let mut n = 0;
                 let mut bytes = bytes.into_iter();
 
-                $(let (c, $name) = $name::unpack_iter(bytes.by_ref())?; n += c;)+
+                $(
+                    let (c, $name) = $name::unpack_iter(bytes.by_ref())
+                        .map_err(|e| $err::error_context(e.into(), Context::Offset(n)))?;
+                    n += c;
+                )+
 
                 Ok((n, ($($name, )+)))
             }
+
+            fn error_context(error: Self::Error, context: Context) -> Self::Error {
+                $err::error_context(error, context)
+            }
         }
     );
 }
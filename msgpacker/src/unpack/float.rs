@@ -1,54 +1,140 @@
 use super::{
     helpers::{take_byte, take_byte_iter, take_num, take_num_iter},
+    int::{unpack_int, unpack_int_iter},
     Error, Format, Unpackable,
 };
+use crate::lossy::Lossy;
+
+/// A decoded number, before the conversion to the target type.
+enum Number {
+    Int(i128),
+    F32(f32),
+    F64(f64),
+}
+
+fn unpack_number(buf: &[u8]) -> Result<(usize, Number), Error> {
+    let mut rest = buf;
+    let format = take_byte(&mut rest)?;
+    match format {
+        Format::FLOAT32 => take_num(&mut rest, f32::from_be_bytes).map(|v| (5, Number::F32(v))),
+        Format::FLOAT64 => take_num(&mut rest, f64::from_be_bytes).map(|v| (9, Number::F64(v))),
+        _ => unpack_int(buf).map(|(n, v)| (n, Number::Int(v))),
+    }
+}
+
+fn unpack_number_iter<I>(bytes: I) -> Result<(usize, Number), Error>
+where
+    I: IntoIterator<Item = u8>,
+{
+    let mut bytes = bytes.into_iter();
+    let format = take_byte_iter(bytes.by_ref())?;
+    match format {
+        Format::FLOAT32 => take_num_iter(bytes, f32::from_be_bytes).map(|v| (5, Number::F32(v))),
+        Format::FLOAT64 => take_num_iter(bytes, f64::from_be_bytes).map(|v| (9, Number::F64(v))),
+        _ => unpack_int_iter(format, bytes).map(|(n, v)| (n, Number::Int(v))),
+    }
+}
+
+impl Number {
+    fn to_f32(&self) -> Result<f32, Error> {
+        match *self {
+            Number::Int(v) if v as f32 as i128 == v => Ok(v as f32),
+            Number::F32(v) => Ok(v),
+            Number::F64(v) if v as f32 as f64 == v || v.is_nan() => Ok(v as f32),
+            _ => Err(Error::PrecisionLoss),
+        }
+    }
+
+    fn to_f64(&self) -> Result<f64, Error> {
+        match *self {
+            Number::Int(v) if v as f64 as i128 == v => Ok(v as f64),
+            Number::F32(v) => Ok(v as f64),
+            Number::F64(v) => Ok(v),
+            _ => Err(Error::PrecisionLoss),
+        }
+    }
+
+    fn to_f32_lossy(&self) -> f32 {
+        match *self {
+            Number::Int(v) => v as f32,
+            Number::F32(v) => v,
+            Number::F64(v) => v as f32,
+        }
+    }
+
+    fn to_f64_lossy(&self) -> f64 {
+        match *self {
+            Number::Int(v) => v as f64,
+            Number::F32(v) => v as f64,
+            Number::F64(v) => v,
+        }
+    }
+}
 
 impl Unpackable for f32 {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        if format != Format::FLOAT32 {
-            return Err(Error::UnexpectedFormatTag);
-        }
-        Ok((5, take_num(&mut buf, f32::from_be_bytes)?))
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let (n, v) = unpack_number(buf)?;
+        Ok((n, v.to_f32()?))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        if format != Format::FLOAT32 {
-            return Err(Error::UnexpectedFormatTag);
-        }
-        Ok((5, take_num_iter(bytes.by_ref(), f32::from_be_bytes)?))
+        let (n, v) = unpack_number_iter(bytes)?;
+        Ok((n, v.to_f32()?))
     }
 }
 
 impl Unpackable for f64 {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            Format::FLOAT32 => Ok((5, take_num(&mut buf, f32::from_be_bytes)? as f64)),
-            Format::FLOAT64 => Ok((9, take_num(&mut buf, f64::from_be_bytes)?)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let (n, v) = unpack_number(buf)?;
+        Ok((n, v.to_f64()?))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            Format::FLOAT32 => Ok((5, take_num_iter(bytes.by_ref(), f32::from_be_bytes)? as f64)),
-            Format::FLOAT64 => Ok((9, take_num_iter(bytes.by_ref(), f64::from_be_bytes)?)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+        let (n, v) = unpack_number_iter(bytes)?;
+        Ok((n, v.to_f64()?))
+    }
+}
+
+impl Unpackable for Lossy<f32> {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let (n, v) = unpack_number(buf)?;
+        Ok((n, Lossy(v.to_f32_lossy())))
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let (n, v) = unpack_number_iter(bytes)?;
+        Ok((n, Lossy(v.to_f32_lossy())))
+    }
+}
+
+impl Unpackable for Lossy<f64> {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let (n, v) = unpack_number(buf)?;
+        Ok((n, Lossy(v.to_f64_lossy())))
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let (n, v) = unpack_number_iter(bytes)?;
+        Ok((n, Lossy(v.to_f64_lossy())))
     }
 }
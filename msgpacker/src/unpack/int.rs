@@ -8,57 +8,101 @@ use super::{
     Error, Format, Unpackable,
 };
 
-impl Unpackable for u8 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+/// Unpacks any integer format, returning the amount of read bytes and the value.
+pub fn unpack_int(mut buf: &[u8]) -> Result<(usize, i128), Error> {
+    let format = take_byte(&mut buf)?;
+    match format {
+        0x00..=Format::POSITIVE_FIXINT => Ok((1, format as i128)),
+        0xe0.. => Ok((1, (format as i8) as i128)),
+        Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as i128)),
+        Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v as i128)),
+        Format::UINT32 => take_num(&mut buf, u32::from_be_bytes).map(|v| (5, v as i128)),
+        Format::UINT64 => take_num(&mut buf, u64::from_be_bytes).map(|v| (9, v as i128)),
+        Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as i128)),
+        Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v as i128)),
+        Format::INT32 => take_num(&mut buf, i32::from_be_bytes).map(|v| (5, v as i128)),
+        Format::INT64 => take_num(&mut buf, i64::from_be_bytes).map(|v| (9, v as i128)),
+        _ => Err(Error::UnexpectedFormatTag),
     }
+}
 
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+/// Unpacks any integer format from the iterator, returning the amount of read bytes and the
+/// value.
+pub fn unpack_int_iter<I>(format: u8, bytes: I) -> Result<(usize, i128), Error>
+where
+    I: Iterator<Item = u8>,
+{
+    match format {
+        0x00..=Format::POSITIVE_FIXINT => Ok((1, format as i128)),
+        0xe0.. => Ok((1, (format as i8) as i128)),
+        Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as i128)),
+        Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v as i128)),
+        Format::UINT32 => take_num_iter(bytes, u32::from_be_bytes).map(|v| (5, v as i128)),
+        Format::UINT64 => take_num_iter(bytes, u64::from_be_bytes).map(|v| (9, v as i128)),
+        Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as i128)),
+        Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v as i128)),
+        Format::INT32 => take_num_iter(bytes, i32::from_be_bytes).map(|v| (5, v as i128)),
+        Format::INT64 => take_num_iter(bytes, i64::from_be_bytes).map(|v| (9, v as i128)),
+        _ => Err(Error::UnexpectedFormatTag),
     }
 }
 
-impl Unpackable for Option<NonZeroU8> {
-    type Error = Error;
+macro_rules! int {
+    ($t:ty) => {
+        impl Unpackable for $t {
+            type Error = Error;
 
-    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        u8::unpack(buf).map(|(s, v)| (s, NonZeroU8::new(v)))
-    }
+            fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+                let (n, v) = unpack_int(buf)?;
+                <$t>::try_from(v)
+                    .map(|v| (n, v))
+                    .map_err(|_| Error::IntegerOverflow)
+            }
 
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        u8::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU8::new(v)))
-    }
+            fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+            where
+                I: IntoIterator<Item = u8>,
+            {
+                let mut bytes = bytes.into_iter();
+                let format = take_byte_iter(bytes.by_ref())?;
+                let (n, v) = unpack_int_iter(format, bytes)?;
+                <$t>::try_from(v)
+                    .map(|v| (n, v))
+                    .map_err(|_| Error::IntegerOverflow)
+            }
+        }
+    };
 }
 
-impl Unpackable for u16 {
+int!(u8);
+int!(u16);
+int!(u32);
+int!(u64);
+int!(usize);
+int!(i8);
+int!(i16);
+int!(i32);
+int!(i64);
+int!(isize);
+
+impl Unpackable for u128 {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u16)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as u16)),
-            Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v)),
-            _ => Err(Error::UnexpectedFormatTag),
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        match buf.first() {
+            Some(&Format::BIN8) => {
+                let mut buf = &buf[1..];
+                if take_byte(&mut buf)? != 16 {
+                    return Err(Error::UnexpectedBinLength);
+                }
+                take_num(&mut buf, u128::from_be_bytes).map(|v| (18, v))
+            }
+            _ => {
+                let (n, v) = unpack_int(buf)?;
+                u128::try_from(v)
+                    .map(|v| (n, v))
+                    .map_err(|_| Error::IntegerOverflow)
+            }
         }
     }
 
@@ -69,40 +113,35 @@ impl Unpackable for u16 {
         let mut bytes = bytes.into_iter();
         let format = take_byte_iter(bytes.by_ref())?;
         match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u16)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as u16)),
-            Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v)),
-            _ => Err(Error::UnexpectedFormatTag),
+            Format::BIN8 => {
+                if take_byte_iter(bytes.by_ref())? != 16 {
+                    return Err(Error::UnexpectedBinLength);
+                }
+                take_num_iter(bytes, u128::from_be_bytes).map(|v| (18, v))
+            }
+            _ => {
+                let (n, v) = unpack_int_iter(format, bytes)?;
+                u128::try_from(v)
+                    .map(|v| (n, v))
+                    .map_err(|_| Error::IntegerOverflow)
+            }
         }
     }
 }
 
-impl Unpackable for Option<NonZeroU16> {
+impl Unpackable for i128 {
     type Error = Error;
 
     fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        u16::unpack(buf).map(|(s, v)| (s, NonZeroU16::new(v)))
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        u16::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU16::new(v)))
-    }
-}
-
-impl Unpackable for u32 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u32)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as u32)),
-            Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v as u32)),
-            Format::UINT32 => take_num(&mut buf, u32::from_be_bytes).map(|v| (5, v)),
-            _ => Err(Error::UnexpectedFormatTag),
+        match buf.first() {
+            Some(&Format::BIN8) => {
+                let mut buf = &buf[1..];
+                if take_byte(&mut buf)? != 16 {
+                    return Err(Error::UnexpectedBinLength);
+                }
+                take_num(&mut buf, i128::from_be_bytes).map(|v| (18, v))
+            }
+            _ => unpack_int(buf),
         }
     }
 
@@ -113,118 +152,74 @@ impl Unpackable for u32 {
         let mut bytes = bytes.into_iter();
         let format = take_byte_iter(bytes.by_ref())?;
         match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u32)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as u32)),
-            Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v as u32)),
-            Format::UINT32 => take_num_iter(bytes, u32::from_be_bytes).map(|v| (5, v)),
-            _ => Err(Error::UnexpectedFormatTag),
+            Format::BIN8 => {
+                if take_byte_iter(bytes.by_ref())? != 16 {
+                    return Err(Error::UnexpectedBinLength);
+                }
+                take_num_iter(bytes, i128::from_be_bytes).map(|v| (18, v))
+            }
+            _ => unpack_int_iter(format, bytes),
         }
     }
 }
 
-impl Unpackable for Option<NonZeroU32> {
+impl Unpackable for Option<NonZeroU8> {
     type Error = Error;
 
     fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        u32::unpack(buf).map(|(s, v)| (s, NonZeroU32::new(v)))
+        u8::unpack(buf).map(|(s, v)| (s, NonZeroU8::new(v)))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        u32::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU32::new(v)))
+        u8::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU8::new(v)))
     }
 }
 
-impl Unpackable for u64 {
+impl Unpackable for Option<NonZeroU16> {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u64)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as u64)),
-            Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v as u64)),
-            Format::UINT32 => take_num(&mut buf, u32::from_be_bytes).map(|v| (5, v as u64)),
-            Format::UINT64 => take_num(&mut buf, u64::from_be_bytes).map(|v| (9, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        u16::unpack(buf).map(|(s, v)| (s, NonZeroU16::new(v)))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u64)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as u64)),
-            Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v as u64)),
-            Format::UINT32 => take_num_iter(bytes, u32::from_be_bytes).map(|v| (5, v as u64)),
-            Format::UINT64 => take_num_iter(bytes, u64::from_be_bytes).map(|v| (9, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+        u16::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU16::new(v)))
     }
 }
 
-impl Unpackable for Option<NonZeroU64> {
+impl Unpackable for Option<NonZeroU32> {
     type Error = Error;
 
     fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        u64::unpack(buf).map(|(s, v)| (s, NonZeroU64::new(v)))
+        u32::unpack(buf).map(|(s, v)| (s, NonZeroU32::new(v)))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        u64::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU64::new(v)))
+        u32::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU32::new(v)))
     }
 }
 
-impl Unpackable for u128 {
+impl Unpackable for Option<NonZeroU64> {
     type Error = Error;
 
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u128)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as u128)),
-            Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v as u128)),
-            Format::UINT32 => take_num(&mut buf, u32::from_be_bytes).map(|v| (5, v as u128)),
-            Format::UINT64 => take_num(&mut buf, u64::from_be_bytes).map(|v| (9, v as u128)),
-            Format::BIN8 => {
-                if take_byte(&mut buf)? != 16 {
-                    return Err(Error::UnexpectedBinLength);
-                }
-                take_num(&mut buf, u128::from_be_bytes).map(|v| (18, v))
-            }
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        u64::unpack(buf).map(|(s, v)| (s, NonZeroU64::new(v)))
     }
 
     fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
     where
         I: IntoIterator<Item = u8>,
     {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as u128)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as u128)),
-            Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v as u128)),
-            Format::UINT32 => take_num_iter(bytes, u32::from_be_bytes).map(|v| (5, v as u128)),
-            Format::UINT64 => take_num_iter(bytes, u64::from_be_bytes).map(|v| (9, v as u128)),
-            Format::BIN8 => {
-                if take_byte_iter(bytes.by_ref())? != 16 {
-                    return Err(Error::UnexpectedBinLength);
-                }
-                take_num_iter(bytes, u128::from_be_bytes).map(|v| (18, v))
-            }
-            _ => Err(Error::UnexpectedFormatTag),
-        }
+        u64::unpack_iter(bytes).map(|(s, v)| (s, NonZeroU64::new(v)))
     }
 }
 
@@ -243,38 +238,6 @@ impl Unpackable for Option<NonZeroU128> {
     }
 }
 
-impl Unpackable for usize {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as usize)),
-            Format::UINT8 => take_byte(&mut buf).map(|v| (2, v as usize)),
-            Format::UINT16 => take_num(&mut buf, u16::from_be_bytes).map(|v| (3, v as usize)),
-            Format::UINT32 => take_num(&mut buf, u32::from_be_bytes).map(|v| (5, v as usize)),
-            Format::UINT64 => take_num(&mut buf, u64::from_be_bytes).map(|v| (9, v as usize)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as usize)),
-            Format::UINT8 => take_byte_iter(bytes).map(|v| (2, v as usize)),
-            Format::UINT16 => take_num_iter(bytes, u16::from_be_bytes).map(|v| (3, v as usize)),
-            Format::UINT32 => take_num_iter(bytes, u32::from_be_bytes).map(|v| (5, v as usize)),
-            Format::UINT64 => take_num_iter(bytes, usize::from_be_bytes).map(|v| (9, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroUsize> {
     type Error = Error;
 
@@ -290,34 +253,6 @@ impl Unpackable for Option<NonZeroUsize> {
     }
 }
 
-impl Unpackable for i8 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as i8)),
-            0xe0.. => Ok((1, format as i8)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, format as i8)),
-            0xe0.. => Ok((1, format as i8)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroI8> {
     type Error = Error;
 
@@ -333,36 +268,6 @@ impl Unpackable for Option<NonZeroI8> {
     }
 }
 
-impl Unpackable for i16 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i16)),
-            0xe0.. => Ok((1, (format as i8) as i16)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as i16)),
-            Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i16)),
-            0xe0.. => Ok((1, (format as i8) as i16)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as i16)),
-            Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroI16> {
     type Error = Error;
 
@@ -378,38 +283,6 @@ impl Unpackable for Option<NonZeroI16> {
     }
 }
 
-impl Unpackable for i32 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i32)),
-            0xe0.. => Ok((1, (format as i8) as i32)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as i32)),
-            Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v as i32)),
-            Format::INT32 => take_num(&mut buf, i32::from_be_bytes).map(|v| (5, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i32)),
-            0xe0.. => Ok((1, (format as i8) as i32)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as i32)),
-            Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v as i32)),
-            Format::INT32 => take_num_iter(bytes, i32::from_be_bytes).map(|v| (5, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroI32> {
     type Error = Error;
 
@@ -425,40 +298,6 @@ impl Unpackable for Option<NonZeroI32> {
     }
 }
 
-impl Unpackable for i64 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i64)),
-            0xe0.. => Ok((1, (format as i8) as i64)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as i64)),
-            Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v as i64)),
-            Format::INT32 => take_num(&mut buf, i32::from_be_bytes).map(|v| (5, v as i64)),
-            Format::INT64 => take_num(&mut buf, i64::from_be_bytes).map(|v| (9, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i64)),
-            0xe0.. => Ok((1, (format as i8) as i64)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as i64)),
-            Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v as i64)),
-            Format::INT32 => take_num_iter(bytes, i32::from_be_bytes).map(|v| (5, v as i64)),
-            Format::INT64 => take_num_iter(bytes, i64::from_be_bytes).map(|v| (9, v)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroI64> {
     type Error = Error;
 
@@ -474,52 +313,6 @@ impl Unpackable for Option<NonZeroI64> {
     }
 }
 
-impl Unpackable for i128 {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i128)),
-            0xe0.. => Ok((1, (format as i8) as i128)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as i128)),
-            Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v as i128)),
-            Format::INT32 => take_num(&mut buf, i32::from_be_bytes).map(|v| (5, v as i128)),
-            Format::INT64 => take_num(&mut buf, i64::from_be_bytes).map(|v| (9, v as i128)),
-            Format::BIN8 => {
-                if take_byte(&mut buf)? != 16 {
-                    return Err(Error::UnexpectedBinLength);
-                }
-                take_num(&mut buf, i128::from_be_bytes).map(|v| (18, v))
-            }
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as i128)),
-            0xe0.. => Ok((1, (format as i8) as i128)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as i128)),
-            Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v as i128)),
-            Format::INT32 => take_num_iter(bytes, i32::from_be_bytes).map(|v| (5, v as i128)),
-            Format::INT64 => take_num_iter(bytes, i64::from_be_bytes).map(|v| (9, v as i128)),
-            Format::BIN8 => {
-                if take_byte_iter(bytes.by_ref())? != 16 {
-                    return Err(Error::UnexpectedBinLength);
-                }
-                take_num_iter(bytes, i128::from_be_bytes).map(|v| (18, v))
-            }
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroI128> {
     type Error = Error;
 
@@ -535,40 +328,6 @@ impl Unpackable for Option<NonZeroI128> {
     }
 }
 
-impl Unpackable for isize {
-    type Error = Error;
-
-    fn unpack(mut buf: &[u8]) -> Result<(usize, Self), Self::Error> {
-        let format = take_byte(&mut buf)?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as isize)),
-            0xe0.. => Ok((1, (format as i8) as isize)),
-            Format::INT8 => take_byte(&mut buf).map(|v| (2, v as i8 as isize)),
-            Format::INT16 => take_num(&mut buf, i16::from_be_bytes).map(|v| (3, v as isize)),
-            Format::INT32 => take_num(&mut buf, i32::from_be_bytes).map(|v| (5, v as isize)),
-            Format::INT64 => take_num(&mut buf, i64::from_be_bytes).map(|v| (9, v as isize)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-
-    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
-    where
-        I: IntoIterator<Item = u8>,
-    {
-        let mut bytes = bytes.into_iter();
-        let format = take_byte_iter(bytes.by_ref())?;
-        match format {
-            0x00..=Format::POSITIVE_FIXINT => Ok((1, (format as i8) as isize)),
-            0xe0.. => Ok((1, (format as i8) as isize)),
-            Format::INT8 => take_byte_iter(bytes).map(|v| (2, v as i8 as isize)),
-            Format::INT16 => take_num_iter(bytes, i16::from_be_bytes).map(|v| (3, v as isize)),
-            Format::INT32 => take_num_iter(bytes, i32::from_be_bytes).map(|v| (5, v as isize)),
-            Format::INT64 => take_num_iter(bytes, i64::from_be_bytes).map(|v| (9, v as isize)),
-            _ => Err(Error::UnexpectedFormatTag),
-        }
-    }
-}
-
 impl Unpackable for Option<NonZeroIsize> {
     type Error = Error;
 
@@ -1,10 +1,30 @@
-use super::{helpers, Error, Format, Unpackable};
+use super::{helpers, Context, Error, Format, Unpackable, UnpackableBorrowed};
 
-#[cfg(feature = "alloc")]
 mod binary;
 mod collections;
 mod common;
 mod float;
 mod int;
 
+pub(crate) use binary::{unpack_bytes, unpack_str};
+pub(crate) use int::unpack_int;
+#[cfg(feature = "json")]
+pub(crate) use int::unpack_int_iter;
+
 pub use collections::{unpack_array, unpack_array_iter, unpack_map, unpack_map_iter};
+
+#[cfg(feature = "alloc")]
+pub use binary::alloc::{unpack_bin, unpack_bin_iter};
+
+/// Attaches the format tag at the start of `buf`, and the amount of bytes read before it, to the
+/// error of a value that failed to unpack from `buf`.
+fn located<X>(error: X::Error, buf: &[u8], offset: usize) -> X::Error
+where
+    X: Unpackable,
+{
+    let error = match buf.first() {
+        Some(tag) => X::error_context(error, Context::Tag(*tag)),
+        None => error,
+    };
+    X::error_context(error, Context::Offset(offset))
+}
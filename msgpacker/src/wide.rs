@@ -0,0 +1,447 @@
+use super::{
+    error::Error,
+    extension::{pack_ext, packed_len_ext, unpack_ext, unpack_ext_iter, ExtensionType},
+    helpers::{take_byte, take_byte_iter, take_num, take_num_iter},
+    Format, Packable, Unpackable,
+};
+use core::{
+    iter,
+    num::{NonZeroI128, NonZeroU128},
+    ops::{Deref, DerefMut},
+    str,
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// 128-bit integer types with alternative encodings for the values that don't fit in 64 bits.
+///
+/// Implemented for `u128`, `i128` and their `NonZero` variants.
+pub trait WideInt: Copy + private::Sealed {
+    /// Whether the plain encoding of the type is signed.
+    #[doc(hidden)]
+    const SIGNED: bool;
+
+    /// Returns the sign and the magnitude of the value.
+    #[doc(hidden)]
+    fn to_wide(self) -> (bool, u128);
+
+    /// Creates a value from its sign and magnitude.
+    #[doc(hidden)]
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error>;
+}
+
+impl private::Sealed for u128 {}
+impl WideInt for u128 {
+    const SIGNED: bool = false;
+
+    fn to_wide(self) -> (bool, u128) {
+        (false, self)
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        match negative && magnitude != 0 {
+            true => Err(Error::IntegerOverflow),
+            false => Ok(magnitude),
+        }
+    }
+}
+
+impl private::Sealed for i128 {}
+impl WideInt for i128 {
+    const SIGNED: bool = true;
+
+    fn to_wide(self) -> (bool, u128) {
+        (self < 0, self.unsigned_abs())
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        match negative {
+            true if magnitude <= i128::MIN.unsigned_abs() => Ok((magnitude as i128).wrapping_neg()),
+            false if magnitude <= i128::MAX as u128 => Ok(magnitude as i128),
+            _ => Err(Error::IntegerOverflow),
+        }
+    }
+}
+
+impl private::Sealed for NonZeroU128 {}
+impl WideInt for NonZeroU128 {
+    const SIGNED: bool = false;
+
+    fn to_wide(self) -> (bool, u128) {
+        self.get().to_wide()
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        u128::from_wide(negative, magnitude)
+            .and_then(|v| NonZeroU128::new(v).ok_or(Error::IntegerOverflow))
+    }
+}
+
+impl private::Sealed for NonZeroI128 {}
+impl WideInt for NonZeroI128 {
+    const SIGNED: bool = true;
+
+    fn to_wide(self) -> (bool, u128) {
+        self.get().to_wide()
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        i128::from_wide(negative, magnitude)
+            .and_then(|v| NonZeroI128::new(v).ok_or(Error::IntegerOverflow))
+    }
+}
+
+impl private::Sealed for Option<NonZeroU128> {}
+impl WideInt for Option<NonZeroU128> {
+    const SIGNED: bool = false;
+
+    fn to_wide(self) -> (bool, u128) {
+        self.map(NonZeroU128::get).unwrap_or(0).to_wide()
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        u128::from_wide(negative, magnitude).map(NonZeroU128::new)
+    }
+}
+
+impl private::Sealed for Option<NonZeroI128> {}
+impl WideInt for Option<NonZeroI128> {
+    const SIGNED: bool = true;
+
+    fn to_wide(self) -> (bool, u128) {
+        self.map(NonZeroI128::get).unwrap_or(0).to_wide()
+    }
+
+    fn from_wide(negative: bool, magnitude: u128) -> Result<Self, Error> {
+        i128::from_wide(negative, magnitude).map(NonZeroI128::new)
+    }
+}
+
+/// Packs the value as a plain integer if it fits in 64 bits.
+fn pack_narrow<T>(buf: &mut T, negative: bool, magnitude: u128) -> Option<usize>
+where
+    T: Extend<u8>,
+{
+    match negative {
+        false if magnitude <= u64::MAX as u128 => Some((magnitude as u64).pack(buf)),
+        true if magnitude <= i64::MIN.unsigned_abs() as u128 => {
+            Some(((magnitude as i128).wrapping_neg() as i64).pack(buf))
+        }
+        _ => None,
+    }
+}
+
+/// Unpacks the plain encoding of the type, accepting the legacy bin form of the wide values.
+fn unpack_plain<W>(buf: &[u8]) -> Result<(usize, W), Error>
+where
+    W: WideInt,
+{
+    let (n, (negative, magnitude)) = match W::SIGNED {
+        true => i128::unpack(buf).map(|(n, v)| (n, v.to_wide()))?,
+        false => u128::unpack(buf).map(|(n, v)| (n, v.to_wide()))?,
+    };
+    Ok((n, W::from_wide(negative, magnitude)?))
+}
+
+fn unpack_plain_iter<I, W>(bytes: I) -> Result<(usize, W), Error>
+where
+    I: IntoIterator<Item = u8>,
+    W: WideInt,
+{
+    let (n, (negative, magnitude)) = match W::SIGNED {
+        true => i128::unpack_iter(bytes).map(|(n, v)| (n, v.to_wide()))?,
+        false => u128::unpack_iter(bytes).map(|(n, v)| (n, v.to_wide()))?,
+    };
+    Ok((n, W::from_wide(negative, magnitude)?))
+}
+
+/// Wrapper struct to pack 128-bit integers that don't fit in 64 bits as an extension.
+///
+/// The payload is the shortest big-endian two's complement representation of the value, with
+/// the extension type id `EXT_TYPE`. Values that fit in 64 bits are packed as plain integers.
+/// Unpacking also accepts the default bin encoding of `u128` and `i128`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExtInt<T, const EXT_TYPE: i8>(pub T);
+
+impl<T, const EXT_TYPE: i8> Deref for ExtInt<T, EXT_TYPE> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T, const EXT_TYPE: i8> DerefMut for ExtInt<T, EXT_TYPE> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T, const EXT_TYPE: i8> ExtInt<T, EXT_TYPE>
+where
+    T: WideInt,
+{
+    /// Returns the two's complement representation, sign extended to 17 bytes, and the length of
+    /// its shortest suffix.
+    fn payload(&self) -> ([u8; 17], usize) {
+        let (negative, magnitude) = self.0.to_wide();
+        let (fill, bits, low) = match negative {
+            true => (
+                0xff,
+                128 - (magnitude - 1).leading_zeros(),
+                magnitude.wrapping_neg(),
+            ),
+            false => (0x00, 128 - magnitude.leading_zeros(), magnitude),
+        };
+        let mut payload = [fill; 17];
+        payload[1..].copy_from_slice(&low.to_be_bytes());
+        (payload, bits as usize / 8 + 1)
+    }
+}
+
+impl<T, const EXT_TYPE: i8> ExtensionType for ExtInt<T, EXT_TYPE>
+where
+    T: WideInt,
+{
+    const EXT_TYPE: i8 = EXT_TYPE;
+
+    type Error = Error;
+
+    fn pack_payload<B>(&self, buf: &mut B) -> usize
+    where
+        B: Extend<u8>,
+    {
+        let (payload, len) = self.payload();
+        buf.extend(payload[17 - len..].iter().copied());
+        len
+    }
+
+    fn payload_len(&self) -> usize {
+        self.payload().1
+    }
+
+    fn unpack_payload(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let len = buf.len();
+        if !(1..=17).contains(&len) {
+            return Err(Error::InvalidExtension);
+        }
+        let negative = buf[0] & 0x80 != 0;
+        let fill = if negative { 0xff } else { 0x00 };
+        let mut payload = [fill; 17];
+        payload[17 - len..].copy_from_slice(buf);
+        if payload[0] != fill {
+            return Err(Error::IntegerOverflow);
+        }
+        let mut low = [0u8; 16];
+        low.copy_from_slice(&payload[1..]);
+        let low = u128::from_be_bytes(low);
+        let magnitude = match negative {
+            true if low == 0 => return Err(Error::IntegerOverflow),
+            true => low.wrapping_neg(),
+            false => low,
+        };
+        Ok((len, ExtInt(T::from_wide(negative, magnitude)?)))
+    }
+
+    fn unpack_payload_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut payload = [0u8; 18];
+        let mut n = 0;
+        for (b, x) in payload.iter_mut().zip(bytes) {
+            *b = x;
+            n += 1;
+        }
+        Self::unpack_payload(&payload[..n])
+    }
+}
+
+impl<T, const EXT_TYPE: i8> Packable for ExtInt<T, EXT_TYPE>
+where
+    T: WideInt,
+{
+    fn pack<B>(&self, buf: &mut B) -> usize
+    where
+        B: Extend<u8>,
+    {
+        let (negative, magnitude) = self.0.to_wide();
+        pack_narrow(buf, negative, magnitude).unwrap_or_else(|| pack_ext(buf, self))
+    }
+
+    fn packed_len(&self) -> usize {
+        let (negative, magnitude) = self.0.to_wide();
+        match pack_narrow(&mut crate::ByteCounter::default(), negative, magnitude) {
+            Some(n) => n,
+            None => packed_len_ext(self),
+        }
+    }
+}
+
+impl<T, const EXT_TYPE: i8> Unpackable for ExtInt<T, EXT_TYPE>
+where
+    T: WideInt,
+{
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        match buf.first() {
+            Some(&(Format::FIXEXT1..=Format::FIXEXT16 | Format::EXT8..=Format::EXT32)) => {
+                unpack_ext(buf)
+            }
+            _ => unpack_plain(buf).map(|(n, v)| (n, ExtInt(v))),
+        }
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut bytes = bytes.into_iter();
+        let format = take_byte_iter(bytes.by_ref())?;
+        let bytes = iter::once(format).chain(bytes);
+        match format {
+            Format::FIXEXT1..=Format::FIXEXT16 | Format::EXT8..=Format::EXT32 => {
+                unpack_ext_iter(bytes)
+            }
+            _ => unpack_plain_iter(bytes).map(|(n, v)| (n, ExtInt(v))),
+        }
+    }
+}
+
+/// Wrapper struct to pack 128-bit integers that don't fit in 64 bits as decimal strings.
+///
+/// Values that fit in 64 bits are packed as plain integers. Unpacking also accepts the default
+/// bin encoding of `u128` and `i128`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DecimalStr<T>(pub T);
+
+impl<T> Deref for DecimalStr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for DecimalStr<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Maximum length of a decimal 128-bit integer, sign included.
+const DECIMAL_LEN: usize = 40;
+
+impl<T> DecimalStr<T>
+where
+    T: WideInt,
+{
+    /// Returns the decimal digits, aligned to the end of the array, and the start index.
+    fn digits(&self) -> ([u8; DECIMAL_LEN], usize) {
+        let (negative, mut magnitude) = self.0.to_wide();
+        let mut digits = [0u8; DECIMAL_LEN];
+        let mut i = DECIMAL_LEN;
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (magnitude % 10) as u8;
+            magnitude /= 10;
+            if magnitude == 0 {
+                break;
+            }
+        }
+        if negative {
+            i -= 1;
+            digits[i] = b'-';
+        }
+        (digits, i)
+    }
+
+    fn parse(digits: &[u8]) -> Result<T, Error> {
+        let digits = str::from_utf8(digits).map_err(|_| Error::InvalidUtf8)?;
+        let (negative, digits) = match digits.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, digits),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidDecimal);
+        }
+        let magnitude = digits.parse().map_err(|_| Error::IntegerOverflow)?;
+        T::from_wide(negative, magnitude)
+    }
+}
+
+impl<T> Packable for DecimalStr<T>
+where
+    T: WideInt,
+{
+    fn pack<B>(&self, buf: &mut B) -> usize
+    where
+        B: Extend<u8>,
+    {
+        let (negative, magnitude) = self.0.to_wide();
+        pack_narrow(buf, negative, magnitude).unwrap_or_else(|| {
+            let (digits, i) = self.digits();
+            // Safety: the digits are ASCII
+            unsafe { str::from_utf8_unchecked(&digits[i..]) }.pack(buf)
+        })
+    }
+}
+
+impl<T> Unpackable for DecimalStr<T>
+where
+    T: WideInt,
+{
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        let mut rest = buf;
+        let (n, len) = match take_byte(&mut rest)? {
+            format @ 0xa0..=0xbf => (1, format as usize & 0x1f),
+            Format::STR8 => (2, take_byte(&mut rest)? as usize),
+            Format::STR16 => (3, take_num(&mut rest, u16::from_be_bytes)? as usize),
+            Format::STR32 => (5, take_num(&mut rest, u32::from_be_bytes)? as usize),
+            _ => return unpack_plain(buf).map(|(n, v)| (n, DecimalStr(v))),
+        };
+        if len > DECIMAL_LEN {
+            return Err(Error::InvalidDecimal);
+        }
+        if rest.len() < len {
+            return Err(Error::BufferTooShort);
+        }
+        Ok((n + len, DecimalStr(Self::parse(&rest[..len])?)))
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut bytes = bytes.into_iter();
+        let (n, len) = match take_byte_iter(bytes.by_ref())? {
+            format @ 0xa0..=0xbf => (1, format as usize & 0x1f),
+            Format::STR8 => (2, take_byte_iter(bytes.by_ref())? as usize),
+            Format::STR16 => (
+                3,
+                take_num_iter(bytes.by_ref(), u16::from_be_bytes)? as usize,
+            ),
+            Format::STR32 => (
+                5,
+                take_num_iter(bytes.by_ref(), u32::from_be_bytes)? as usize,
+            ),
+            format => {
+                return unpack_plain_iter(iter::once(format).chain(bytes))
+                    .map(|(n, v)| (n, DecimalStr(v)))
+            }
+        };
+        if len > DECIMAL_LEN {
+            return Err(Error::InvalidDecimal);
+        }
+        let mut digits = [0u8; DECIMAL_LEN];
+        for d in digits[..len].iter_mut() {
+            *d = take_byte_iter(bytes.by_ref())?;
+        }
+        Ok((n + len, DecimalStr(Self::parse(&digits[..len])?)))
+    }
+}
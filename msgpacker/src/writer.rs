@@ -0,0 +1,146 @@
+use super::Error;
+
+/// A bounded writer over a caller-provided slice of bytes.
+///
+/// Bytes are written sequentially until the slice is full. Once a byte doesn't fit, the writer
+/// is flagged as overflowed and every subsequent byte is discarded, so the slice is never
+/// written past the failure point.
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    position: usize,
+    overflowed: bool,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a new writer that starts at the beginning of the provided slice.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            position: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Amount of bytes written to the slice.
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns `true` if a byte didn't fit in the slice.
+    pub const fn is_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Returns the amount of written bytes, or [Error::BufferOverflow] if the slice was too
+    /// short.
+    pub fn finish(self) -> Result<usize, Error> {
+        if self.overflowed {
+            return Err(Error::BufferOverflow);
+        }
+        Ok(self.position)
+    }
+}
+
+impl<'a> Extend<u8> for SliceWriter<'a> {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = u8>,
+    {
+        if self.overflowed {
+            return;
+        }
+        for b in iter {
+            match self.buf.get_mut(self.position) {
+                Some(x) => {
+                    *x = b;
+                    self.position += 1;
+                }
+                None => {
+                    self.overflowed = true;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Feeds the bytes of the iterator to `f` in chunks, without allocating.
+fn feed_chunks<T, F>(iter: T, mut f: F)
+where
+    T: IntoIterator<Item = u8>,
+    F: FnMut(&[u8]),
+{
+    let mut chunk = [0u8; 64];
+    let mut len = 0;
+    for b in iter {
+        chunk[len] = b;
+        len += 1;
+        if len == chunk.len() {
+            f(&chunk);
+            len = 0;
+        }
+    }
+    if len > 0 {
+        f(&chunk[..len]);
+    }
+}
+
+/// A sink that streams the packed bytes into a [Hasher](core::hash::Hasher).
+///
+/// The bytes are written in chunks, so the result is only independent of the packing
+/// implementation for streaming hashers, such as `crc32fast::Hasher`.
+#[derive(Debug, Default, Clone)]
+pub struct HasherSink<H>(pub H);
+
+impl<H> HasherSink<H> {
+    /// Returns the wrapped hasher.
+    pub fn into_inner(self) -> H {
+        self.0
+    }
+}
+
+impl<H> Extend<u8> for HasherSink<H>
+where
+    H: core::hash::Hasher,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = u8>,
+    {
+        feed_chunks(iter, |b| self.0.write(b));
+    }
+}
+
+#[cfg(feature = "digest")]
+mod digest {
+    use super::feed_chunks;
+    use ::digest::Update;
+
+    /// A sink that streams the packed bytes into a [Update] implementation, such as a
+    /// cryptographic digest.
+    #[derive(Debug, Default, Clone)]
+    pub struct HashSink<D>(pub D);
+
+    impl<D> HashSink<D> {
+        /// Returns the wrapped digest.
+        pub fn into_inner(self) -> D {
+            self.0
+        }
+    }
+
+    impl<D> Extend<u8> for HashSink<D>
+    where
+        D: Update,
+    {
+        fn extend<T>(&mut self, iter: T)
+        where
+            T: IntoIterator<Item = u8>,
+        {
+            feed_chunks(iter, |b| self.0.update(b));
+        }
+    }
+}
+
+#[cfg(feature = "digest")]
+pub use digest::HashSink;
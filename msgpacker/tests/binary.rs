@@ -1,5 +1,6 @@
 use msgpacker::prelude::*;
 use proptest::prelude::*;
+use std::borrow::Cow;
 
 mod utils;
 
@@ -29,7 +30,61 @@ fn empty_str() {
     assert_eq!(s, y);
 }
 
+#[test]
+fn bin_accepts_array_of_u8() {
+    let bytes = vec![1u8, 2, 3].pack_to_vec();
+    assert_eq!(0x93, bytes[0]);
+    assert_eq!(
+        Ok((4, vec![1u8, 2, 3])),
+        msgpacker::unpack_bin::<Vec<u8>>(&bytes)
+    );
+    assert_eq!(
+        Ok((4, [1u8, 2, 3])),
+        msgpacker::unpack_bin_iter::<_, [u8; 3]>(bytes.clone())
+    );
+    assert_eq!(
+        Ok((4, Box::from(&[1u8, 2, 3][..]))),
+        Box::<[u8]>::unpack(&bytes)
+    );
+}
+
+#[test]
+fn bin_length_mismatch() {
+    let mut bytes = vec![];
+    msgpacker::pack_bin(&mut bytes, [1u8, 2, 3]);
+    assert_eq!(vec![0xc4, 3, 1, 2, 3], bytes);
+    assert_eq!(
+        Err(Error::UnexpectedBinLength),
+        msgpacker::unpack_bin::<[u8; 4]>(&bytes)
+    );
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        msgpacker::unpack_bin::<Vec<u8>>(&bytes[..4])
+    );
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        msgpacker::unpack_bin_iter::<_, Vec<u8>>(bytes[..4].to_vec())
+    );
+}
+
 proptest! {
+    #[test]
+    fn boxed_bytes(v: Vec<u8>) {
+        let b: Box<[u8]> = v.clone().into();
+        prop_assert_eq!(msgpacker::packed_len_bin(&v), b.packed_len());
+        utils::case(b);
+    }
+
+    #[test]
+    fn cow_bytes(v: Vec<u8>) {
+        utils::case(Cow::<[u8]>::Owned(v));
+    }
+
+    #[test]
+    fn bin_roundtrip(v: Vec<u8>) {
+        utils::case(MsgPackerBin(v));
+    }
+
     #[test]
     fn str(s: String) {
         utils::case(s);
@@ -0,0 +1,132 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+use std::borrow::Cow;
+
+#[derive(Debug, PartialEq, MsgPacker)]
+struct Message<'a> {
+    id: u32,
+    name: &'a str,
+    payload: &'a [u8],
+    raw: MsgPackerBinSlice<'a>,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, MsgPacker)]
+struct Name<'a>(&'a str, Option<u8>);
+
+fn roundtrip<'a, T>(x: &T, bytes: &'a mut Vec<u8>) -> T
+where
+    T: Packable + UnpackableBorrowed<'a> + PartialEq + core::fmt::Debug,
+    <T as UnpackableBorrowed<'a>>::Error: core::fmt::Debug,
+{
+    let n = x.pack(bytes);
+    assert_eq!(n, x.packed_len());
+    let (o, y) = T::unpack_borrowed(bytes).unwrap();
+    assert_eq!(n, o);
+    assert_eq!(x, &y);
+    y
+}
+
+#[test]
+fn str_points_into_buffer() {
+    let bytes = "hello".pack_to_vec();
+    let (n, s) = <&str>::unpack_borrowed(&bytes).unwrap();
+    assert_eq!(bytes.len(), n);
+    assert_eq!("hello", s);
+    assert_eq!(bytes[1..].as_ptr(), s.as_ptr());
+}
+
+#[test]
+fn bytes_require_bin() {
+    let bytes = [1u8, 2, 3][..].pack_to_vec();
+    assert_eq!(vec![0xc4, 3, 1, 2, 3], bytes);
+    assert_eq!(Ok((5, &[1u8, 2, 3][..])), <&[u8]>::unpack_borrowed(&bytes));
+    assert_eq!(
+        Err(Error::UnexpectedFormatTag),
+        <&[u8]>::unpack_borrowed(&vec![1u8, 2, 3].pack_to_vec())
+    );
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        <&[u8]>::unpack_borrowed(&bytes[..4])
+    );
+}
+
+#[derive(Debug, PartialEq, MsgPacker)]
+struct Owned {
+    id: u32,
+    #[msgpacker(bin)]
+    payload: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, MsgPacker)]
+struct Borrowed<'a> {
+    id: u32,
+    payload: &'a [u8],
+}
+
+#[test]
+fn borrowed_bytes_match_bin_fields() {
+    let owned = Owned {
+        id: 7,
+        payload: vec![1, 2, 3],
+    };
+    let bytes = owned.pack_to_vec();
+    let borrowed = Borrowed {
+        id: 7,
+        payload: &[1, 2, 3],
+    };
+    assert_eq!(bytes, borrowed.pack_to_vec());
+    assert_eq!(
+        Ok((bytes.len(), borrowed)),
+        Borrowed::unpack_borrowed(&bytes)
+    );
+}
+
+#[test]
+fn cow_str_is_borrowed() {
+    let bytes = "cow".pack_to_vec();
+    let (_, s) = Cow::<str>::unpack_borrowed(&bytes).unwrap();
+    assert!(matches!(s, Cow::Borrowed("cow")));
+}
+
+#[test]
+fn owned_types_are_borrowed_too() {
+    let bytes = 42u16.pack_to_vec();
+    assert_eq!(Ok((1, 42u16)), u16::unpack_borrowed(&bytes));
+}
+
+#[test]
+fn derived_tuple_struct() {
+    let mut bytes = vec![];
+    roundtrip(&Name("tuple", Some(3)), &mut bytes);
+}
+
+proptest! {
+    #[test]
+    fn str(s: String) {
+        let mut bytes = vec![];
+        roundtrip(&s.as_str(), &mut bytes);
+    }
+
+    #[test]
+    fn bytes(v: Vec<u8>) {
+        let mut bytes = vec![];
+        roundtrip(&v.as_slice(), &mut bytes);
+        let mut bytes = vec![];
+        roundtrip(&MsgPackerBinSlice(&v), &mut bytes);
+    }
+
+    #[test]
+    fn derived_struct(id: u32, name: String, payload: Vec<u8>, raw: Vec<u8>, tags: Vec<String>) {
+        let m = Message {
+            id,
+            name: &name,
+            payload: &payload,
+            raw: MsgPackerBinSlice(&raw),
+            tags,
+        };
+        let mut bytes = vec![];
+        let x = roundtrip(&m, &mut bytes);
+        prop_assert_eq!(m, x);
+    }
+}
@@ -0,0 +1,194 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, MsgPacker)]
+struct Document {
+    id: i64,
+    tags: HashSet<String>,
+    #[msgpacker(map)]
+    scores: HashMap<String, i32>,
+    weights: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, MsgPacker)]
+enum Change {
+    Tags(HashSet<u16>),
+    Weight { value: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq, MsgPacker)]
+#[msgpacker(ext = 9)]
+struct Inventory {
+    id: i64,
+    #[msgpacker(map)]
+    stock: HashMap<String, u32>,
+}
+
+fn canonical<T: Packable>(x: &T) -> Vec<u8> {
+    let mut bytes = vec![];
+    let n = x.pack_canonical(&mut bytes);
+    assert_eq!(n, bytes.len());
+    bytes
+}
+
+#[test]
+fn shortest_integers() {
+    assert_eq!(vec![0xd1, 0x00, 0xc8], 200i64.pack_to_vec());
+    assert_eq!(vec![0xcc, 0xc8], canonical(&200i64));
+    assert_eq!(vec![0xff], canonical(&-1i32));
+    assert_eq!(vec![0xd0, 0x80], canonical(&i8::MIN));
+    assert_eq!(canonical(&(i128::MAX as u128)), canonical(&i128::MAX));
+}
+
+#[test]
+fn nan_is_normalized() {
+    let nan = f64::from_bits(0x7ff8_dead_beef_0001);
+    assert!(nan.is_nan());
+    assert_eq!(f64::NAN.pack_to_vec(), canonical(&nan));
+    assert_eq!(
+        f64::NAN.pack_to_vec(),
+        msgpacker::canonicalize(&nan.pack_to_vec()).unwrap()
+    );
+    assert_eq!(1.5f32.pack_to_vec(), canonical(&1.5f32));
+}
+
+#[test]
+fn sets_and_maps_are_sorted() {
+    let words = ["b", "aa", "ccc", "d", "ee"];
+    let set: HashSet<&str> = words.into_iter().collect();
+    let reversed: HashSet<&str> = words.into_iter().rev().collect();
+    let ordered: BTreeSet<&str> = words.into_iter().collect();
+    assert_eq!(canonical(&set), canonical(&reversed));
+    assert_eq!(canonical(&set), canonical(&ordered));
+    assert_eq!(
+        vec![0x95, 0xa1, b'b', 0xa1, b'd', 0xa2, b'a', b'a', 0xa2, b'e', b'e'],
+        canonical(&ordered)[..11].to_vec()
+    );
+
+    let map: HashMap<i64, bool> = (-20..20).map(|i| (i * 7, i % 2 == 0)).collect();
+    let ordered: BTreeMap<i64, bool> = map.clone().into_iter().collect();
+    assert_eq!(canonical(&map), canonical(&ordered));
+    assert_eq!(
+        canonical(&map),
+        msgpacker::canonicalize(&ordered.pack_to_vec()).unwrap()
+    );
+}
+
+#[test]
+fn derived_types() {
+    let tags = ["red", "green", "blue", "cyan", "magenta"];
+    let a = Document {
+        id: 300,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        scores: tags
+            .iter()
+            .zip(0..)
+            .map(|(t, i)| (t.to_string(), i))
+            .collect(),
+        weights: vec![f64::NAN, -0.5],
+    };
+    let b = Document {
+        tags: tags.iter().rev().map(|t| t.to_string()).collect(),
+        scores: tags
+            .iter()
+            .zip(0..5)
+            .rev()
+            .map(|(t, i)| (t.to_string(), i))
+            .collect(),
+        ..a.clone()
+    };
+    let bytes = canonical(&a);
+    assert_eq!(bytes, canonical(&b));
+    assert_eq!(bytes, msgpacker::canonicalize(&bytes).unwrap());
+
+    let (n, c) = Document::unpack(&bytes).unwrap();
+    assert_eq!(bytes.len(), n);
+    assert_eq!((a.id, a.tags, a.scores), (c.id, c.tags, c.scores));
+
+    let change: HashSet<u16> = (0..40).map(|i| i * 1000).collect();
+    let reversed: HashSet<u16> = (0..40).rev().map(|i| i * 1000).collect();
+    assert_eq!(
+        canonical(&Change::Tags(change)),
+        canonical(&Change::Tags(reversed))
+    );
+    assert_eq!(
+        Change::Weight { value: f32::NAN }.pack_to_vec(),
+        canonical(&Change::Weight {
+            value: f32::from_bits(0x7fc0_0001)
+        })
+    );
+}
+
+#[test]
+fn derived_extensions() {
+    let items = ["apple", "pear", "plum", "fig", "kiwi", "lime"];
+    let a = Inventory {
+        id: 200,
+        stock: items
+            .iter()
+            .zip(0..)
+            .map(|(i, n)| (i.to_string(), n))
+            .collect(),
+    };
+    let b = Inventory {
+        stock: items
+            .iter()
+            .zip(0..6)
+            .rev()
+            .map(|(i, n)| (i.to_string(), n))
+            .collect(),
+        ..a.clone()
+    };
+    let bytes = canonical(&a);
+    assert_eq!(bytes, canonical(&b));
+    assert!(bytes.len() < a.pack_to_vec().len());
+
+    let (n, c) = Inventory::unpack(&bytes).unwrap();
+    assert_eq!(bytes.len(), n);
+    assert_eq!(a, c);
+}
+
+#[test]
+fn canonicalize_bytes() {
+    // int16 5, str8 "abc", bin32 [1], map16 {2: nil, 1: nil}, array16 [uint64 1]
+    let bytes = [
+        0xd1, 0x00, 0x05, 0xd9, 3, b'a', b'b', b'c', 0xc6, 0, 0, 0, 1, 1, 0xde, 0, 2, 0x02, 0xc0,
+        0x01, 0xc0, 0xdc, 0, 1, 0xcf, 0, 0, 0, 0, 0, 0, 0, 1,
+    ];
+    assert_eq!(
+        vec![0x05, 0xa3, b'a', b'b', b'c', 0xc4, 1, 1, 0x82, 0x01, 0xc0, 0x02, 0xc0, 0x91, 0x01],
+        msgpacker::canonicalize(&bytes).unwrap()
+    );
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        msgpacker::canonicalize(&bytes[..bytes.len() - 1])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedFormatTag),
+        msgpacker::canonicalize(&[0xc1])
+    );
+    assert_eq!(
+        Err(Error::LimitExceeded(msgpacker::Limit::Depth)),
+        msgpacker::canonicalize(&[0x91; 100])
+    );
+}
+
+proptest! {
+    #[test]
+    fn canonicalize_matches_pack_canonical(map: HashMap<i32, Vec<i64>>, x: Option<i16>) {
+        let value = (map, x);
+        let bytes = canonical(&value);
+        prop_assert_eq!(&bytes, &msgpacker::canonicalize(&value.pack_to_vec()).unwrap());
+        prop_assert_eq!(Ok((bytes.len(), value)), <(HashMap<i32, Vec<i64>>, Option<i16>)>::unpack(&bytes));
+    }
+
+    #[test]
+    fn sets_ignore_insertion_order(v: Vec<String>) {
+        let set: HashSet<String> = v.iter().cloned().collect();
+        let reversed: HashSet<String> = v.into_iter().rev().collect();
+        let bytes = canonical(&set);
+        prop_assert_eq!(&bytes, &canonical(&reversed));
+        prop_assert_eq!(Ok((bytes.len(), set)), HashSet::<String>::unpack(&bytes));
+    }
+}
@@ -37,12 +37,108 @@ struct Value {
     pub t16: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct Payload {
+    data: Vec<u8>,
+    #[msgpacker(bin)]
+    blob: Vec<u8>,
+    #[msgpacker(bin)]
+    hash: [u8; 4],
+    #[msgpacker(array)]
+    raw: Vec<u8>,
+    boxed: Box<[u8]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct Digest(#[msgpacker(bin)] [u8; 4], Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+enum Message {
+    Raw(Vec<u8>),
+    Blob(#[msgpacker(bin)] Vec<u8>),
+    Signed {
+        data: Vec<u8>,
+        #[msgpacker(bin)]
+        signature: [u8; 2],
+    },
+}
+
+#[test]
+fn derived_byte_vectors_are_arrays() {
+    // the wire format of `Vec<u8>` fields doesn't change with the bin support
+    let bytes = Payload {
+        data: vec![1, 2],
+        blob: vec![],
+        hash: [0; 4],
+        raw: vec![],
+        boxed: vec![].into(),
+    }
+    .pack_to_vec();
+    assert_eq!(vec![0x92, 1, 2], bytes[..3]);
+    assert_eq!(vec![1, 2].pack_to_vec(), bytes[..3]);
+
+    let bytes = Digest([0; 4], vec![3]).pack_to_vec();
+    assert_eq!(vec![0x91, 3], bytes[6..]);
+
+    let bytes = Message::Raw(vec![4, 5]).pack_to_vec();
+    assert_eq!(vec![0, 0x92, 4, 5], bytes);
+}
+
+#[test]
+fn bytes_are_packed_as_bin() {
+    let payload = Payload {
+        data: vec![1, 2],
+        blob: vec![3],
+        hash: [4, 5, 6, 7],
+        raw: vec![8],
+        boxed: vec![9].into(),
+    };
+    let bytes = payload.pack_to_vec();
+    assert_eq!(
+        vec![0x92, 1, 2, 0xc4, 1, 3, 0xc4, 4, 4, 5, 6, 7, 0x91, 8, 0xc4, 1, 9],
+        bytes
+    );
+    assert_eq!(Ok((bytes.len(), payload)), Payload::unpack(&bytes));
+
+    let digest = Digest([1, 2, 3, 4], vec![]);
+    let bytes = digest.pack_to_vec();
+    assert_eq!(vec![0xc4, 4, 1, 2, 3, 4, 0x90], bytes);
+    assert_eq!(Ok((7, digest.clone())), Digest::unpack_iter(bytes));
+    // arrays of u8 are accepted as well
+    assert_eq!(Ok((6, digest)), Digest::unpack(&[0x94, 1, 2, 3, 4, 0x90]));
+
+    let message = Message::Blob(vec![1, 2]);
+    let bytes = message.pack_to_vec();
+    assert_eq!(vec![1, 0xc4, 2, 1, 2], bytes);
+    assert_eq!(Ok((5, message.clone())), Message::unpack_iter(bytes));
+
+    let message = Message::Signed {
+        data: vec![3],
+        signature: [4, 5],
+    };
+    let bytes = message.pack_to_vec();
+    assert_eq!(vec![2, 0x91, 3, 0xc4, 2, 4, 5], bytes);
+    assert_eq!(Ok((7, message)), Message::unpack(&bytes));
+}
+
 proptest! {
+    #[test]
+    fn payload(data: Vec<u8>, blob: Vec<u8>, hash: [u8; 4], raw: Vec<u8>, boxed: Vec<u8>) {
+        utils::case(Payload {
+            data,
+            blob,
+            hash,
+            raw,
+            boxed: boxed.into(),
+        });
+    }
+
     #[test]
     fn array(value: Vec<Value>) {
         let mut bytes = Vec::new();
         let n = msgpacker::pack_array(&mut bytes, &value);
         assert_eq!(n, bytes.len());
+        assert_eq!(n, msgpacker::packed_len_array(&value));
         let (o, x): (usize, Vec<Value>) = msgpacker::unpack_array(&bytes).unwrap();
         let (p, y): (usize, Vec<Value>) = msgpacker::unpack_array_iter(bytes).unwrap();
         assert_eq!(n, o);
@@ -56,6 +152,7 @@ proptest! {
         let mut bytes = Vec::new();
         let n = msgpacker::pack_map(&mut bytes, &map);
         assert_eq!(n, bytes.len());
+        assert_eq!(n, msgpacker::packed_len_map(&map));
         let (o, x): (usize, HashMap<Value, Value>) = msgpacker::unpack_map(&bytes).unwrap();
         let (p, y): (usize, HashMap<Value, Value>) = msgpacker::unpack_map_iter(bytes).unwrap();
         assert_eq!(n, o);
@@ -0,0 +1,227 @@
+use msgpacker::prelude::*;
+use msgpacker::{DetailedError, PathSegment};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+struct Item {
+    name: String,
+    price: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+struct Order {
+    id: u32,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+struct Orders {
+    orders: Vec<Order>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+struct Catalog {
+    #[msgpacker(map)]
+    items: BTreeMap<String, Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+enum Shape {
+    Circle { radius: Item },
+    Pair(Item, Item),
+}
+
+const MARKER: u64 = 0xdead_beef;
+
+fn item(price: u64) -> Item {
+    Item {
+        name: "item".into(),
+        price,
+    }
+}
+
+fn corrupt(bytes: &mut [u8]) -> usize {
+    // uint32 tag followed by the marker
+    let marker = [0xce, 0xde, 0xad, 0xbe, 0xef];
+    let offset = bytes
+        .windows(marker.len())
+        .position(|w| w == marker)
+        .unwrap();
+    bytes[offset] = 0xc1;
+    offset
+}
+
+fn path(e: &DetailedError) -> Vec<PathSegment> {
+    e.path().copied().collect()
+}
+
+#[test]
+fn nested_path_and_offset() {
+    let orders = Orders {
+        orders: (0..20)
+            .map(|id| Order {
+                id,
+                items: (0..5)
+                    .map(|i| item(if id == 17 && i == 3 { MARKER } else { i }))
+                    .collect(),
+            })
+            .collect(),
+    };
+    let mut bytes = orders.pack_to_vec();
+    let offset = corrupt(&mut bytes);
+
+    let e = Orders::unpack(&bytes).unwrap_err();
+    assert_eq!(Error::UnexpectedFormatTag, e.kind());
+    assert_eq!(offset, e.offset());
+    assert_eq!(Some(0xc1), e.tag());
+    assert_eq!(
+        vec![
+            PathSegment::Field("orders"),
+            PathSegment::Index(17),
+            PathSegment::Field("items"),
+            PathSegment::Index(3),
+            PathSegment::Field("price"),
+        ],
+        path(&e)
+    );
+    assert_eq!(
+        format!("unexpected format tag (tag 0xc1) at byte {offset} in orders[17].items[3].price"),
+        e.to_string()
+    );
+
+    let i = Orders::unpack_iter(bytes).unwrap_err();
+    assert_eq!(e.kind(), i.kind());
+    assert_eq!(e.offset(), i.offset());
+    assert_eq!(None, i.tag());
+    assert_eq!(path(&e), path(&i));
+}
+
+#[test]
+fn map_value_path() {
+    let catalog = Catalog {
+        items: [("a", 1), ("b", MARKER)]
+            .into_iter()
+            .map(|(k, p)| (k.to_string(), item(p)))
+            .collect(),
+    };
+    let mut bytes = catalog.pack_to_vec();
+    let offset = corrupt(&mut bytes);
+
+    let e = Catalog::unpack(&bytes).unwrap_err();
+    assert_eq!(offset, e.offset());
+    assert_eq!(
+        vec![
+            PathSegment::Field("items"),
+            PathSegment::Value(1),
+            PathSegment::Field("price"),
+        ],
+        path(&e)
+    );
+    assert!(e.to_string().ends_with("in items[1].value.price"));
+}
+
+#[test]
+fn enum_fields_path() {
+    let mut bytes = Shape::Circle {
+        radius: item(MARKER),
+    }
+    .pack_to_vec();
+    let offset = corrupt(&mut bytes);
+    let e = Shape::unpack(&bytes).unwrap_err();
+    assert_eq!(offset, e.offset());
+    assert_eq!(
+        vec![PathSegment::Field("radius"), PathSegment::Field("price")],
+        path(&e)
+    );
+
+    let mut bytes = Shape::Pair(item(1), item(MARKER)).pack_to_vec();
+    let offset = corrupt(&mut bytes);
+    let e = Shape::unpack(&bytes).unwrap_err();
+    assert_eq!(offset, e.offset());
+    assert_eq!(
+        vec![PathSegment::Field("1"), PathSegment::Field("price")],
+        path(&e)
+    );
+}
+
+#[test]
+fn truncated_input() {
+    let bytes = vec![item(1), item(2)].pack_to_vec();
+    let e = Vec::<Item>::unpack(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(Error::BufferTooShort, e.kind());
+    assert_eq!(bytes.len() - 1, e.offset());
+    assert_eq!(
+        vec![PathSegment::Index(1), PathSegment::Field("price")],
+        path(&e)
+    );
+    assert_eq!(Error::BufferTooShort, Error::from(e));
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct Plain {
+    orders: Vec<Order>,
+}
+
+#[test]
+fn plain_errors_by_default() {
+    let bytes = Plain {
+        orders: vec![Order {
+            id: 1,
+            items: vec![item(MARKER)],
+        }],
+    }
+    .pack_to_vec();
+    let e: Error = Plain::unpack(&bytes[..bytes.len() - 1]).unwrap_err();
+    assert_eq!(Error::BufferTooShort, e);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Odd(u8);
+
+#[derive(Debug, PartialEq, Eq)]
+enum OddError {
+    Even,
+    Unpack(Error),
+}
+
+impl From<Error> for OddError {
+    fn from(e: Error) -> Self {
+        OddError::Unpack(e)
+    }
+}
+
+impl Unpackable for Odd {
+    type Error = OddError;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        match u8::unpack(buf)? {
+            (_, v) if v % 2 == 0 => Err(OddError::Even),
+            (n, v) => Ok((n, Odd(v))),
+        }
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        match u8::unpack_iter(bytes)? {
+            (_, v) if v % 2 == 0 => Err(OddError::Even),
+            (n, v) => Ok((n, Odd(v))),
+        }
+    }
+}
+
+#[test]
+fn errors_without_context() {
+    let bytes = vec![1u8, 3, 4].pack_to_vec();
+    assert_eq!(Err(OddError::Even), Vec::<Odd>::unpack(&bytes).map(|_| ()));
+    assert_eq!(
+        Err(OddError::Even),
+        Vec::<Odd>::unpack_iter(bytes).map(|_| ())
+    );
+}
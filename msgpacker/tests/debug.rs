@@ -0,0 +1,95 @@
+use msgpacker::debug::dump;
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+
+#[test]
+fn annotated_lines() {
+    let mut buf = Vec::new();
+    (vec![1u8; 3], "ab", -300i32).pack(&mut buf);
+    msgpacker::pack_map(&mut buf, [(true, 1.5f32)]);
+    msgpacker::pack_bin(&mut buf, [1u8; 10]);
+    ExtensionRef {
+        ext_type: 3,
+        data: &[7; 4],
+    }
+    .pack(&mut buf);
+
+    let expected = "\
+0000  93                          fixarray len=3
+0001  01                            positive fixint 1
+0002  01                            positive fixint 1
+0003  01                            positive fixint 1
+0004  a2 61 62                    fixstr len=2 \"ab\"
+0007  d1 fe d4                    int16 -300
+000a  81                          fixmap len=1
+000b  c3                            true
+000c  ca 3f c0 00 00                float32 1.5
+0011  c4 0a 01 01 01 01 01 01 ..  bin8 len=10
+001d  d6 03 07 07 07 07           fixext4 type=3 len=4
+";
+    assert_eq!(expected, dump(&buf).to_string());
+    assert_eq!(
+        "[1, 1, 1], \"ab\", -300, {true: 1.5}, bin(01010101010101010101), ext(3, 07070707)",
+        format!("{:?}", dump(&buf))
+    );
+}
+
+#[test]
+fn errors_are_marked() {
+    let buf = [0x93, 0x01, 0xc1, 0x02];
+    assert_eq!(
+        "\
+0000  93                          fixarray len=3
+0001  01                            positive fixint 1
+0002  c1 02  <- error: unexpected format tag
+",
+        dump(&buf).to_string()
+    );
+    assert_eq!(
+        "[1, <error at 0x2: unexpected format tag>",
+        format!("{:#?}", dump(&buf))
+    );
+    assert_eq!(
+        "\
+0100  93                          fixarray len=3
+0101  01                            positive fixint 1
+0102  c1 02  <- error: unexpected format tag
+",
+        dump(&buf).with_base(0x100).to_string()
+    );
+    assert_eq!(
+        "[1, <error at 0x102: unexpected format tag>",
+        format!("{:?}", dump(&buf).with_base(0x100))
+    );
+
+    let buf = [0x92, 0x01];
+    assert_eq!(
+        "[1, <error at 0x2: unexpected end of input>",
+        format!("{:?}", dump(&buf))
+    );
+
+    let buf = [0x91; 100];
+    assert!(
+        format!("{:?}", dump(&buf)).ends_with("<error at 0x40: maximum nesting depth exceeded>")
+    );
+}
+
+#[test]
+fn long_strings_are_truncated() {
+    let s = "x".repeat(40);
+    assert_eq!(
+        format!("\"{}\"..", "x".repeat(32)),
+        format!("{:?}", dump(&s.pack_to_vec()))
+    );
+}
+
+proptest! {
+    #[test]
+    fn one_line_per_element(x: Vec<(String, Option<i64>)>) {
+        let buf = x.pack_to_vec();
+        let lines = dump(&buf).to_string().lines().count();
+        let some = x.iter().filter(|(_, v)| v.is_some()).count();
+        assert_eq!(1 + 2 * x.len() + some, lines);
+        assert!(!format!("{:?}", dump(&buf)).contains("error"));
+    }
+}
@@ -0,0 +1,143 @@
+use msgpacker::encode::*;
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+/// A pair packed as a two-element array, written with the encoder primitives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pair(u64, Option<String>);
+
+impl Packable for Pair {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        let n = write_array_len(buf, 2) + write_uint(buf, self.0);
+        match &self.1 {
+            Some(s) => {
+                let m = write_str_header(buf, s.len() as u32);
+                buf.extend(s.bytes());
+                n + m + s.len()
+            }
+            None => n + write_nil(buf),
+        }
+    }
+
+    fn packed_len(&self) -> usize {
+        1 + self.0.packed_len() + self.1.as_ref().map(|s| s.packed_len()).unwrap_or(1)
+    }
+}
+
+#[test]
+fn nil_and_bool() {
+    let mut buf = Vec::new();
+    assert_eq!(1, write_nil(&mut buf));
+    assert_eq!(2, write_bool(&mut buf, true) + write_bool(&mut buf, false));
+    assert_eq!(vec![Format::NIL, Format::TRUE, Format::FALSE], buf);
+}
+
+#[test]
+fn boundaries() {
+    let mut buf = Vec::new();
+    write_int(&mut buf, -32);
+    write_int(&mut buf, -33);
+    write_uint(&mut buf, 128);
+    write_array_len(&mut buf, 15);
+    write_array_len(&mut buf, 16);
+    write_map_len(&mut buf, 65536);
+    write_str_header(&mut buf, 31);
+    write_str_header(&mut buf, 32);
+    assert_eq!(
+        vec![
+            0xe0,
+            Format::INT8,
+            0xdf,
+            Format::UINT8,
+            0x80,
+            0x9f,
+            Format::ARRAY16,
+            0,
+            16,
+            Format::MAP32,
+            0,
+            1,
+            0,
+            0,
+            0xbf,
+            Format::STR8,
+            32
+        ],
+        buf
+    );
+}
+
+#[test]
+fn manual_framing() {
+    let pair = Pair(300, None);
+    assert_eq!(
+        vec![0x92, Format::UINT16, 0x01, 0x2c, Format::NIL],
+        pair.pack_to_vec()
+    );
+    assert_eq!(5, pair.packed_len());
+
+    let pair = Pair(1, Some("abc".to_string()));
+    let mut expected = vec![0x92, 0x01];
+    "abc".pack(&mut expected);
+    assert_eq!(expected, pair.pack_to_vec());
+}
+
+proptest! {
+    #[test]
+    fn uint_matches_pack(x: u64) {
+        let mut buf = Vec::new();
+        let n = write_uint(&mut buf, x);
+        assert_eq!(x.pack_to_vec(), buf);
+        assert_eq!(n, buf.len());
+    }
+
+    #[test]
+    fn int_matches_canonical(x: i64) {
+        let mut buf = Vec::new();
+        let n = write_int(&mut buf, x);
+        let mut canonical = Vec::new();
+        x.pack_canonical(&mut canonical);
+        assert_eq!(canonical, buf);
+        assert_eq!(n, buf.len());
+        assert_eq!(x, i64::unpack(&buf).unwrap().1);
+    }
+
+    #[test]
+    fn headers_match_pack(x: Vec<u8>, s: String, v: Vec<bool>, t: i8) {
+        let mut buf = Vec::new();
+        write_bin_header(&mut buf, x.len() as u32);
+        buf.extend(&x);
+        let mut bin = Vec::new();
+        msgpacker::pack_bin(&mut bin, &x);
+        assert_eq!(bin, buf);
+
+        let mut buf = Vec::new();
+        write_str_header(&mut buf, s.len() as u32);
+        buf.extend(s.bytes());
+        assert_eq!(s.pack_to_vec(), buf);
+
+        let mut buf = Vec::new();
+        write_array_len(&mut buf, v.len() as u32);
+        v.iter().for_each(|b| { write_bool(&mut buf, *b); });
+        let mut array = Vec::new();
+        msgpacker::pack_array(&mut array, &v);
+        assert_eq!(array, buf);
+
+        let mut buf = Vec::new();
+        write_ext_header(&mut buf, t, x.len() as u32);
+        buf.extend(&x);
+        assert_eq!(ExtensionRef { ext_type: t, data: &x }.pack_to_vec(), buf);
+    }
+
+    #[test]
+    fn map_header_matches_pack(x: BTreeMap<u8, u8>) {
+        let mut buf = Vec::new();
+        write_map_len(&mut buf, x.len() as u32);
+        x.iter().for_each(|(k, v)| { k.pack(&mut buf); v.pack(&mut buf); });
+        assert_eq!(x.pack_to_vec(), buf);
+    }
+}
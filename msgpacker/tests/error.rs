@@ -0,0 +1,121 @@
+use msgpacker::prelude::*;
+use msgpacker::{DetailedError, ErrorContext, Limit};
+use std::error::Error as _;
+use std::io;
+
+#[test]
+fn display_is_human_readable() {
+    assert_eq!("unexpected end of input", Error::BufferTooShort.to_string());
+    assert_eq!(
+        "missing field `price`",
+        Error::MissingField("price").to_string()
+    );
+    assert_eq!(
+        "integer out of range for the target type",
+        Error::IntegerOverflow.to_string()
+    );
+    assert_eq!(
+        "maximum nesting depth exceeded",
+        Error::LimitExceeded(Limit::Depth).to_string()
+    );
+    assert_eq!(
+        "trailing bytes: consumed 3 of 5",
+        Error::TrailingBytes {
+            consumed: 3,
+            total: 5
+        }
+        .to_string()
+    );
+    assert_eq!(
+        "extension type mismatch: expected 7, found -1",
+        Error::ExtensionTypeMismatch {
+            expected: 7,
+            found: -1
+        }
+        .to_string()
+    );
+    assert_eq!("bad checksum", Error::Custom("bad checksum").to_string());
+}
+
+#[test]
+fn io_errors_keep_the_source() {
+    let io = io::Error::new(io::ErrorKind::UnexpectedEof, "stream closed");
+    assert_eq!(
+        Error::Io(io::ErrorKind::UnexpectedEof),
+        Error::from(io::Error::from(io::ErrorKind::UnexpectedEof))
+    );
+
+    let e = DetailedError::from(io);
+    assert_eq!(Error::Io(io::ErrorKind::UnexpectedEof), e.kind());
+    assert_eq!("stream closed", e.source().unwrap().to_string());
+    assert!(Error::BufferTooShort.source().is_none());
+}
+
+#[test]
+fn custom_message() {
+    let e = DetailedError::custom(format_args!("signature of {} is invalid", "token"))
+        .with_offset(4)
+        .with_field("signature");
+    assert!(matches!(e.kind(), Error::Custom(_)));
+    assert_eq!(Some("signature of token is invalid"), e.message());
+    assert_eq!(
+        "signature of token is invalid at byte 4 in signature",
+        e.to_string()
+    );
+    assert!(e.source().is_none());
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Even(u32);
+
+impl Packable for Even {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        self.0.pack(buf)
+    }
+}
+
+impl Unpackable for Even {
+    type Error = DetailedError;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        match u32::unpack(buf)? {
+            (n, v) if v % 2 == 0 => Ok((n, Even(v))),
+            (_, v) => Err(DetailedError::custom(format_args!("{v} is odd"))),
+        }
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        match u32::unpack_iter(bytes)? {
+            (n, v) if v % 2 == 0 => Ok((n, Even(v))),
+            (_, v) => Err(DetailedError::custom(format_args!("{v} is odd"))),
+        }
+    }
+
+    fn error_context(error: Self::Error, context: msgpacker::Context) -> Self::Error {
+        error.with_context(context)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, MsgPacker)]
+#[msgpacker(detailed_errors)]
+struct Pairs {
+    id: u8,
+    values: Vec<Even>,
+}
+
+#[test]
+fn custom_message_from_nested_value() {
+    let bytes = (7u8, vec![2u32, 4, 5]).pack_to_vec();
+    let e = Pairs::unpack(&bytes).unwrap_err();
+    assert_eq!(Some("5 is odd"), e.message());
+    assert_eq!("5 is odd (tag 0x05) at byte 4 in values[2]", e.to_string());
+
+    let i = Pairs::unpack_iter(bytes).unwrap_err();
+    assert_eq!("5 is odd at byte 4 in values[2]", i.to_string());
+}
@@ -0,0 +1,40 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+
+#[test]
+fn trailing_bytes_are_rejected() {
+    let mut bytes = "token".pack_to_vec();
+    assert_eq!(Ok("token".to_string()), unpack_exact(&bytes));
+    bytes.push(0xc0);
+    assert_eq!(
+        Err(Error::TrailingBytes {
+            consumed: 6,
+            total: 7
+        }),
+        from_slice_exact::<String>(&bytes)
+    );
+}
+
+#[test]
+fn unpack_errors_are_kept() {
+    assert_eq!(Err(Error::BufferTooShort), u32::unpack_exact(&[]));
+}
+
+proptest! {
+    #[test]
+    fn exact_roundtrip(x: Vec<(u64, Option<String>)>, extra: Vec<u8>) {
+        let mut bytes = x.pack_to_vec();
+        let consumed = bytes.len();
+        prop_assert_eq!(&x, &Vec::<(u64, Option<String>)>::unpack_exact(&bytes).unwrap());
+        bytes.extend_from_slice(&extra);
+        let res = unpack_exact::<Vec<(u64, Option<String>)>>(&bytes);
+        if extra.is_empty() {
+            prop_assert_eq!(Ok(x), res);
+        } else {
+            prop_assert_eq!(
+                Err(Error::TrailingBytes { consumed, total: bytes.len() }),
+                res
+            );
+        }
+    }
+}
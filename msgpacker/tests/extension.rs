@@ -0,0 +1,176 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+
+mod utils;
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(ext = 7)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+#[msgpacker(ext = 8)]
+struct Label(String, Option<u8>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rgb([u8; 3]);
+
+impl ExtensionType for Rgb {
+    const EXT_TYPE: i8 = 9;
+
+    type Error = Error;
+
+    fn pack_payload<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        buf.extend(self.0);
+        3
+    }
+
+    fn payload_len(&self) -> usize {
+        3
+    }
+
+    fn unpack_payload(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        <[u8; 3]>::try_from(buf)
+            .map(|rgb| (3, Rgb(rgb)))
+            .map_err(|_| Error::InvalidExtension)
+    }
+
+    fn unpack_payload_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut bytes = bytes.into_iter();
+        let mut rgb = [0u8; 3];
+        for b in rgb.iter_mut() {
+            *b = bytes.next().ok_or(Error::BufferTooShort)?;
+        }
+        Ok((3, Rgb(rgb)))
+    }
+}
+
+impl Packable for Rgb {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        msgpacker::pack_ext(buf, self)
+    }
+}
+
+impl Unpackable for Rgb {
+    type Error = Error;
+
+    fn unpack(buf: &[u8]) -> Result<(usize, Self), Self::Error> {
+        msgpacker::unpack_ext(buf)
+    }
+
+    fn unpack_iter<I>(bytes: I) -> Result<(usize, Self), Self::Error>
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        msgpacker::unpack_ext_iter(bytes)
+    }
+}
+
+#[test]
+fn derived_payload_is_framed() {
+    let p = Point { x: 1, y: -1 };
+    let bytes = p.pack_to_vec();
+    assert_eq!(vec![0xd5, 7, 0x01, 0xff], bytes);
+    assert_eq!(
+        Extension::Ext(7, vec![0x01, 0xff]),
+        Extension::unpack(&bytes).unwrap().1
+    );
+    utils::case(p);
+}
+
+#[test]
+fn manual_implementation() {
+    let c = Rgb([0xff, 0x80, 0x00]);
+    let bytes = c.pack_to_vec();
+    assert_eq!(vec![0xc7, 3, 9, 0xff, 0x80, 0x00], bytes);
+    assert_eq!(bytes, Extension::Ext(9, c.0.to_vec()).pack_to_vec());
+    utils::case(c);
+}
+
+#[test]
+fn type_mismatch() {
+    let bytes = Rgb([1, 2, 3]).pack_to_vec();
+    let expected = Error::ExtensionTypeMismatch {
+        expected: 7,
+        found: 9,
+    };
+    assert_eq!(expected, Point::unpack(&bytes).unwrap_err());
+    assert_eq!(expected, Point::unpack_iter(bytes).unwrap_err());
+    assert_eq!(
+        Err(Error::InvalidExtension),
+        Rgb::unpack(&0u8.pack_to_vec())
+    );
+}
+
+#[test]
+fn payload_must_be_consumed() {
+    let bytes = Extension::Ext(7, vec![1, 2, 3]).pack_to_vec();
+    assert_eq!(Error::InvalidExtension, Point::unpack(&bytes).unwrap_err());
+    assert_eq!(
+        Error::InvalidExtension,
+        Point::unpack_iter(bytes.clone()).unwrap_err()
+    );
+    assert_eq!(
+        Error::BufferTooShort,
+        Point::unpack_iter(bytes[..4].to_vec()).unwrap_err()
+    );
+}
+
+#[test]
+fn borrowed_payload() {
+    let blob = vec![0xab; 300];
+    let bytes = Extension::Ext(42, blob.clone()).pack_to_vec();
+    assert_eq!(&[0xc8, 0x01, 0x2c, 42][..], &bytes[..4]);
+    let (n, ext) = ExtensionRef::unpack_borrowed(&bytes).unwrap();
+    assert_eq!(bytes.len(), n);
+    assert_eq!(42, ext.ext_type);
+    assert_eq!(blob, ext.data);
+    assert_eq!(bytes[4..].as_ptr(), ext.data.as_ptr());
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        ExtensionRef::unpack_borrowed(&bytes[..bytes.len() - 1])
+    );
+    assert_eq!(
+        Err(Error::InvalidExtension),
+        ExtensionRef::unpack_borrowed(&blob)
+    );
+}
+
+proptest! {
+    #[test]
+    fn extension_ref(ext_type: i8, data: Vec<u8>) {
+        let ext = ExtensionRef { ext_type, data: &data };
+        let bytes = ext.pack_to_vec();
+        prop_assert_eq!(bytes.len(), ext.packed_len());
+        prop_assert_eq!(&bytes, &Extension::Ext(ext_type, data.clone()).pack_to_vec());
+        prop_assert_eq!(Ok((bytes.len(), ext)), ExtensionRef::unpack_borrowed(&bytes));
+    }
+
+    #[test]
+    fn point(x: i32, y: i32) {
+        utils::case(Point { x, y });
+    }
+
+    #[test]
+    fn label(s: String, o: Option<u8>) {
+        let l = Label(s, o);
+        prop_assert_eq!(msgpacker::packed_len_ext(&l), l.packed_len());
+        utils::case(l);
+    }
+
+    #[test]
+    fn rgb(c: [u8; 3]) {
+        utils::case(Rgb(c));
+    }
+}
@@ -1,7 +1,50 @@
+use msgpacker::prelude::*;
+use msgpacker::Lossy;
 use proptest::prelude::*;
 
 mod utils;
 
+#[test]
+fn integers_as_floats() {
+    assert_eq!(Ok((1, 1.0)), f32::unpack(&[0x01]));
+    assert_eq!(Ok((1, -3.0)), f64::unpack(&[0xfd]));
+    assert_eq!(Ok((3, 300.0)), f32::unpack(&300u16.pack_to_vec()));
+    assert_eq!(
+        Ok((9, -(1i64 << 40) as f64)),
+        f64::unpack(&(-(1i64 << 40)).pack_to_vec())
+    );
+    assert_eq!(Ok((1, 1.0)), f32::unpack_iter([0x01]));
+}
+
+#[test]
+fn inexact_values_are_rejected() {
+    // 2^24 + 1 isn't representable as f32
+    let bytes = ((1u32 << 24) + 1).pack_to_vec();
+    assert_eq!(Err(Error::PrecisionLoss), f32::unpack(&bytes));
+    assert_eq!(
+        Ok((5, Lossy(((1u32 << 24) + 1) as f32))),
+        Lossy::<f32>::unpack(&bytes)
+    );
+
+    let bytes = u64::MAX.pack_to_vec();
+    assert_eq!(Err(Error::PrecisionLoss), f64::unpack(&bytes));
+    assert_eq!(Err(Error::PrecisionLoss), f64::unpack_iter(bytes.clone()));
+    assert_eq!(
+        Ok((9, Lossy(u64::MAX as f64))),
+        Lossy::<f64>::unpack(&bytes)
+    );
+
+    let bytes = 0.1f64.pack_to_vec();
+    assert_eq!(Err(Error::PrecisionLoss), f32::unpack(&bytes));
+    assert_eq!(Ok((9, Lossy(0.1f32))), Lossy::<f32>::unpack_iter(bytes));
+}
+
+#[test]
+fn non_numbers_are_rejected() {
+    assert_eq!(Err(Error::UnexpectedFormatTag), f32::unpack(&[0xc0]));
+    assert_eq!(Err(Error::BufferTooShort), f64::unpack(&[0xcb, 0x00]));
+}
+
 proptest! {
     #[test]
     fn f32(x: f32) {
@@ -12,4 +55,23 @@ proptest! {
     fn f64(x: f64) {
         utils::case(x);
     }
+
+    #[test]
+    fn lossy(x: f64) {
+        utils::case(Lossy(x));
+        utils::case(Lossy(x as f32));
+    }
+
+    #[test]
+    fn f64_narrowing(x: f32) {
+        let bytes = (x as f64).pack_to_vec();
+        prop_assert_eq!(Ok((9, x)), f32::unpack(&bytes));
+    }
+
+    #[test]
+    fn int_widening(x: i32) {
+        let bytes = x.pack_to_vec();
+        prop_assert_eq!(x as f64, f64::unpack(&bytes).unwrap().1);
+        prop_assert_eq!(x as f32, *Lossy::<f32>::unpack(&bytes).unwrap().1);
+    }
 }
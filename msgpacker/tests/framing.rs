@@ -0,0 +1,185 @@
+use msgpacker::framing::{FramedReader, FramedWriter, LengthPrefix};
+use msgpacker::prelude::*;
+use msgpacker::Format;
+use proptest::prelude::*;
+use std::io::{self, Cursor};
+
+fn prefix() -> impl Strategy<Value = LengthPrefix> {
+    prop_oneof![Just(LengthPrefix::U32Be), Just(LengthPrefix::Varint)]
+}
+
+#[test]
+fn u32_header_is_big_endian() {
+    let mut writer = FramedWriter::new(Vec::new());
+    assert_eq!(Ok(7), writer.write("ab"));
+    assert_eq!(vec![0, 0, 0, 3, 0xa2, b'a', b'b'], writer.into_inner());
+}
+
+#[test]
+fn varint_header() {
+    for (len, header) in [
+        (0usize, vec![0x00]),
+        (0x7f, vec![0x7f]),
+        (0x80, vec![0x80, 0x01]),
+        (300, vec![0xac, 0x02]),
+        (0x4000, vec![0x80, 0x80, 0x01]),
+    ] {
+        let mut buf = Vec::new();
+        assert_eq!(header.len(), LengthPrefix::Varint.write(&mut buf, len));
+        assert_eq!(header, buf);
+        assert_eq!(
+            Ok(Some(len)),
+            LengthPrefix::Varint.read(&mut Cursor::new(buf))
+        );
+    }
+}
+
+#[test]
+fn varint_overflow() {
+    let mut bytes = vec![0xff; 10];
+    bytes.push(0x01);
+    assert_eq!(
+        Err(Error::IntegerOverflow),
+        LengthPrefix::Varint.read(&mut Cursor::new(bytes))
+    );
+}
+
+#[test]
+fn empty_stream_has_no_frames() {
+    let mut reader = FramedReader::new(Cursor::new(Vec::new()));
+    assert_eq!(Ok(None), reader.read::<u64>());
+}
+
+#[test]
+fn truncated_frame() {
+    let mut bytes = Vec::new();
+    FramedWriter::new(&mut bytes).write("hello").unwrap();
+    bytes.truncate(bytes.len() - 1);
+    let mut reader = FramedReader::new(Cursor::new(bytes));
+    assert_eq!(
+        Err(Error::Io(io::ErrorKind::UnexpectedEof)),
+        reader.read::<String>()
+    );
+
+    let mut reader = FramedReader::new(Cursor::new(vec![0, 0]));
+    assert_eq!(
+        Err(Error::Io(io::ErrorKind::UnexpectedEof)),
+        reader.read::<String>()
+    );
+}
+
+#[test]
+fn writer_rejects_large_frames() {
+    let mut writer = FramedWriter::new(Vec::new()).with_max_frame_len(4);
+    assert_eq!(
+        Err(Error::FrameTooLarge { len: 6, max: 4 }),
+        writer.write("hello")
+    );
+    assert_eq!(
+        Err(Error::FrameTooLarge { len: 5, max: 4 }),
+        writer.write_frame(&[0; 5])
+    );
+    assert!(writer.get_ref().is_empty());
+    assert_eq!(Ok(8), writer.write("abc"));
+}
+
+/// Packs the inner value but reports a wrong packed length.
+struct WrongLen(&'static str);
+
+impl Packable for WrongLen {
+    fn pack<T>(&self, buf: &mut T) -> usize
+    where
+        T: Extend<u8>,
+    {
+        self.0.pack(buf)
+    }
+
+    fn packed_len(&self) -> usize {
+        1
+    }
+}
+
+#[test]
+fn writer_uses_the_packed_length() {
+    for prefix in [LengthPrefix::U32Be, LengthPrefix::Varint] {
+        let mut writer = FramedWriter::new(Vec::new())
+            .with_prefix(prefix)
+            .with_max_frame_len(4);
+        assert_eq!(
+            Err(Error::FrameTooLarge { len: 6, max: 4 }),
+            writer.write(&WrongLen("hello"))
+        );
+        assert!(writer.get_ref().is_empty());
+        writer.write(&WrongLen("abc")).unwrap();
+        writer.write(&WrongLen("")).unwrap();
+
+        let mut reader = FramedReader::new(Cursor::new(writer.into_inner())).with_prefix(prefix);
+        assert_eq!(Ok(Some("abc".to_string())), reader.read());
+        assert_eq!(Ok(Some(String::new())), reader.read());
+        assert_eq!(Ok(None), reader.read::<String>());
+    }
+}
+
+#[test]
+fn reader_skips_large_frames() {
+    let mut writer = FramedWriter::new(Vec::new());
+    writer.write(&1u8).unwrap();
+    writer.write(&vec![7u8; 100]).unwrap();
+    writer.write(&2u8).unwrap();
+
+    let mut reader = FramedReader::new(Cursor::new(writer.into_inner())).with_max_frame_len(16);
+    assert_eq!(Ok(Some(1)), reader.read::<u8>());
+    assert_eq!(
+        Err(Error::FrameTooLarge { len: 103, max: 16 }),
+        reader.read::<Vec<u8>>()
+    );
+    assert_eq!(Ok(Some(2)), reader.read::<u8>());
+    assert_eq!(Ok(None), reader.read::<u8>());
+}
+
+#[test]
+fn reader_recovers_from_corrupt_frames() {
+    let mut writer = FramedWriter::new(Vec::new()).with_prefix(LengthPrefix::Varint);
+    writer.write("first").unwrap();
+    writer.write_frame(&[Format::STR8, 0xff, b'x']).unwrap();
+    writer.write_frame(&[Format::UINT8, 1, 2]).unwrap();
+    writer.write("last").unwrap();
+
+    let mut reader =
+        FramedReader::new(Cursor::new(writer.into_inner())).with_prefix(LengthPrefix::Varint);
+    assert_eq!(Ok(Some("first".to_string())), reader.read());
+    assert_eq!(Err(Error::BufferTooShort), reader.read::<String>());
+    assert_eq!(
+        Err(Error::TrailingBytes {
+            consumed: 2,
+            total: 3
+        }),
+        reader.read::<u8>()
+    );
+    assert_eq!(Ok(Some("last".to_string())), reader.read());
+    assert_eq!(Ok(None), reader.read::<String>());
+}
+
+proptest! {
+    #[test]
+    fn frames(prefix in prefix(), values: Vec<Option<String>>) {
+        let mut writer = FramedWriter::new(Vec::new()).with_prefix(prefix);
+        for value in &values {
+            writer.write(value).unwrap();
+        }
+
+        let mut reader = FramedReader::new(Cursor::new(writer.into_inner())).with_prefix(prefix);
+        for value in values {
+            assert_eq!(Ok(Some(value)), reader.read());
+        }
+        assert_eq!(Ok(None), reader.read::<Option<String>>());
+    }
+
+    #[test]
+    fn headers(prefix in prefix(), len in 0..=u32::MAX as usize) {
+        let mut buf = Vec::new();
+        let n = prefix.write(&mut buf, len);
+        assert_eq!(n, buf.len());
+        assert_eq!(Ok(Some(len)), prefix.read(&mut Cursor::new(buf)));
+    }
+}
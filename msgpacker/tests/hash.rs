@@ -0,0 +1,35 @@
+use msgpacker::prelude::*;
+use msgpacker::{HashSink, HasherSink};
+use proptest::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+#[test]
+fn large_values_are_chunked() {
+    let value = vec![0xabu8; 1000];
+    let bytes = value.pack_to_vec();
+
+    let mut sink = HashSink(Sha256::new());
+    value.pack(&mut sink);
+    assert_eq!(Sha256::digest(&bytes), sink.into_inner().finalize());
+
+    let mut sink = HasherSink(crc32fast::Hasher::new());
+    value.pack(&mut sink);
+    assert_eq!(crc32fast::hash(&bytes), sink.into_inner().finalize());
+}
+
+proptest! {
+    #[test]
+    fn sha256_matches_vec(x: BTreeMap<String, Vec<u64>>) {
+        let mut sink = HashSink(Sha256::new());
+        x.pack(&mut sink);
+        assert_eq!(Sha256::digest(x.pack_to_vec()), sink.0.finalize());
+    }
+
+    #[test]
+    fn crc32_matches_vec(x: (String, Option<i64>, Vec<u8>)) {
+        let mut sink = HasherSink(crc32fast::Hasher::new());
+        x.pack(&mut sink);
+        assert_eq!(crc32fast::hash(&x.pack_to_vec()), sink.0.finalize());
+    }
+}
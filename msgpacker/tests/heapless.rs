@@ -0,0 +1,91 @@
+use heapless::{FnvIndexMap, String, Vec};
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+
+mod utils;
+
+#[test]
+fn vec_capacity_exceeded() {
+    let bytes = vec![1u32, 2, 3].pack_to_vec();
+    let (n, v) = Vec::<u32, 3>::unpack(&bytes).unwrap();
+    assert_eq!(bytes.len(), n);
+    assert_eq!(&[1, 2, 3], v.as_slice());
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        Vec::<u32, 2>::unpack(&bytes).map(|(_, v)| v)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        Vec::<u32, 2>::unpack_iter(bytes).map(|(_, v)| v)
+    );
+}
+
+#[test]
+fn string_capacity_exceeded() {
+    let bytes = "hello".pack_to_vec();
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        String::<4>::unpack(&bytes).map(|(_, s)| s)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        String::<4>::unpack_iter(bytes).map(|(_, s)| s)
+    );
+}
+
+#[test]
+fn map_capacity_exceeded() {
+    let map: std::collections::BTreeMap<u8, bool> = (0..5).map(|i| (i, i % 2 == 0)).collect();
+    let bytes = map.pack_to_vec();
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        FnvIndexMap::<u8, bool, 4>::unpack(&bytes).map(|(_, m)| m)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        FnvIndexMap::<u8, bool, 4>::unpack_iter(bytes).map(|(_, m)| m)
+    );
+}
+
+#[test]
+fn capacity_checked_before_elements() {
+    // the headers announce more elements than fit, followed by no elements at all
+    let array = [0xdc, 0xff, 0xff];
+    let map = [0x85];
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        Vec::<u32, 2>::unpack(&array).map(|(_, v)| v)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        Vec::<u32, 2>::unpack_iter(array).map(|(_, v)| v)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        FnvIndexMap::<u8, bool, 4>::unpack(&map).map(|(_, m)| m)
+    );
+    assert_eq!(
+        Err(Error::CapacityExceeded),
+        FnvIndexMap::<u8, bool, 4>::unpack_iter(map).map(|(_, m)| m)
+    );
+}
+
+proptest! {
+    #[test]
+    fn vec(v in prop::collection::vec(any::<u64>(), 0..=16)) {
+        utils::case(Vec::<u64, 16>::from_slice(&v).unwrap());
+    }
+
+    #[test]
+    fn string(s in "\\PC{0,16}") {
+        let mut x = String::<64>::new();
+        x.push_str(&s).unwrap();
+        utils::case(x);
+    }
+
+    #[test]
+    fn map(m in prop::collection::btree_map(any::<i32>(), any::<std::string::String>(), 0..=16)) {
+        let x: FnvIndexMap<i32, std::string::String, 16> = m.into_iter().collect();
+        utils::case(x);
+    }
+}
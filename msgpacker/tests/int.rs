@@ -1,7 +1,38 @@
+use msgpacker::prelude::*;
 use proptest::prelude::*;
 
 mod utils;
 
+fn cross<T>(bytes: &[u8], expected: i128)
+where
+    T: Unpackable<Error = Error> + TryFrom<i128> + PartialEq + core::fmt::Debug,
+{
+    let expected = T::try_from(expected).map_err(|_| Error::IntegerOverflow);
+    assert_eq!(expected, T::unpack(bytes).map(|(_, v)| v));
+    assert_eq!(expected, T::unpack_iter(bytes.to_vec()).map(|(_, v)| v));
+}
+
+#[test]
+fn uint_tags_for_signed() {
+    // uint8 200, uint16 300, uint32 70000, uint64 2^40
+    cross::<i16>(&[0xcc, 200], 200);
+    cross::<i16>(&[0xcd, 0x01, 0x2c], 300);
+    cross::<i32>(&[0xce, 0x00, 0x01, 0x11, 0x70], 70000);
+    cross::<i64>(&[0xcf, 0, 0, 1, 0, 0, 0, 0, 0], 1 << 40);
+    cross::<i8>(&[0xcc, 200], 200);
+}
+
+#[test]
+fn wider_tags_that_fit() {
+    cross::<u16>(&[0xce, 0x00, 0x00, 0x01, 0x2c], 300);
+    cross::<u8>(&[0xd1, 0x00, 0x7f], 127);
+    cross::<u8>(&[0xe0], -32);
+    cross::<usize>(
+        &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        u64::MAX as i128,
+    );
+}
+
 proptest! {
     #[test]
     fn u8(x: u8) {
@@ -62,4 +93,28 @@ proptest! {
     fn isize(x: isize) {
         utils::case(x);
     }
+
+    #[test]
+    fn i64_into_any(x: i64) {
+        let bytes = x.pack_to_vec();
+        cross::<u8>(&bytes, x as i128);
+        cross::<u16>(&bytes, x as i128);
+        cross::<u32>(&bytes, x as i128);
+        cross::<u64>(&bytes, x as i128);
+        cross::<i8>(&bytes, x as i128);
+        cross::<i16>(&bytes, x as i128);
+        cross::<i32>(&bytes, x as i128);
+        cross::<i128>(&bytes, x as i128);
+    }
+
+    #[test]
+    fn u64_into_any(x: u64) {
+        let bytes = x.pack_to_vec();
+        cross::<u8>(&bytes, x as i128);
+        cross::<u32>(&bytes, x as i128);
+        cross::<i8>(&bytes, x as i128);
+        cross::<i32>(&bytes, x as i128);
+        cross::<i64>(&bytes, x as i128);
+        cross::<u128>(&bytes, x as i128);
+    }
 }
@@ -0,0 +1,188 @@
+use msgpacker::encode::{write_array_len, write_map_len, write_nil};
+use msgpacker::json::{BinFormat, JsonOptions};
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+mod utils;
+
+fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        ".*".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::from),
+            prop::collection::btree_map(".*", inner, 0..8)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+#[test]
+fn integers_are_exact() {
+    let buf = (u64::MAX, i64::MIN, -1i8, 0u8).pack_to_vec();
+    let mut n = 0;
+    let mut values = Vec::new();
+    for _ in 0..4 {
+        let (count, v) = Value::unpack(&buf[n..]).unwrap();
+        n += count;
+        values.push(v);
+    }
+    assert_eq!(json!([u64::MAX, i64::MIN, -1, 0]), Value::from(values));
+    assert_eq!(buf.len(), n);
+}
+
+#[test]
+fn floats() {
+    assert_eq!(json!(1.5), Value::unpack(&1.5f32.pack_to_vec()).unwrap().1);
+    assert_eq!(
+        Value::Null,
+        Value::unpack(&f64::NAN.pack_to_vec()).unwrap().1
+    );
+    assert_eq!(
+        Value::Null,
+        Value::unpack(&f32::INFINITY.pack_to_vec()).unwrap().1
+    );
+    assert_eq!(0.25f64.pack_to_vec(), json!(0.25).pack_to_vec());
+}
+
+#[test]
+fn bins() {
+    let mut buf = Vec::new();
+    msgpacker::pack_bin(&mut buf, b"foob");
+    assert_eq!(json!("Zm9vYg=="), Value::unpack(&buf).unwrap().1);
+    let array = JsonOptions::new().with_bin(BinFormat::Array);
+    assert_eq!(json!([102, 111, 111, 98]), array.to_json(&buf).unwrap().1);
+
+    for (bytes, encoded) in [
+        (&b""[..], ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"\xff\xfe\xfd\xfc", "//79/A=="),
+    ] {
+        let mut buf = Vec::new();
+        msgpacker::pack_bin(&mut buf, bytes);
+        assert_eq!(json!(encoded), Value::unpack(&buf).unwrap().1);
+    }
+}
+
+#[test]
+fn extensions_are_tagged() {
+    let ext = ExtensionRef {
+        ext_type: -3,
+        data: &[1, 2, 3],
+    }
+    .pack_to_vec();
+    let value = Value::unpack(&ext).unwrap().1;
+    assert_eq!(json!({"$ext": -3, "data": "AQID"}), value);
+    assert_eq!(ext, value.pack_to_vec());
+    assert_eq!(ext, json!({"$ext": -3, "data": [1, 2, 3]}).pack_to_vec());
+
+    // not the tagged shape, packed as maps
+    for value in [
+        json!({"$ext": -3, "data": "AQID", "x": 1}),
+        json!({"$ext": 300, "data": "AQID"}),
+        json!({"$ext": -3, "data": "AQI"}),
+        json!({"$ext": -3, "data": [256]}),
+    ] {
+        assert_eq!(
+            Some(&0x80),
+            value.pack_to_vec().first().map(|b| b & 0xf0).as_ref()
+        );
+    }
+}
+
+#[test]
+fn keys_are_stringified() {
+    let mut map = BTreeMap::new();
+    map.insert(1u8, "a");
+    map.insert(200, "b");
+    let mut buf = map.pack_to_vec();
+    msgpacker::pack_map(&mut buf, [(vec![1u8, 2], true)]);
+    write_map_len(&mut buf, 1);
+    write_nil(&mut buf);
+    write_nil(&mut buf);
+
+    let mut values = Vec::new();
+    let mut n = 0;
+    while n < buf.len() {
+        let (count, value) = Value::unpack(&buf[n..]).unwrap();
+        n += count;
+        values.push(value);
+    }
+    assert_eq!(
+        vec![
+            json!({"1": "a", "200": "b"}),
+            json!({"[1,2]": true}),
+            json!({"null": null})
+        ],
+        values
+    );
+}
+
+#[test]
+fn malformed() {
+    assert_eq!(Err(Error::UnexpectedFormatTag), Value::unpack(&[0xc1]));
+    assert_eq!(Err(Error::InvalidUtf8), Value::unpack(&[0xa1, 0xff]));
+    assert_eq!(Err(Error::BufferTooShort), Value::unpack(&[0x92, 0x01]));
+    assert_eq!(
+        Err(Error::LimitExceeded(msgpacker::Limit::Depth)),
+        Value::unpack(&[0x91; 100])
+    );
+}
+
+proptest! {
+    #[test]
+    fn json_round_trip(x in arb_json()) {
+        utils::case(x);
+    }
+
+    #[test]
+    fn packed_round_trip(x: Vec<Vec<i64>>, y: Vec<Option<String>>, t: i8, data: Vec<u8>) {
+        let mut buf = Vec::new();
+        x.pack_canonical(&mut buf);
+        write_array_len(&mut buf, y.len() as u32);
+        for s in &y {
+            match s {
+                Some(s) => s.pack(&mut buf),
+                None => write_nil(&mut buf),
+            };
+        }
+        ExtensionRef { ext_type: t, data: &data }.pack(&mut buf);
+
+        let mut packed = Vec::new();
+        let mut n = 0;
+        while n < buf.len() {
+            let (count, value) = Value::unpack(&buf[n..]).unwrap();
+            let (count_iter, value_iter) = Value::unpack_iter(buf[n..].iter().copied()).unwrap();
+            assert_eq!(count, count_iter);
+            assert_eq!(value, value_iter);
+            n += count;
+            value.pack(&mut packed);
+        }
+        assert_eq!(buf, packed);
+    }
+
+    #[test]
+    fn bin_formats(x: Vec<u8>) {
+        let mut buf = Vec::new();
+        msgpacker::pack_bin(&mut buf, &x);
+        let base64 = Value::unpack(&buf).unwrap().1;
+        let array = JsonOptions::new().with_bin(BinFormat::Array).to_json(&buf).unwrap().1;
+        assert_eq!(Value::from(x.as_slice()), array);
+        let decoded = json!({"$ext": 1, "data": base64}).pack_to_vec();
+        let mut expected = Vec::new();
+        ExtensionRef { ext_type: 1, data: &x }.pack(&mut expected);
+        assert_eq!(expected, decoded);
+    }
+}
@@ -0,0 +1,227 @@
+use msgpacker::prelude::*;
+use msgpacker::{Limit, UnpackLimits};
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn huge_array_header_is_rejected() {
+    let bytes = [0xdd, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        Vec::<u64>::unpack(&bytes).map(|(_, v)| v)
+    );
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        UnpackLimits::UNLIMITED.validate(&bytes)
+    );
+}
+
+#[test]
+fn huge_map_header_is_rejected() {
+    let bytes = [0xdf, 0xff, 0xff, 0xff, 0xff, 0x01, 0x02];
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        BTreeMap::<u8, u8>::unpack(&bytes).map(|(_, v)| v)
+    );
+}
+
+#[test]
+fn depth_limit() {
+    let value = vec![vec![vec![1u8]]];
+    let bytes = value.pack_to_vec();
+    let limits = UnpackLimits {
+        max_depth: 2,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::Depth)),
+        limits.validate(&bytes)
+    );
+    let limits = UnpackLimits {
+        max_depth: 3,
+        ..Default::default()
+    };
+    assert_eq!(Ok((bytes.len(), value)), limits.unpack(&bytes));
+}
+
+#[test]
+fn container_len_limit() {
+    let bytes = vec![0u8; 17].pack_to_vec();
+    let limits = UnpackLimits {
+        max_container_len: 16,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::ContainerLen)),
+        limits.unpack::<Vec<u8>>(&bytes).map(|(_, v)| v)
+    );
+}
+
+#[test]
+fn bytes_len_limit() {
+    let bytes = "a".repeat(33).pack_to_vec();
+    let limits = UnpackLimits {
+        max_bytes_len: 32,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::BytesLen)),
+        limits.validate(&bytes)
+    );
+}
+
+#[test]
+fn alloc_limit() {
+    let bytes = vec!["a".repeat(10); 4].pack_to_vec();
+    let limits = UnpackLimits {
+        max_alloc: 43,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::Alloc)),
+        limits.validate(&bytes)
+    );
+    let limits = UnpackLimits {
+        max_alloc: 44,
+        ..Default::default()
+    };
+    assert_eq!(Ok(()), limits.validate(&bytes));
+}
+
+#[derive(Debug, PartialEq, MsgPacker)]
+struct Order {
+    id: u64,
+    note: Option<String>,
+    items: Vec<(u32, Vec<u16>)>,
+}
+
+fn unpacked<T>(result: Result<(usize, T), T::Error>) -> Result<T, Error>
+where
+    T: Unpackable,
+    Error: From<T::Error>,
+{
+    result.map(|(_, v)| v).map_err(Error::from)
+}
+
+fn order() -> Order {
+    Order {
+        id: 7,
+        note: Some("urgent".into()),
+        items: vec![(1, vec![10, 20]), (2, vec![30])],
+    }
+}
+
+#[test]
+fn limits_follow_the_derived_layout() {
+    let value = order();
+    let bytes = value.pack_to_vec();
+
+    // the struct, its tuples and its option add no container, so `items` is at depth 0
+    let limits = UnpackLimits {
+        max_depth: 2,
+        max_container_len: 2,
+        ..Default::default()
+    };
+    assert_eq!(Ok((bytes.len(), order())), limits.unpack(&bytes));
+    assert_eq!(
+        Ok((bytes.len(), order())),
+        limits.unpack_iter(bytes.iter().copied())
+    );
+
+    let limits = UnpackLimits {
+        max_depth: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::Depth)),
+        unpacked(limits.unpack::<Order>(&bytes))
+    );
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::Depth)),
+        unpacked(limits.unpack_iter::<Order, _>(bytes.iter().copied()))
+    );
+
+    let limits = UnpackLimits {
+        max_container_len: 1,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::ContainerLen)),
+        unpacked(limits.unpack::<Order>(&bytes))
+    );
+
+    let limits = UnpackLimits {
+        max_bytes_len: 5,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::BytesLen)),
+        unpacked(limits.unpack_iter::<Order, _>(bytes.iter().copied()))
+    );
+
+    // 6 bytes of note, and 2 + 2 + 1 container entries
+    let limits = UnpackLimits {
+        max_alloc: 10,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(Error::LimitExceeded(Limit::Alloc)),
+        unpacked(limits.unpack::<Order>(&bytes))
+    );
+    let limits = UnpackLimits {
+        max_alloc: 11,
+        ..Default::default()
+    };
+    assert_eq!(Ok((bytes.len(), order())), limits.unpack(&bytes));
+}
+
+#[test]
+fn validate_walks_raw_msgpack() {
+    // zero-sized elements are packed as nothing, which validate can't tell from a short buffer
+    let bytes = vec![(); 4].pack_to_vec();
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        UnpackLimits::default().validate(&bytes)
+    );
+    assert_eq!(
+        Ok((bytes.len(), vec![(); 4])),
+        UnpackLimits::default().unpack(&bytes)
+    );
+}
+
+#[test]
+fn limits_are_scoped() {
+    let bytes = vec![1u8, 2, 3].pack_to_vec();
+    let limits = UnpackLimits {
+        max_container_len: 2,
+        ..Default::default()
+    };
+    assert!(limits.unpack::<Vec<u8>>(&bytes).is_err());
+    assert_eq!(Ok((bytes.len(), vec![1, 2, 3])), Vec::<u8>::unpack(&bytes));
+}
+
+#[test]
+fn truncated_input() {
+    let bytes = "foo".pack_to_vec();
+    assert_eq!(
+        Err(Error::BufferTooShort),
+        UnpackLimits::default().validate(&bytes[..bytes.len() - 1])
+    );
+    assert_eq!(
+        Err(Error::UnexpectedFormatTag),
+        UnpackLimits::default().validate(&[0xc1])
+    );
+}
+
+proptest! {
+    #[test]
+    fn default_limits_accept_packed(x: Vec<(String, Option<i64>, BTreeMap<u16, Vec<u8>>, f64)>) {
+        let bytes = x.pack_to_vec();
+        prop_assert_eq!(Ok(()), UnpackLimits::default().validate(&bytes));
+    }
+
+    #[test]
+    fn validate_never_panics(bytes: Vec<u8>) {
+        let _ = UnpackLimits::default().validate(&bytes);
+    }
+}
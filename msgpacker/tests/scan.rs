@@ -0,0 +1,97 @@
+use msgpacker::prelude::*;
+use msgpacker::{ElementHeader, ElementKind, ElementLen, Format, ValueScanner};
+use proptest::prelude::*;
+
+/// Feeds the stream in chunks of `size` bytes, returning the lengths of the found values.
+fn scan(buf: &[u8], size: usize) -> Vec<usize> {
+    let mut scanner = ValueScanner::new();
+    let mut values = Vec::new();
+    let mut start = 0;
+    for (i, chunk) in buf.chunks(size).enumerate() {
+        let mut chunk = chunk;
+        let mut offset = i * size;
+        while let Some(n) = scanner.feed(chunk).unwrap() {
+            values.push(offset + n - start);
+            start = offset + n;
+            offset += n;
+            chunk = &chunk[n..];
+        }
+    }
+    assert!(scanner.is_empty());
+    values
+}
+
+#[test]
+fn headers() {
+    assert_eq!(
+        Ok(ElementHeader {
+            kind: ElementKind::Scalar,
+            len: ElementLen::Fixed(0)
+        }),
+        ElementHeader::from_tag(0xff)
+    );
+    assert_eq!(
+        Ok(ElementHeader {
+            kind: ElementKind::Bytes,
+            len: ElementLen::Fixed(3)
+        }),
+        ElementHeader::from_tag(0xa3)
+    );
+    assert_eq!(
+        Ok(ElementHeader {
+            kind: ElementKind::Map,
+            len: ElementLen::Prefixed(2)
+        }),
+        ElementHeader::from_tag(Format::MAP16)
+    );
+    assert_eq!(
+        Err(Error::UnexpectedFormatTag),
+        ElementHeader::from_tag(0xc1)
+    );
+    assert_eq!((5, 0), ElementKind::Ext.layout(4));
+    assert_eq!((0, 6), ElementKind::Map.layout(3));
+}
+
+#[test]
+fn partial_values() {
+    let mut scanner = ValueScanner::new();
+    assert_eq!(Ok(None), scanner.feed(&[]));
+    assert_eq!(Ok(None), scanner.feed(&[Format::ARRAY32, 0xff, 0xff]));
+    assert_eq!(Ok(None), scanner.feed(&[0xff, 0xff]));
+    assert_eq!(5, scanner.len());
+
+    scanner.reset();
+    assert_eq!(Ok(None), scanner.feed(&[0x92, Format::STR8, 2, b'a']));
+    assert_eq!(Ok(Some(2)), scanner.feed(&[b'b', 0x01, 0x07]));
+    assert!(scanner.is_empty());
+    assert_eq!(Ok(Some(1)), scanner.feed(&[0x07]));
+}
+
+#[test]
+fn errors_report_the_offset() {
+    let mut scanner = ValueScanner::new();
+    assert_eq!(Ok(None), scanner.feed(&[0x93, 0x01]));
+    assert_eq!(Err(Error::UnexpectedFormatTag), scanner.feed(&[0x02, 0xc1]));
+    assert_eq!(3, scanner.len());
+}
+
+proptest! {
+    #[test]
+    fn values_split_in_chunks(
+        values: Vec<(Vec<i32>, String, i64, Option<(i8, [u8; 4])>)>,
+        size in 1..32usize,
+    ) {
+        let mut buf = Vec::new();
+        let mut lens = Vec::new();
+        for (array, bin, int, ext) in values {
+            lens.push(array.pack(&mut buf));
+            lens.push(msgpacker::pack_bin(&mut buf, bin.as_bytes()));
+            lens.push(int.pack(&mut buf));
+            if let Some((ext_type, data)) = ext {
+                lens.push(ExtensionRef { ext_type, data: &data }.pack(&mut buf));
+            }
+        }
+        prop_assert_eq!(&lens, &scan(&buf, size));
+        prop_assert_eq!(lens, scan(&buf, buf.len().max(1)));
+    }
+}
@@ -0,0 +1,42 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+
+#[test]
+fn overflow_leaves_remainder_untouched() {
+    let value = ("foo".to_string(), 1024u32, true);
+    let bytes = value.pack_to_vec();
+    let mut buf = [0xffu8; 16];
+    let res = msgpacker::pack_to_slice(&value, &mut buf[..bytes.len() - 1]);
+    assert_eq!(Err(Error::BufferOverflow), res);
+    assert_eq!(bytes[..bytes.len() - 1], buf[..bytes.len() - 1]);
+    assert!(buf[bytes.len() - 1..].iter().all(|b| *b == 0xff));
+}
+
+#[test]
+fn writer_discards_after_overflow() {
+    let mut buf = [0u8; 2];
+    let mut writer = msgpacker::SliceWriter::new(&mut buf);
+    writer.extend([1, 2, 3]);
+    writer.extend([4]);
+    assert!(writer.is_overflowed());
+    assert_eq!(2, writer.position());
+    assert_eq!(Err(Error::BufferOverflow), writer.finish());
+    assert_eq!([1, 2], buf);
+}
+
+proptest! {
+    #[test]
+    fn slice_matches_vec(x: Vec<String>, extra in 0usize..8) {
+        let bytes = x.pack_to_vec();
+        let mut buf = vec![0u8; bytes.len() + extra];
+        let n = msgpacker::pack_to_slice(&x, &mut buf).unwrap();
+        assert_eq!(bytes.len(), n);
+        assert_eq!(bytes, buf[..n]);
+        if n > 0 {
+            assert_eq!(
+                Err(Error::BufferOverflow),
+                msgpacker::pack_to_slice(&x, &mut buf[..n - 1])
+            );
+        }
+    }
+}
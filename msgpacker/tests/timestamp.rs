@@ -0,0 +1,118 @@
+use msgpacker::prelude::*;
+use proptest::prelude::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod utils;
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct Event {
+    id: u32,
+    #[msgpacker(timestamp)]
+    at: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct RawEvent {
+    id: u32,
+    at: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, MsgPacker)]
+struct Tick(#[msgpacker(timestamp)] SystemTime);
+
+#[test]
+fn smallest_form() {
+    let t = |secs, nanos| Timestamp::new(secs, nanos).unwrap().pack_to_vec();
+    assert_eq!(vec![0xd6, 0xff, 0, 0, 0, 1], t(1, 0));
+    assert_eq!(vec![0xd7, 0xff, 0, 0, 0, 4, 0, 0, 0, 1], t(1, 1));
+    assert_eq!(
+        vec![0xc7, 12, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        t(-1, 0)
+    );
+    assert_eq!(
+        Ok((15, Timestamp::from_secs(-1))),
+        Timestamp::unpack(&t(-1, 0))
+    );
+}
+
+#[test]
+fn nanos_out_of_range() {
+    assert_eq!(
+        Err(Error::InvalidExtension),
+        Timestamp::new(0, 1_000_000_000)
+    );
+    // the fields can't be set directly, so a timestamp that can't be unpacked is never packed
+    assert_eq!(
+        Err(Error::InvalidExtension),
+        Timestamp::new(0, 2_000_000_000)
+    );
+    assert_eq!(Err(Error::InvalidExtension), Timestamp::new(-1, u32::MAX));
+
+    let mut fixext8 = vec![0xd7, 0xff];
+    fixext8.extend((1_000_000_000u64 << 34).to_be_bytes());
+    assert_eq!(Err(Error::InvalidExtension), Timestamp::unpack(&fixext8));
+    assert_eq!(Err(Error::InvalidExtension), Extension::unpack(&fixext8));
+
+    let mut ext8 = vec![0xc7, 12, 0xff];
+    ext8.extend(u32::MAX.to_be_bytes());
+    ext8.extend(0i64.to_be_bytes());
+    assert_eq!(Err(Error::InvalidExtension), Timestamp::unpack(&ext8));
+    assert_eq!(
+        Err(Error::InvalidExtension),
+        Timestamp::unpack_iter(ext8.clone())
+    );
+    assert_eq!(Err(Error::InvalidExtension), Extension::unpack(&ext8));
+}
+
+#[test]
+fn compatible_with_extension() {
+    let d = Duration::new(1_700_000_000, 123);
+    let bytes = Extension::Timestamp(d).pack_to_vec();
+    let (_, t) = Timestamp::unpack(&bytes).unwrap();
+    assert_eq!(Timestamp::from(UNIX_EPOCH + d), t);
+    assert_eq!(bytes, t.pack_to_vec());
+    assert_eq!(
+        Err(Error::ExtensionTypeMismatch {
+            expected: -1,
+            found: 3
+        }),
+        Timestamp::unpack(&Extension::Ext(3, vec![0; 4]).pack_to_vec())
+    );
+}
+
+#[test]
+fn before_epoch() {
+    let at = UNIX_EPOCH - Duration::from_millis(1500);
+    let t = Timestamp::from(at);
+    assert_eq!((-2, 500_000_000), (t.secs(), t.nanos()));
+    assert_eq!(Ok(at), SystemTime::try_from(t));
+    utils::case(Event { id: 1, at });
+}
+
+#[test]
+fn derived_field() {
+    let at = UNIX_EPOCH + Duration::new(1_700_000_000, 5);
+    let event = Event { id: 7, at };
+    let raw = RawEvent {
+        id: 7,
+        at: Timestamp::from(at),
+    };
+    assert_eq!(raw.pack_to_vec(), event.pack_to_vec());
+    utils::case(event);
+    utils::case(Tick(at));
+}
+
+proptest! {
+    #[test]
+    fn timestamp(secs: i64, nanos in 0..Timestamp::NANOS_PER_SEC) {
+        utils::case(Timestamp::new(secs, nanos).unwrap());
+    }
+
+    #[test]
+    fn system_time(secs in -(1i64 << 40)..(1i64 << 40), nanos in 0..Timestamp::NANOS_PER_SEC) {
+        let t = Timestamp::new(secs, nanos).unwrap();
+        let at = SystemTime::try_from(t).unwrap();
+        prop_assert_eq!(t, Timestamp::from(at));
+        utils::case(Event { id: 0, at });
+    }
+}
@@ -0,0 +1,125 @@
+use msgpacker::encode::*;
+use msgpacker::prelude::*;
+use msgpacker::{Event, Token, Tokenizer};
+use proptest::prelude::*;
+
+/// Writes the tokens back with the encoder primitives, using the smallest forms.
+fn reencode(buf: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for event in Tokenizer::new(buf) {
+        match event.unwrap().token {
+            Token::Nil => write_nil(&mut out),
+            Token::Bool(b) => write_bool(&mut out, b),
+            Token::Uint(v) => write_uint(&mut out, v),
+            Token::Int(v) => write_int(&mut out, v),
+            Token::F32(v) => v.pack_canonical(&mut out),
+            Token::F64(v) => v.pack_canonical(&mut out),
+            Token::Str(s) => {
+                let n = write_str_header(&mut out, s.len() as u32);
+                out.extend(s.bytes());
+                n
+            }
+            Token::Bin(b) => {
+                let n = write_bin_header(&mut out, b.len() as u32);
+                out.extend(b);
+                n
+            }
+            Token::ArrayStart(len) => write_array_len(&mut out, len as u32),
+            Token::MapStart(len) => write_map_len(&mut out, len as u32),
+            Token::Ext(t, data) => ExtensionRef { ext_type: t, data }.pack(&mut out),
+        };
+    }
+    out
+}
+
+#[test]
+fn events_carry_offsets() {
+    let mut buf = Vec::new();
+    (vec![1u8, 2], "ab", Option::<u8>::None).pack(&mut buf);
+    write_map_len(&mut buf, 1);
+    (-40i32, 1.5f32, true).pack(&mut buf);
+    write_nil(&mut buf);
+    ExtensionRef {
+        ext_type: 3,
+        data: &[7; 4],
+    }
+    .pack(&mut buf);
+
+    let events: Vec<_> = Tokenizer::new(&buf).collect::<Result<_, _>>().unwrap();
+    let tokens: Vec<_> = events.iter().map(|e| (e.offset, e.token)).collect();
+    assert_eq!(
+        vec![
+            (0, Token::ArrayStart(2)),
+            (1, Token::Uint(1)),
+            (2, Token::Uint(2)),
+            (3, Token::Str("ab")),
+            (6, Token::Uint(0)),
+            (7, Token::MapStart(1)),
+            (8, Token::Int(-40)),
+            (10, Token::F32(1.5)),
+            (15, Token::Bool(true)),
+            (16, Token::Nil),
+            (17, Token::Ext(3, &[7; 4])),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn stops_after_error() {
+    let buf = [0x92, 0x01, 0xc1, 0x02];
+    let mut tokenizer = Tokenizer::new(&buf);
+    assert!(tokenizer.next().unwrap().is_ok());
+    assert!(tokenizer.next().unwrap().is_ok());
+    assert_eq!(Some(Err(Error::UnexpectedFormatTag)), tokenizer.next());
+    assert_eq!(None, tokenizer.next());
+    assert_eq!(2, tokenizer.offset());
+    assert_eq!(&buf[2..], tokenizer.remaining());
+
+    let buf = [Format::STR8, 4, b'a'];
+    assert_eq!(
+        vec![Err(Error::BufferTooShort)],
+        Tokenizer::new(&buf).collect::<Vec<_>>()
+    );
+    let buf = [0xa1, 0xff];
+    assert_eq!(
+        vec![Err(Error::InvalidUtf8)],
+        Tokenizer::new(&buf).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn bin_is_borrowed() {
+    let mut buf = Vec::new();
+    msgpacker::pack_bin(&mut buf, [1, 2, 3]);
+    assert_eq!(
+        Some(Ok(Event {
+            offset: 0,
+            token: Token::Bin(&buf[2..])
+        })),
+        Tokenizer::new(&buf).next()
+    );
+}
+
+proptest! {
+    #[test]
+    fn reencode_matches_canonical(x: Vec<(i64, String, Vec<u8>, Option<f64>)>, y: Vec<bool>) {
+        let mut buf = x.pack_to_vec();
+        let mut bin = Vec::new();
+        msgpacker::pack_bin(&mut bin, x.pack_to_vec());
+        buf.extend(bin);
+        y.pack(&mut buf);
+        assert_eq!(msgpacker::canonicalize(&buf).unwrap(), reencode(&buf));
+    }
+
+    #[test]
+    fn offsets_are_increasing(x: Vec<(String, u64)>) {
+        let buf = x.pack_to_vec();
+        let mut last = None;
+        for event in Tokenizer::new(&buf) {
+            let event = event.unwrap();
+            assert!(last.map(|l| l < event.offset).unwrap_or(event.offset == 0));
+            last = Some(event.offset);
+        }
+    }
+}
@@ -9,6 +9,10 @@ where
     let mut bytes = vec![];
     let n = x.pack(&mut bytes);
     assert_eq!(n, bytes.len());
+    assert_eq!(n, x.packed_len());
+    let mut slice = vec![0u8; n];
+    assert_eq!(Ok(n), msgpacker::pack_to_slice(&x, &mut slice));
+    assert_eq!(bytes, slice);
     let (o, y) = T::unpack(&bytes).unwrap();
     let (p, z) = T::unpack_iter(bytes).unwrap();
     assert_eq!(n, o);
@@ -0,0 +1,144 @@
+use msgpacker::prelude::*;
+use msgpacker::{DecimalStr, ExtInt};
+use proptest::prelude::*;
+use std::num::{NonZeroI128, NonZeroU128};
+
+mod utils;
+
+type Ext<T> = ExtInt<T, 5>;
+
+fn ext<T>(v: T) -> Ext<T> {
+    ExtInt(v)
+}
+
+#[test]
+fn ext_payload() {
+    let mut expected = vec![0xc7, 17, 5, 0x00];
+    expected.extend([0xff; 16]);
+    assert_eq!(expected, ext(u128::MAX).pack_to_vec());
+
+    let mut expected = vec![0xd8, 5, 0x80];
+    expected.extend([0x00; 15]);
+    assert_eq!(expected, ext(i128::MIN).pack_to_vec());
+
+    let mut expected = vec![0xc7, 9, 5, 0xff, 0x7f];
+    expected.extend([0xff; 7]);
+    assert_eq!(expected, ext(i64::MIN as i128 - 1).pack_to_vec());
+
+    let mut expected = vec![0xc7, 9, 5, 0x01];
+    expected.extend([0x00; 8]);
+    assert_eq!(expected, ext(u64::MAX as u128 + 1).pack_to_vec());
+}
+
+#[test]
+fn narrow_values_are_plain_integers() {
+    assert_eq!(u64::MAX.pack_to_vec(), ext(u64::MAX as u128).pack_to_vec());
+    assert_eq!(i64::MIN.pack_to_vec(), ext(i64::MIN as i128).pack_to_vec());
+    assert_eq!(vec![0x2a], DecimalStr(42u128).pack_to_vec());
+    assert_eq!(vec![0xff], DecimalStr(-1i128).pack_to_vec());
+}
+
+#[test]
+fn legacy_bin_is_accepted() {
+    let bytes = u128::MAX.pack_to_vec();
+    assert_eq!(Ok((18, ext(u128::MAX))), Ext::<u128>::unpack(&bytes));
+    assert_eq!(
+        Ok((18, DecimalStr(u128::MAX))),
+        DecimalStr::<u128>::unpack_iter(bytes)
+    );
+    let bytes = i128::MIN.pack_to_vec();
+    assert_eq!(Ok((18, ext(i128::MIN))), Ext::<i128>::unpack_iter(bytes));
+}
+
+#[test]
+fn out_of_range() {
+    let negative = ext(-1i128 << 100).pack_to_vec();
+    assert_eq!(Err(Error::IntegerOverflow), Ext::<u128>::unpack(&negative));
+    let wide = ext(u128::MAX).pack_to_vec();
+    assert_eq!(Err(Error::IntegerOverflow), Ext::<i128>::unpack(&wide));
+    assert_eq!(
+        Err(Error::ExtensionTypeMismatch {
+            expected: 6,
+            found: 5
+        }),
+        ExtInt::<u128, 6>::unpack(&wide)
+    );
+    assert_eq!(
+        Err(Error::IntegerOverflow),
+        Ext::<NonZeroU128>::unpack(&0u8.pack_to_vec())
+    );
+    assert_eq!(
+        Ok((1, ext(None))),
+        Ext::<Option<NonZeroU128>>::unpack(&0u8.pack_to_vec())
+    );
+}
+
+#[test]
+fn decimal_strings() {
+    assert_eq!(
+        "340282366920938463463374607431768211455".pack_to_vec(),
+        DecimalStr(u128::MAX).pack_to_vec()
+    );
+    assert_eq!(
+        "-170141183460469231731687303715884105728".pack_to_vec(),
+        DecimalStr(i128::MIN).pack_to_vec()
+    );
+    assert_eq!(
+        Ok((4, DecimalStr(-12i128))),
+        DecimalStr::unpack(&"-12".pack_to_vec())
+    );
+    assert_eq!(
+        Err(Error::InvalidDecimal),
+        DecimalStr::<u128>::unpack(&"12a".pack_to_vec())
+    );
+    assert_eq!(
+        Err(Error::InvalidDecimal),
+        DecimalStr::<u128>::unpack_iter("+12".pack_to_vec())
+    );
+    assert_eq!(
+        Err(Error::IntegerOverflow),
+        DecimalStr::<u128>::unpack(&"-12".pack_to_vec())
+    );
+    assert_eq!(
+        Err(Error::IntegerOverflow),
+        DecimalStr::<u128>::unpack(&"340282366920938463463374607431768211456".pack_to_vec())
+    );
+}
+
+proptest! {
+    #[test]
+    fn ext_u128(v: u128) {
+        utils::case(ext(v));
+    }
+
+    #[test]
+    fn ext_i128(v: i128) {
+        utils::case(ext(v));
+    }
+
+    #[test]
+    fn ext_nonzero(u: Option<NonZeroU128>, i: Option<NonZeroI128>) {
+        utils::case(ext(u));
+        utils::case(ext(i));
+        if let (Some(u), Some(i)) = (u, i) {
+            utils::case(ext(u));
+            utils::case(ext(i));
+        }
+    }
+
+    #[test]
+    fn decimal_u128(v: u128) {
+        utils::case(DecimalStr(v));
+    }
+
+    #[test]
+    fn decimal_i128(v: i128) {
+        utils::case(DecimalStr(v));
+    }
+
+    #[test]
+    fn decimal_nonzero(u: Option<NonZeroU128>, i: Option<NonZeroI128>) {
+        utils::case(DecimalStr(u));
+        utils::case(DecimalStr(i));
+    }
+}